@@ -0,0 +1,20 @@
+-- Add migration script here
+CREATE TABLE IF NOT EXISTS subscriptions (
+    user_id BIGINT NOT NULL,
+    quiz_id UUID NOT NULL,
+    PRIMARY KEY(user_id, quiz_id),
+    FOREIGN KEY(quiz_id) REFERENCES quizes(uuid)
+    ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS study_cards (
+    user_id BIGINT NOT NULL,
+    question_id UUID NOT NULL,
+    ease DOUBLE PRECISION NOT NULL,
+    interval_days INTEGER NOT NULL,
+    repetitions INTEGER NOT NULL,
+    due_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY(user_id, question_id),
+    FOREIGN KEY(question_id) REFERENCES questions(uuid)
+    ON DELETE CASCADE
+);
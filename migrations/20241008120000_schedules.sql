@@ -0,0 +1,17 @@
+-- Add migration script here
+CREATE TABLE IF NOT EXISTS reminders (
+    user_id BIGINT PRIMARY KEY NOT NULL,
+    time_of_day TIME NOT NULL,
+    utc_offset_minutes INTEGER NOT NULL,
+    next_fire_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS scheduled_pushes (
+    uuid UUID PRIMARY KEY NOT NULL,
+    quiz_id UUID NOT NULL,
+    chat_id BIGINT,
+    fire_at TIMESTAMPTZ NOT NULL,
+    sent BOOLEAN NOT NULL DEFAULT FALSE,
+    FOREIGN KEY(quiz_id) REFERENCES quizes(uuid)
+    ON DELETE CASCADE
+);
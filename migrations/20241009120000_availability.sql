@@ -0,0 +1,15 @@
+-- Add migration script here
+ALTER TABLE quizes ADD COLUMN IF NOT EXISTS opens_at TIMESTAMPTZ;
+ALTER TABLE quizes ADD COLUMN IF NOT EXISTS closes_at TIMESTAMPTZ;
+ALTER TABLE quizes ADD COLUMN IF NOT EXISTS max_attempts INTEGER;
+
+CREATE TABLE IF NOT EXISTS attempts (
+    uuid UUID PRIMARY KEY NOT NULL,
+    quiz_id UUID NOT NULL,
+    user_id BIGINT NOT NULL,
+    score INTEGER,
+    started_at TIMESTAMPTZ NOT NULL,
+    finished_at TIMESTAMPTZ,
+    FOREIGN KEY(quiz_id) REFERENCES quizes(uuid)
+    ON DELETE CASCADE
+);
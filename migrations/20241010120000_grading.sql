@@ -0,0 +1,15 @@
+-- Add migration script here
+ALTER TABLE quizes ADD COLUMN IF NOT EXISTS pass_mark_kind VARCHAR;
+ALTER TABLE quizes ADD COLUMN IF NOT EXISTS pass_mark DOUBLE PRECISION;
+
+CREATE TABLE IF NOT EXISTS grade_bands (
+    quiz_id UUID NOT NULL,
+    label VARCHAR NOT NULL,
+    min_percent DOUBLE PRECISION NOT NULL,
+    PRIMARY KEY(quiz_id, label),
+    FOREIGN KEY(quiz_id) REFERENCES quizes(uuid)
+    ON DELETE CASCADE
+);
+
+ALTER TABLE attempts ADD COLUMN IF NOT EXISTS passed BOOLEAN;
+ALTER TABLE attempts ADD COLUMN IF NOT EXISTS grade VARCHAR;
@@ -0,0 +1,6 @@
+-- Add migration script here
+ALTER TABLE questions ADD COLUMN IF NOT EXISTS points DOUBLE PRECISION NOT NULL DEFAULT 1;
+ALTER TABLE questions ADD COLUMN IF NOT EXISTS penalty DOUBLE PRECISION NOT NULL DEFAULT 0;
+
+ALTER TABLE attempts ALTER COLUMN score TYPE DOUBLE PRECISION;
+ALTER TABLE attempts ADD COLUMN IF NOT EXISTS max_score DOUBLE PRECISION;
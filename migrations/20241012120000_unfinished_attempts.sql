@@ -0,0 +1,4 @@
+-- Add migration script here
+ALTER TABLE attempts ADD COLUMN IF NOT EXISTS curr_idx INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE attempts ADD COLUMN IF NOT EXISTS progress JSONB NOT NULL DEFAULT '[]';
+ALTER TABLE attempts ADD COLUMN IF NOT EXISTS updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
@@ -0,0 +1,2 @@
+-- Add migration script here
+ALTER TABLE quizes ADD COLUMN IF NOT EXISTS native_polls BOOLEAN NOT NULL DEFAULT FALSE;
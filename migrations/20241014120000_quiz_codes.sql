@@ -0,0 +1,5 @@
+-- Add migration script here
+ALTER TABLE quizes ADD COLUMN IF NOT EXISTS code VARCHAR;
+UPDATE quizes SET code = UPPER(SUBSTR(REPLACE(uuid::text, '-', ''), 1, 8)) WHERE code IS NULL;
+ALTER TABLE quizes ALTER COLUMN code SET NOT NULL;
+CREATE UNIQUE INDEX IF NOT EXISTS quizes_code ON quizes(code);
@@ -0,0 +1,17 @@
+-- Add migration script here
+CREATE TABLE IF NOT EXISTS tags (
+    uuid UUID PRIMARY KEY NOT NULL,
+    name VARCHAR UNIQUE NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS quiz_tags (
+    quiz_id UUID NOT NULL,
+    tag_id UUID NOT NULL,
+    PRIMARY KEY(quiz_id, tag_id),
+    FOREIGN KEY(quiz_id) REFERENCES quizes(uuid)
+    ON DELETE CASCADE,
+    FOREIGN KEY(tag_id) REFERENCES tags(uuid)
+    ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS quiz_tags_tag_id ON quiz_tags(tag_id);
@@ -0,0 +1,3 @@
+-- Add migration script here
+-- Quizes created before statuses existed were already public.
+ALTER TABLE quizes ADD COLUMN IF NOT EXISTS status VARCHAR NOT NULL DEFAULT 'published';
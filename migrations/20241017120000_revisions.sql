@@ -0,0 +1,13 @@
+-- Add migration script here
+ALTER TABLE quizes ADD COLUMN IF NOT EXISTS revision INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE attempts ADD COLUMN IF NOT EXISTS revision INTEGER NOT NULL DEFAULT 1;
+
+CREATE TABLE IF NOT EXISTS quiz_revisions (
+    quiz_id UUID NOT NULL,
+    revision INTEGER NOT NULL,
+    snapshot JSONB NOT NULL,
+    saved_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY(quiz_id, revision),
+    FOREIGN KEY(quiz_id) REFERENCES quizes(uuid)
+    ON DELETE CASCADE
+);
@@ -0,0 +1,3 @@
+-- Add migration script here
+ALTER TABLE quizes ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
+ALTER TABLE questions ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
@@ -0,0 +1,10 @@
+-- Add migration script here
+CREATE TABLE IF NOT EXISTS operations (
+    id BIGSERIAL PRIMARY KEY,
+    user_id BIGINT NOT NULL,
+    quiz_id UUID NOT NULL,
+    operation JSONB NOT NULL,
+    done_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    FOREIGN KEY(quiz_id) REFERENCES quizes(uuid)
+    ON DELETE CASCADE
+);
@@ -0,0 +1,2 @@
+-- Add migration script here
+ALTER TABLE quizes ADD COLUMN IF NOT EXISTS revision_pending BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,12 @@
+-- Reminders keep a time zone instead of a fixed offset, so that they follow daylight saving time.
+ALTER TABLE reminders ADD COLUMN IF NOT EXISTS time_zone TEXT NOT NULL DEFAULT 'UTC';
+
+-- Whole-hour offsets become the matching Etc zone (its sign is inverted), others have none and fall back to UTC.
+UPDATE reminders SET time_zone = CASE
+    WHEN utc_offset_minutes = 0 OR utc_offset_minutes % 60 <> 0 THEN 'UTC'
+    WHEN utc_offset_minutes > 0 THEN 'Etc/GMT-' || (utc_offset_minutes / 60)
+    ELSE 'Etc/GMT+' || (-utc_offset_minutes / 60)
+END;
+
+ALTER TABLE reminders DROP COLUMN utc_offset_minutes;
+ALTER TABLE reminders ALTER COLUMN time_zone DROP DEFAULT;
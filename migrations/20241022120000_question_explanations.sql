@@ -0,0 +1,2 @@
+-- Add migration script here
+ALTER TABLE questions ADD COLUMN IF NOT EXISTS explanation TEXT;
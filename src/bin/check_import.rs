@@ -0,0 +1,32 @@
+//! Checks a GIFT or Aiken question bank before it is uploaded to the bot:
+//! prints the questions it would import and every problem found.
+
+use std::{env, fs, process::ExitCode};
+
+use rustquizbot::formats;
+
+fn main() -> ExitCode {
+    let Some(path) = env::args().nth(1) else {
+        eprintln!("Usage: check_import <file.gift|file.aiken|file.txt>");
+        return ExitCode::FAILURE;
+    };
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) => {
+            eprintln!("Can't read {}: {}", path, e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let import = formats::parse(&path, &text);
+    for (i, question) in import.questions().iter().enumerate() {
+        print!("{}. {}", i + 1, question);
+    }
+    eprint!("{}", import);
+
+    if import.questions().is_empty() {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
@@ -1,9 +1,22 @@
+use std::sync::Arc;
+
 use teloxide::{
     payloads::SendMessageSetters, prelude::Requester, types::Message, utils::command::BotCommands,
     Bot,
 };
 
-use crate::{keyboard::action_keyboard, state::QuizState, HandlerResult, UserDialogue};
+use crate::{
+    database::{
+        attempt::ATTEMPT_EXPIRY,
+        connection::{Attempts, Schedule},
+        schedule::Reminder,
+    },
+    keyboard::{action_keyboard, with_resume},
+    poll::{PollMode, PollRegistry},
+    scheduler::{next_daily_fire, parse_reminder},
+    state::QuizState,
+    HandlerResult, UserDialogue,
+};
 
 #[derive(Debug, Clone, BotCommands)]
 #[command(rename_rule = "lowercase")]
@@ -13,9 +26,27 @@ pub enum Command {
     #[command(description = "start bot")]
     Cancel,
     #[command(description = "start the bot")]
-    Start,
-    #[command(description = "retutning back(only works in editor)")]
+    Start(String),
+    #[command(description = "go one step back in the editor or while creating a quiz")]
     Back,
+    #[command(description = "undo your last change to the edited quiz")]
+    Undo,
+    #[command(description = "review due questions of subscribed quizes")]
+    Study,
+    #[command(description = "subscribe to a quiz for studying (or unsubscribe)")]
+    Subscribe,
+    #[command(
+        description = "set a daily reminder, e.g. /remind 08:30 Europe/Berlin, or /remind off"
+    )]
+    Remind(String),
+    #[command(description = "start a quiz for everyone in a group chat, e.g. /group My quiz")]
+    Group(String),
+    #[command(description = "stop the group quiz running in this chat (host or admins)")]
+    Stop,
+    #[command(description = "open a live quiz lobby for players to join, e.g. /host My quiz")]
+    Host(String),
+    #[command(description = "join a live quiz by its code, e.g. /join AB12CD")]
+    Join(String),
 }
 
 pub(crate) async fn help(bot: Bot, msg: Message) -> HandlerResult {
@@ -24,16 +55,87 @@ pub(crate) async fn help(bot: Bot, msg: Message) -> HandlerResult {
     Ok(())
 }
 
-pub(crate) async fn cancel(bot: Bot, dialogue: UserDialogue, msg: Message) -> HandlerResult {
+pub(crate) async fn cancel(
+    bot: Bot,
+    dialogue: UserDialogue,
+    msg: Message,
+    polls: PollRegistry,
+) -> HandlerResult {
     bot.send_message(msg.chat.id, "Cancelling dialogue").await?;
+    if let Some(QuizState::Running { .. }) = dialogue.get().await? {
+        polls.forget(msg.chat.id, PollMode::Private);
+        bot.send_message(
+            msg.chat.id,
+            "Your progress is saved. Use /start and choose 'Continue unfinished quiz⏯️' to resume.",
+        )
+        .await?;
+    }
     dialogue.update(QuizState::Start).await?;
     Ok(())
 }
 
-pub(crate) async fn start(bot: Bot, msg: Message, dialogue: UserDialogue) -> HandlerResult {
+pub(crate) async fn start<Connect: Attempts>(
+    bot: Bot,
+    msg: Message,
+    dialogue: UserDialogue,
+    connection: Arc<Connect>,
+) -> HandlerResult {
+    let mut keyboard = action_keyboard(msg.chat.username().unwrap());
+    if connection
+        .retreive_unfinished_attempt(msg.chat.id.0, chrono::Utc::now() - ATTEMPT_EXPIRY)
+        .await?
+        .is_some()
+    {
+        keyboard = with_resume(keyboard);
+    }
+
     bot.send_message(msg.chat.id, "Please choose what to do:")
-        .reply_markup(action_keyboard(msg.chat.username().unwrap()))
+        .reply_markup(keyboard)
         .await?;
     dialogue.update(QuizState::Start).await?;
     Ok(())
 }
+
+pub(crate) async fn remind<Connect: Schedule>(
+    bot: Bot,
+    msg: Message,
+    args: String,
+    connection: Arc<Connect>,
+) -> HandlerResult {
+    if args.trim() == "off" {
+        let text = if connection.remove_reminder(msg.chat.id.0).await? {
+            "Daily reminder turned off."
+        } else {
+            "You have no daily reminder."
+        };
+        bot.send_message(msg.chat.id, text).await?;
+        return Ok(());
+    }
+
+    match parse_reminder(&args) {
+        Some((time, time_zone)) => {
+            let next_fire_at = next_daily_fire(&time, &time_zone, chrono::Utc::now());
+            connection
+                .set_reminder(&Reminder::new(msg.chat.id.0, time, time_zone, next_fire_at))
+                .await?;
+            bot.send_message(
+                msg.chat.id,
+                format!(
+                    "OK. I will remind you every day at {} ({}).",
+                    time.format("%H:%M"),
+                    time_zone
+                ),
+            )
+            .await?;
+        }
+        None => {
+            bot.send_message(
+                msg.chat.id,
+                "Please, use the format /remind HH:MM [time zone], e.g. /remind 08:30 Europe/Berlin.",
+            )
+            .await?;
+        }
+    }
+
+    Ok(())
+}
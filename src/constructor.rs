@@ -1,15 +1,20 @@
-use crate::database::connection::{CreateQuiz, RetreiveQuiz};
+use crate::database::connection::{CreateQuiz, RetreiveQuiz, Revisions, Trash};
 use crate::database::quiz::{Answer, Question, Quiz};
-use crate::keyboard::{action_keyboard, yes_no_keyboard};
-use crate::state::{QuizData, QuizState};
+use crate::database::tag::parse_tags;
+use crate::database::validator::MAX_MESSAGE_LEN;
+use crate::editor;
+use crate::formats::{self, Import};
+use crate::keyboard::{action_keyboard, no_tags_keyboard, review_keyboard, yes_no_keyboard};
+use crate::state::{QuizData, QuizState, ReviewAction};
 use crate::{HandlerResult, UserDialogue};
 use std::sync::Arc;
-use teloxide::types::ReplyMarkup;
+use teloxide::net::Download;
+use teloxide::types::{ChatId, ReplyMarkup};
 use teloxide::{payloads::SendMessageSetters, prelude::Requester, types::Message, Bot};
 use tracing::instrument;
 
 #[instrument(level = "info", skip(connection, bot, dialogue))]
-pub(crate) async fn receive_quiz_description<DbConnection: RetreiveQuiz>(
+pub(crate) async fn receive_quiz_description<DbConnection: RetreiveQuiz + Trash>(
     bot: Bot,
     dialogue: UserDialogue,
     msg: Message,
@@ -20,6 +25,12 @@ pub(crate) async fn receive_quiz_description<DbConnection: RetreiveQuiz>(
             if let Ok(Some(_)) = connection.retreive_quiz(title).await {
                 bot.send_message(msg.chat.id, "Quiz with already exists. Try again.")
                     .await?;
+            } else if connection.is_quiz_trashed(title).await? {
+                bot.send_message(
+                    msg.chat.id,
+                    "Quiz with this name is in the trash. Restore it or try another name.",
+                )
+                .await?;
             } else {
                 bot.send_message(msg.chat.id, "OK. What is new quiz about?")
                     .await?;
@@ -49,16 +60,17 @@ pub(crate) async fn receive_quiz_author(
         Some(description) => {
             bot.send_message(
                 msg.chat.id,
-                "Do you want to add the first question?(Yes/No)",
+                "Which topics is it about? Send tags separated by commas, e.g. math, algebra, or choose 'No tags'.",
             )
-            .reply_markup(yes_no_keyboard())
+            .reply_markup(no_tags_keyboard())
             .await?;
             dialogue
-                .update(QuizState::ReceiveQuizAuthor {
+                .update(QuizState::ReceiveQuizTags {
                     quiz_info: QuizData {
                         quiz_name: title,
                         description: description.to_owned(),
                         author: msg.chat.username().unwrap().to_owned(),
+                        tags: Vec::default(),
                         questions: Vec::default(),
                     },
                 })
@@ -73,13 +85,51 @@ pub(crate) async fn receive_quiz_author(
     Ok(())
 }
 
-#[instrument(level = "info", skip(connection, bot, dialogue))]
-pub(crate) async fn receive_new_question<DbConnection: CreateQuiz>(
+#[instrument(level = "info", skip(dialogue, bot))]
+pub(crate) async fn receive_quiz_tags(
+    bot: Bot,
+    dialogue: UserDialogue,
+    msg: Message,
+    mut quiz_info: QuizData,
+) -> HandlerResult {
+    let tags = match msg.text() {
+        Some("No tags") => Some(vec![]),
+        Some(text) => parse_tags(text),
+        None => None,
+    };
+
+    match tags {
+        Some(tags) => {
+            quiz_info.tags = tags;
+            bot.send_message(
+                msg.chat.id,
+                "Do you want to add the first question?(Yes/No)",
+            )
+            .reply_markup(yes_no_keyboard())
+            .await?;
+            dialogue
+                .update(QuizState::ReceiveQuizAuthor { quiz_info })
+                .await?;
+        }
+        None => {
+            bot.send_message(
+                msg.chat.id,
+                "Please, send short tags separated by commas or choose 'No tags'.",
+            )
+            .reply_markup(no_tags_keyboard())
+            .await?;
+        }
+    }
+
+    Ok(())
+}
+
+#[instrument(level = "info", skip(bot, dialogue))]
+pub(crate) async fn receive_new_question(
     bot: Bot,
     dialogue: UserDialogue,
     msg: Message,
     quiz_info: QuizData,
-    connection: Arc<DbConnection>,
 ) -> HandlerResult {
     match msg.text() {
         Some("Yes") | Some("Yes✔️") => {
@@ -91,25 +141,7 @@ pub(crate) async fn receive_new_question<DbConnection: CreateQuiz>(
                 .await?;
         }
         Some("No") | Some("No❌") => {
-            let new_quiz = Quiz::new(
-                quiz_info.quiz_name,
-                quiz_info.description,
-                quiz_info.author,
-                Some(quiz_info.questions),
-            );
-
-            let quiz_name = connection.create_quiz(new_quiz).await?;
-            bot.send_message(
-                msg.chat.id,
-                format!(
-                    "OK. Saving quiz {}. What do you want to do next?",
-                    quiz_name
-                ),
-            )
-            .reply_markup(action_keyboard(msg.chat.username().unwrap()))
-            .await?;
-
-            dialogue.exit().await?;
+            show_review(&bot, msg.chat.id, &dialogue, quiz_info).await?;
         }
         _ => {
             bot.send_message(
@@ -280,3 +312,426 @@ pub(crate) async fn receive_add_new_answer(
 
     Ok(())
 }
+
+// Draft with numbered questions and answers, so that the author can pick them.
+fn review_text(quiz_info: &QuizData) -> String {
+    let mut text = format!(
+        "Please, review the quiz before saving.\n\n{}\n{}\n",
+        quiz_info.quiz_name, quiz_info.description
+    );
+    if !quiz_info.tags.is_empty() {
+        text.push_str(&format!("Tags: {}\n", quiz_info.tags.join(", ")));
+    }
+    text.push('\n');
+    if quiz_info.questions.is_empty() {
+        text.push_str("No questions yet.\n");
+    }
+    for (i, question) in quiz_info.questions.iter().enumerate() {
+        text.push_str(&format!("{}. {}\n", i + 1, question.text()));
+        for (j, answer) in question.answers().iter().enumerate() {
+            text.push_str(&format!("    {}.{} {}\n", i + 1, j + 1, answer));
+        }
+    }
+
+    text
+}
+
+// Splits the text at line ends into messages Telegram accepts, cutting lines longer than one.
+fn split_message(text: &str) -> Vec<String> {
+    let mut messages = Vec::new();
+    let mut message = String::new();
+    let mut len = 0;
+    for line in text.split_inclusive('\n') {
+        let chars: Vec<char> = line.chars().collect();
+        for piece in chars.chunks(MAX_MESSAGE_LEN) {
+            if len + piece.len() > MAX_MESSAGE_LEN {
+                messages.push(std::mem::take(&mut message));
+                len = 0;
+            }
+            message.extend(piece);
+            len += piece.len();
+        }
+    }
+    messages.push(message);
+    // Telegram refuses blank messages.
+    messages.retain(|message| !message.trim().is_empty());
+
+    messages
+}
+
+/// Shows the draft with the review options.
+pub(crate) async fn show_review(
+    bot: &Bot,
+    chat_id: ChatId,
+    dialogue: &UserDialogue,
+    quiz_info: QuizData,
+) -> HandlerResult {
+    // A long draft takes several messages, the options go under the last one.
+    let mut messages = split_message(&review_text(&quiz_info));
+    let last = messages.pop().unwrap_or_default();
+    for message in messages {
+        bot.send_message(chat_id, message).await?;
+    }
+    bot.send_message(chat_id, last)
+        .reply_markup(review_keyboard())
+        .await?;
+    dialogue.update(QuizState::ReviewQuiz { quiz_info }).await?;
+
+    Ok(())
+}
+
+#[instrument(level = "info", skip(connection, bot, dialogue))]
+pub(crate) async fn review_quiz<DbConnection: CreateQuiz>(
+    bot: Bot,
+    dialogue: UserDialogue,
+    msg: Message,
+    quiz_info: QuizData,
+    connection: Arc<DbConnection>,
+) -> HandlerResult {
+    let (action, prompt) = match msg.text() {
+        Some("Save quiz💾") => {
+            let mut new_quiz = Quiz::new(
+                quiz_info.quiz_name,
+                quiz_info.description,
+                quiz_info.author,
+                Some(quiz_info.questions),
+            );
+            new_quiz.set_tags(quiz_info.tags);
+
+            let quiz_name = connection.create_quiz(new_quiz).await?;
+            bot.send_message(
+                msg.chat.id,
+                format!(
+                    "OK. Saving quiz {} as a draft. Publish it from the editor once it's ready. What do you want to do next?",
+                    quiz_name
+                ),
+            )
+            .reply_markup(action_keyboard(msg.chat.username().unwrap()))
+            .await?;
+
+            dialogue.exit().await?;
+            return Ok(());
+        }
+        Some("Add question") => {
+            bot.send_message(msg.chat.id, "Great. Please enter a question.")
+                .reply_markup(ReplyMarkup::kb_remove())
+                .await?;
+            dialogue
+                .update(QuizState::ReceiveNewQuestion { quiz_info })
+                .await?;
+            return Ok(());
+        }
+        Some("Edit text✏️") => (
+            ReviewAction::EditText,
+            "Send the number of the question, e.g. 2, or of the answer, e.g. 2.1, to edit.",
+        ),
+        Some("Toggle correct✅") => (
+            ReviewAction::ToggleCorrectness,
+            "Send the number of the answer, e.g. 2.1, to mark it correct or incorrect.",
+        ),
+        Some("Move↕️") => (
+            ReviewAction::Move,
+            "Send the number of the question or answer and its new position, e.g. 3 1 or 2.3 1.",
+        ),
+        Some("Delete🗑️") => (
+            ReviewAction::Delete,
+            "Send the number of the question, e.g. 2, or of the answer, e.g. 2.1, to delete.",
+        ),
+        _ => {
+            bot.send_message(msg.chat.id, "Please, choose an option below.")
+                .reply_markup(review_keyboard())
+                .await?;
+            return Ok(());
+        }
+    };
+
+    bot.send_message(msg.chat.id, prompt)
+        .reply_markup(ReplyMarkup::kb_remove())
+        .await?;
+    dialogue
+        .update(QuizState::ReviewPick { quiz_info, action })
+        .await?;
+
+    Ok(())
+}
+
+// Parses `2` as the second question and `2.1` as its first answer.
+fn parse_item(text: &str, quiz_info: &QuizData) -> Option<(usize, Option<usize>)> {
+    let (question, answer) = match text.split_once('.') {
+        Some((question, answer)) => (question, Some(answer)),
+        None => (text, None),
+    };
+    let question_idx = question.parse::<usize>().ok()?.checked_sub(1)?;
+    let question = quiz_info.questions.get(question_idx)?;
+
+    match answer {
+        Some(answer) => {
+            let answer_idx = answer.parse::<usize>().ok()?.checked_sub(1)?;
+            (answer_idx < question.answers().len()).then_some((question_idx, Some(answer_idx)))
+        }
+        None => Some((question_idx, None)),
+    }
+}
+
+// Moves the item to the 1-based position, returns `false` if there is no such position.
+fn move_item<T>(items: &mut Vec<T>, from: usize, position: usize) -> bool {
+    if position == 0 || position > items.len() {
+        return false;
+    }
+    let item = items.remove(from);
+    items.insert(position - 1, item);
+    true
+}
+
+#[instrument(level = "info", skip(bot, dialogue))]
+pub(crate) async fn review_pick(
+    bot: Bot,
+    dialogue: UserDialogue,
+    msg: Message,
+    (mut quiz_info, action): (QuizData, ReviewAction),
+) -> HandlerResult {
+    let mut words = msg.text().unwrap_or_default().split_whitespace();
+    let Some((question_idx, answer_idx)) =
+        words.next().and_then(|item| parse_item(item, &quiz_info))
+    else {
+        bot.send_message(msg.chat.id, "No such question or answer. Try again.")
+            .await?;
+        return Ok(());
+    };
+
+    let question = &mut quiz_info.questions[question_idx];
+    match (action, answer_idx) {
+        (ReviewAction::EditText, _) => {
+            bot.send_message(msg.chat.id, "OK. Send the new text.")
+                .await?;
+            dialogue
+                .update(QuizState::ReviewEditText {
+                    quiz_info,
+                    question_idx,
+                    answer_idx,
+                })
+                .await?;
+            return Ok(());
+        }
+        (ReviewAction::ToggleCorrectness, Some(answer_idx)) => {
+            let answer = &mut question.answers_mut()[answer_idx];
+            answer.set_correct(!answer.is_correct());
+        }
+        (ReviewAction::ToggleCorrectness, None) => {
+            bot.send_message(
+                msg.chat.id,
+                "Please, send the number of an answer, e.g. 2.1.",
+            )
+            .await?;
+            return Ok(());
+        }
+        (ReviewAction::Move, answer_idx) => {
+            let moved = match (
+                words.next().and_then(|word| word.parse::<usize>().ok()),
+                answer_idx,
+            ) {
+                (Some(position), Some(answer_idx)) => {
+                    move_item(question.answers_mut(), answer_idx, position)
+                }
+                (Some(position), None) => {
+                    move_item(&mut quiz_info.questions, question_idx, position)
+                }
+                (None, _) => false,
+            };
+            if !moved {
+                bot.send_message(
+                    msg.chat.id,
+                    "Please, send the item and a valid new position, e.g. 3 1 or 2.3 1.",
+                )
+                .await?;
+                return Ok(());
+            }
+        }
+        (ReviewAction::Delete, Some(answer_idx)) => {
+            question.answers_mut().remove(answer_idx);
+        }
+        (ReviewAction::Delete, None) => {
+            quiz_info.questions.remove(question_idx);
+        }
+    }
+
+    show_review(&bot, msg.chat.id, &dialogue, quiz_info).await
+}
+
+#[instrument(level = "info", skip(bot, dialogue))]
+pub(crate) async fn review_edit_text(
+    bot: Bot,
+    dialogue: UserDialogue,
+    msg: Message,
+    (mut quiz_info, question_idx, answer_idx): (QuizData, usize, Option<usize>),
+) -> HandlerResult {
+    let Some(text) = msg.text() else {
+        bot.send_message(msg.chat.id, "Please, send the new text.")
+            .await?;
+        return Ok(());
+    };
+
+    let question = &mut quiz_info.questions[question_idx];
+    match answer_idx {
+        Some(answer_idx) => question.answers_mut()[answer_idx].set_text(text.to_owned()),
+        None => question.set_text(text.to_owned()),
+    }
+
+    show_review(&bot, msg.chat.id, &dialogue, quiz_info).await
+}
+
+/// Largest question bank accepted for import, in bytes.
+const MAX_IMPORT_SIZE: u32 = 1024 * 1024;
+/// Import problems listed in the chat, the rest are only counted.
+const MAX_LISTED_PROBLEMS: usize = 20;
+
+fn import_report(import: &Import) -> String {
+    let mut report = format!(
+        "Found {} question(s) and {} problem(s).\n",
+        import.questions().len(),
+        import.problems().len()
+    );
+    for problem in import.problems().iter().take(MAX_LISTED_PROBLEMS) {
+        report.push_str(&format!("{}\n", problem));
+    }
+    if import.problems().len() > MAX_LISTED_PROBLEMS {
+        report.push_str(&format!(
+            "...and {} more.",
+            import.problems().len() - MAX_LISTED_PROBLEMS
+        ));
+    }
+
+    report
+}
+
+/// Creates a draft quiz from an uploaded GIFT or Aiken file named after it.
+#[instrument(level = "info", skip(connection, bot, dialogue))]
+pub(crate) async fn receive_import<DbConnection: CreateQuiz + RetreiveQuiz + Revisions + Trash>(
+    bot: Bot,
+    dialogue: UserDialogue,
+    msg: Message,
+    connection: Arc<DbConnection>,
+) -> HandlerResult {
+    let Some(document) = msg.document() else {
+        bot.send_message(msg.chat.id, "Please, send the questions as a file.")
+            .await?;
+        return Ok(());
+    };
+    if document.file.size > MAX_IMPORT_SIZE {
+        bot.send_message(msg.chat.id, "The file is too large, the limit is 1 MB.")
+            .await?;
+        return Ok(());
+    }
+
+    let file_name = document
+        .file_name
+        .clone()
+        .unwrap_or_else(|| "Imported quiz".to_owned());
+    let quiz_name = file_name
+        .rsplit_once('.')
+        .map_or(file_name.as_str(), |(stem, _)| stem)
+        .trim()
+        .to_owned();
+    if quiz_name.is_empty() {
+        bot.send_message(
+            msg.chat.id,
+            "The file name is used as the quiz name and can't be empty. Rename the file and send it again.",
+        )
+        .await?;
+        return Ok(());
+    }
+    if connection.retreive_quiz(&quiz_name).await?.is_some()
+        || connection.is_quiz_trashed(&quiz_name).await?
+    {
+        bot.send_message(
+            msg.chat.id,
+            format!(
+                "Quiz '{}' already exists or is in the trash. Rename the file and send it again.",
+                quiz_name
+            ),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let file = bot.get_file(document.file.id.clone()).await?;
+    let mut content = Vec::new();
+    bot.download_file(&file.path, &mut content).await?;
+    let Ok(text) = String::from_utf8(content) else {
+        bot.send_message(msg.chat.id, "The file is not a UTF-8 text file.")
+            .await?;
+        return Ok(());
+    };
+
+    let import = formats::parse(&file_name, &text);
+    bot.send_message(msg.chat.id, import_report(&import))
+        .await?;
+    if import.questions().is_empty() {
+        bot.send_message(
+            msg.chat.id,
+            "Nothing to import. Fix the file and send it again.",
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let new_quiz = Quiz::new(
+        quiz_name,
+        format!("Imported from {}.", file_name),
+        msg.chat.username().unwrap_or_default().to_owned(),
+        Some(import.into_questions()),
+    );
+    let quiz_name = connection.create_quiz(new_quiz).await?;
+    bot.send_message(
+        msg.chat.id,
+        format!("Saved quiz '{}' as a draft.", quiz_name),
+    )
+    .await?;
+
+    editor::open_quiz(
+        &bot,
+        msg.chat.id,
+        &dialogue,
+        &quiz_name,
+        connection.as_ref(),
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_text_is_one_message() {
+        assert_eq!(
+            split_message("Quiz\n1. Question\n"),
+            ["Quiz\n1. Question\n"]
+        );
+    }
+
+    #[test]
+    fn long_text_is_split_at_line_ends() {
+        let line = format!("{}\n", "a".repeat(99));
+        let text = line.repeat(100);
+
+        let messages = split_message(&text);
+
+        assert_eq!(messages.len(), 3);
+        assert!(messages
+            .iter()
+            .all(|message| message.chars().count() <= MAX_MESSAGE_LEN && message.ends_with('\n')));
+        assert_eq!(messages.concat(), text);
+    }
+
+    #[test]
+    fn overlong_line_is_cut() {
+        let text = "é".repeat(MAX_MESSAGE_LEN + 1);
+
+        let messages = split_message(&text);
+
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].chars().count(), MAX_MESSAGE_LEN);
+        assert_eq!(messages.concat(), text);
+    }
+}
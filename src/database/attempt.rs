@@ -0,0 +1,82 @@
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Attempts left alone for longer than that are closed out with the score so far.
+pub const ATTEMPT_EXPIRY: Duration = Duration::hours(24);
+
+/// What the taker did with a question of a running quiz.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum QuestionProgress {
+    Unanswered,
+    Answered { points: f64 },
+}
+
+/// Attempt that was started but never finished.
+#[derive(Debug, Clone)]
+pub struct UnfinishedAttempt {
+    uuid: Uuid,
+    user_id: i64,
+    quiz_name: String,
+    revision: i32,
+    curr_idx: usize,
+    progress: Vec<QuestionProgress>,
+}
+
+pub fn score(progress: &[QuestionProgress]) -> f64 {
+    progress
+        .iter()
+        .map(|progress| match progress {
+            QuestionProgress::Answered { points } => *points,
+            QuestionProgress::Unanswered => 0.0,
+        })
+        .sum()
+}
+
+impl UnfinishedAttempt {
+    pub fn retreive(
+        uuid: Uuid,
+        user_id: i64,
+        quiz_name: String,
+        revision: i32,
+        curr_idx: usize,
+        progress: Vec<QuestionProgress>,
+    ) -> Self {
+        Self {
+            uuid,
+            user_id,
+            quiz_name,
+            revision,
+            curr_idx,
+            progress,
+        }
+    }
+
+    pub fn uuid(&self) -> &Uuid {
+        &self.uuid
+    }
+
+    pub fn user_id(&self) -> i64 {
+        self.user_id
+    }
+
+    pub fn quiz_name(&self) -> &String {
+        &self.quiz_name
+    }
+
+    /// Revision of the quiz the attempt was started on.
+    pub fn revision(&self) -> i32 {
+        self.revision
+    }
+
+    pub fn curr_idx(&self) -> usize {
+        self.curr_idx
+    }
+
+    /// Progress fitted to `len` questions of the revision the attempt was started on.
+    pub fn progress(&self, len: usize) -> Vec<QuestionProgress> {
+        let mut progress = self.progress.clone();
+        progress.resize(len, QuestionProgress::Unanswered);
+        progress
+    }
+}
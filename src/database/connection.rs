@@ -1,8 +1,20 @@
 use std::{borrow::Cow, error::Error};
 
-use sqlx::postgres::PgPool;
-
-use super::quiz::{Answer, Question, Quiz};
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use sqlx::{postgres::PgPool, Acquire, PgConnection};
+use uuid::Uuid;
+
+use super::{
+    attempt::{QuestionProgress, UnfinishedAttempt},
+    grading::{GradeBand, Grading, Outcome, PassMark},
+    operation::{Operation, Undo},
+    page::{contains_pattern, Page, PAGE_SIZE},
+    quiz::{quiz_code, Answer, Availability, Question, Quiz, Status},
+    revision::Revision,
+    schedule::{Reminder, ScheduledPush},
+    study::StudyCard,
+};
 
 pub(crate) struct Connection {
     pool: PgPool,
@@ -32,6 +44,45 @@ type GenericError = Result<String, Box<dyn Error + Send + Sync>>;
 
 pub(crate) trait CreateQuiz {
     async fn create_quiz(&self, quiz: Quiz) -> GenericError;
+
+    /// Copies the quiz with its questions and answers as a new draft, returning the new name.
+    async fn duplicate_quiz(&self, id: impl Into<String>) -> GenericError;
+}
+
+/// Deleted quizes and questions stay in the trash until purged.
+pub(crate) trait Trash {
+    async fn retreive_trashed_quiz_names_page(
+        &self,
+        query: &str,
+        page: i64,
+    ) -> Result<Page, Box<dyn Error + Send + Sync>>;
+
+    async fn retreive_trashed_question_names_page(
+        &self,
+        id_quiz: impl Into<String>,
+        query: &str,
+        page: i64,
+    ) -> Result<Page, Box<dyn Error + Send + Sync>>;
+
+    /// Whether a quiz with exactly this name is in the trash.
+    async fn is_quiz_trashed(
+        &self,
+        id: impl Into<String>,
+    ) -> Result<bool, Box<dyn Error + Send + Sync>>;
+
+    async fn restore_quiz(&self, id: impl Into<String>) -> GenericError;
+
+    async fn restore_question(
+        &self,
+        quiz_id: impl Into<String>,
+        question_id: impl Into<String>,
+    ) -> GenericError;
+
+    /// Deletes for good everything trashed before `deleted_before`.
+    async fn purge_trash(
+        &self,
+        deleted_before: DateTime<Utc>,
+    ) -> Result<u64, Box<dyn Error + Send + Sync>>;
 }
 
 pub(crate) trait DeleteQuiz {
@@ -61,7 +112,36 @@ pub(crate) trait RetreiveQuiz {
         id: impl Into<String>,
     ) -> Result<Option<Quiz>, Box<dyn Error + Send + Sync>>;
 
-    async fn retreive_all_quiz_names(&self) -> Result<Vec<String>, Box<dyn Error + Send + Sync>>;
+    /// Page of quiz names containing `query`, limited to quizes tagged with `tag`,
+    /// in `status` and open at `open_at` if given.
+    async fn retreive_quiz_names_page(
+        &self,
+        query: &str,
+        tag: Option<&str>,
+        status: Option<Status>,
+        open_at: Option<DateTime<Utc>>,
+        page: i64,
+    ) -> Result<Page, Box<dyn Error + Send + Sync>>;
+
+    /// Page of tags containing `query` that have a published quiz, open at `open_at` if given.
+    async fn retreive_tags_page(
+        &self,
+        query: &str,
+        open_at: Option<DateTime<Utc>>,
+        page: i64,
+    ) -> Result<Page, Box<dyn Error + Send + Sync>>;
+
+    async fn retreive_quiz_name_by_code(
+        &self,
+        code: &str,
+    ) -> Result<Option<String>, Box<dyn Error + Send + Sync>>;
+
+    /// Quizes whose name or description contains `query`, without their questions.
+    async fn search_quizes(
+        &self,
+        query: &str,
+        limit: i64,
+    ) -> Result<Vec<Quiz>, Box<dyn Error + Send + Sync>>;
 }
 
 pub(crate) trait EditQuiz {
@@ -70,6 +150,20 @@ pub(crate) trait EditQuiz {
     async fn edit_description(&self, id: impl Into<String>, new: impl Into<String>)
         -> GenericError;
 
+    async fn edit_availability(
+        &self,
+        id: impl Into<String>,
+        availability: &Availability,
+    ) -> GenericError;
+
+    async fn edit_grading(&self, id: impl Into<String>, grading: &Grading) -> GenericError;
+
+    async fn edit_delivery(&self, id: impl Into<String>, native_polls: bool) -> GenericError;
+
+    async fn edit_status(&self, id: impl Into<String>, status: Status) -> GenericError;
+
+    async fn edit_tags(&self, id: impl Into<String>, tags: &[String]) -> GenericError;
+
     // async fn add_question(&self, new: Question) -> GenericError;
 
     // async fn delete_question(&self, id: impl Into<String>) -> GenericError;
@@ -82,10 +176,13 @@ pub(crate) trait RetreiveQuestion {
         id_question: impl Into<String>,
     ) -> Result<Option<Question>, Box<dyn Error + Send + Sync>>;
 
-    async fn retreive_all_question_names(
+    /// Page of question texts of the quiz containing `query`.
+    async fn retreive_question_names_page(
         &self,
         id_quiz: impl Into<String>,
-    ) -> Result<Vec<String>, Box<dyn Error + Send + Sync>>;
+        query: &str,
+        page: i64,
+    ) -> Result<Page, Box<dyn Error + Send + Sync>>;
 }
 
 pub(crate) trait EditQuestion {
@@ -96,6 +193,14 @@ pub(crate) trait EditQuestion {
         new: impl Into<String>,
     ) -> GenericError;
 
+    async fn edit_scoring(
+        &self,
+        quiz_id: impl Into<String>,
+        question_id: impl Into<String>,
+        points: f64,
+        penalty: f64,
+    ) -> GenericError;
+
     // async fn add_answer(&self, new: Answer) -> GenericError;
 
     // async fn delete_answer(&self, id: impl Into<String>) -> GenericError;
@@ -112,11 +217,8 @@ pub(crate) trait CreateAnswer {
 }
 
 pub(crate) trait CreateQuestion {
-    async fn create_question(
-        &self,
-        quiz_id: impl Into<String>,
-        new: impl Into<String>,
-    ) -> GenericError;
+    /// Adds the question with its answers in one transaction.
+    async fn create_question(&self, quiz_id: impl Into<String>, new: &Question) -> GenericError;
 }
 
 pub(crate) trait RetreiveAnswer {
@@ -152,6 +254,153 @@ pub(crate) trait EditAnswer {
     ) -> GenericError;
 }
 
+pub(crate) trait Attempts {
+    async fn count_attempts(
+        &self,
+        user_id: i64,
+        quiz_id: &Uuid,
+    ) -> Result<i64, Box<dyn Error + Send + Sync>>;
+
+    /// Starts an attempt on the given revision of the quiz.
+    async fn start_attempt(
+        &self,
+        user_id: i64,
+        quiz_id: &Uuid,
+        revision: i32,
+    ) -> Result<Uuid, Box<dyn Error + Send + Sync>>;
+
+    async fn finish_attempt(
+        &self,
+        attempt_id: &Uuid,
+        score: f64,
+        max_score: f64,
+        outcome: &Outcome,
+    ) -> Result<(), Box<dyn Error + Send + Sync>>;
+
+    async fn save_progress(
+        &self,
+        attempt_id: &Uuid,
+        curr_idx: usize,
+        progress: &[QuestionProgress],
+    ) -> Result<(), Box<dyn Error + Send + Sync>>;
+
+    async fn retreive_unfinished_attempt(
+        &self,
+        user_id: i64,
+        active_since: DateTime<Utc>,
+    ) -> Result<Option<UnfinishedAttempt>, Box<dyn Error + Send + Sync>>;
+
+    /// Unfinished attempts idle since before `inactive_since`, except those of trashed quizzes.
+    async fn retreive_expired_attempts(
+        &self,
+        inactive_since: DateTime<Utc>,
+    ) -> Result<Vec<UnfinishedAttempt>, Box<dyn Error + Send + Sync>>;
+}
+
+pub(crate) trait Revisions {
+    /// Opens an edit session. Its first change snapshots the quiz as it is and
+    /// moves it to a new revision, as does any change after attempts were started.
+    async fn begin_edit_session(
+        &self,
+        id: impl Into<String>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>>;
+
+    /// Snapshots of the quiz, newest first.
+    async fn retreive_revisions(
+        &self,
+        id: impl Into<String>,
+    ) -> Result<Vec<Revision>, Box<dyn Error + Send + Sync>>;
+
+    /// The quiz as it was on the given revision: its snapshot, or the quiz itself
+    /// while it is still on that revision.
+    async fn retreive_quiz_at(
+        &self,
+        id: impl Into<String>,
+        revision: i32,
+    ) -> Result<Option<Quiz>, Box<dyn Error + Send + Sync>>;
+
+    /// Snapshots the current content, then brings back the content of the given
+    /// revision into a new one. Name, status and code of the quiz are kept.
+    async fn rollback(&self, id: impl Into<String>, revision: i32) -> GenericError;
+}
+
+/// Editor changes per user, so that the latest one can be undone.
+pub(crate) trait Operations {
+    async fn log_operation(
+        &self,
+        user_id: i64,
+        quiz_id: impl Into<String>,
+        operation: &Operation,
+    ) -> Result<(), Box<dyn Error + Send + Sync>>;
+
+    /// Reverts the latest change of the user and takes it off the log in one transaction,
+    /// limited to the quiz if given. A change that no longer applies is dropped from the log.
+    async fn undo_operation(
+        &self,
+        user_id: i64,
+        quiz_id: Option<String>,
+    ) -> Result<Option<Undo>, Box<dyn Error + Send + Sync>>;
+}
+
+pub(crate) trait Subscription {
+    async fn toggle_subscription(
+        &self,
+        user_id: i64,
+        quiz_id: impl Into<String>,
+    ) -> Result<Option<bool>, Box<dyn Error + Send + Sync>>;
+}
+
+pub(crate) trait Study {
+    async fn retreive_due_card(
+        &self,
+        user_id: i64,
+    ) -> Result<Option<(Question, StudyCard)>, Box<dyn Error + Send + Sync>>;
+
+    async fn retreive_next_due_time(
+        &self,
+        user_id: i64,
+    ) -> Result<Option<DateTime<Utc>>, Box<dyn Error + Send + Sync>>;
+
+    async fn save_card(
+        &self,
+        user_id: i64,
+        card: &StudyCard,
+    ) -> Result<(), Box<dyn Error + Send + Sync>>;
+}
+
+pub(crate) trait Schedule {
+    async fn set_reminder(&self, reminder: &Reminder) -> Result<(), Box<dyn Error + Send + Sync>>;
+
+    async fn remove_reminder(&self, user_id: i64) -> Result<bool, Box<dyn Error + Send + Sync>>;
+
+    async fn retreive_due_reminders(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<Reminder>, Box<dyn Error + Send + Sync>>;
+
+    async fn schedule_push(
+        &self,
+        quiz_id: impl Into<String>,
+        chat_id: Option<i64>,
+        fire_at: DateTime<Utc>,
+    ) -> GenericError;
+
+    async fn retreive_due_pushes(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<ScheduledPush>, Box<dyn Error + Send + Sync>>;
+
+    async fn mark_push_sent(
+        &self,
+        push: &ScheduledPush,
+    ) -> Result<(), Box<dyn Error + Send + Sync>>;
+
+    async fn retreive_subscribers(
+        &self,
+        quiz_id: &Uuid,
+    ) -> Result<Vec<i64>, Box<dyn Error + Send + Sync>>;
+}
+
 impl CreateQuiz for Connection {
     async fn create_quiz(&self, quiz: Quiz) -> GenericError {
         log::debug!("Creating transaction");
@@ -159,16 +408,37 @@ impl CreateQuiz for Connection {
 
         log::debug!("Adding quiz");
         let name = sqlx::query!(
-            "INSERT INTO quizes VALUES ($1, $2, $3, $4) RETURNING name",
+            "INSERT INTO quizes (uuid, name, description, author, opens_at, closes_at, max_attempts, pass_mark_kind, pass_mark, native_polls, code, status) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12) RETURNING name",
             quiz.uuid(),
             quiz.title(),
             quiz.description(),
-            quiz.author()
+            quiz.author(),
+            quiz.availability().opens_at(),
+            quiz.availability().closes_at(),
+            quiz.availability().max_attempts(),
+            quiz.grading().pass_mark().map(|pass_mark| pass_mark.kind()),
+            quiz.grading().pass_mark().map(|pass_mark| pass_mark.value()),
+            quiz.native_polls(),
+            quiz.code(),
+            quiz.status().as_str()
         )
         .fetch_one(&mut *tx)
         .await?
         .name;
 
+        insert_tags(&mut tx, quiz.uuid(), quiz.tags()).await?;
+
+        for band in quiz.grading().bands() {
+            sqlx::query!(
+                "INSERT INTO grade_bands VALUES ($1, $2, $3)",
+                quiz.uuid(),
+                band.label(),
+                band.min_percent()
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
         log::debug!("Adding questions");
         for question in quiz.questions() {
             log::debug!(
@@ -177,10 +447,13 @@ impl CreateQuiz for Connection {
                 question.uuid()
             );
             sqlx::query!(
-                "INSERT INTO questions VALUES ($1, $2, $3)",
+                "INSERT INTO questions (uuid, text, quiz_id, points, penalty, explanation) VALUES ($1, $2, $3, $4, $5, $6)",
                 question.uuid(),
                 question.text(),
-                quiz.uuid()
+                quiz.uuid(),
+                question.points(),
+                question.penalty(),
+                question.explanation()
             )
             .execute(&mut *tx)
             .await?;
@@ -208,6 +481,92 @@ impl CreateQuiz for Connection {
 
         Ok(name)
     }
+
+    async fn duplicate_quiz(&self, id: impl Into<String>) -> GenericError {
+        let id = id.into();
+        let mut tx = self.pool.begin().await?;
+
+        let source = sqlx::query!("SELECT uuid FROM quizes WHERE name = $1", &id)
+            .fetch_one(&mut *tx)
+            .await?
+            .uuid;
+
+        let mut name = format!("{} (copy)", id);
+        let mut copies = 1;
+        while sqlx::query!("SELECT uuid FROM quizes WHERE name = $1", &name)
+            .fetch_optional(&mut *tx)
+            .await?
+            .is_some()
+        {
+            copies += 1;
+            name = format!("{} (copy {})", id, copies);
+        }
+
+        let uuid = Uuid::new_v4();
+        sqlx::query!(
+            "INSERT INTO quizes (uuid, name, description, author, opens_at, closes_at, max_attempts, pass_mark_kind, pass_mark, native_polls, code, status) SELECT $1, $2, description, author, opens_at, closes_at, max_attempts, pass_mark_kind, pass_mark, native_polls, $3, $4 FROM quizes WHERE uuid = $5",
+            uuid,
+            &name,
+            quiz_code(&uuid),
+            Status::Draft.as_str(),
+            source
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!(
+            "INSERT INTO grade_bands SELECT $1, label, min_percent FROM grade_bands WHERE quiz_id = $2",
+            uuid,
+            source
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!(
+            "INSERT INTO quiz_tags SELECT $1, tag_id FROM quiz_tags WHERE quiz_id = $2",
+            uuid,
+            source
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let questions = sqlx::query!(
+            "SELECT uuid FROM questions WHERE quiz_id = $1 AND deleted_at IS NULL",
+            source
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        for question in questions {
+            let question_copy = Uuid::new_v4();
+            sqlx::query!(
+                "INSERT INTO questions (uuid, text, quiz_id, points, penalty, explanation) SELECT $1, text, $2, points, penalty, explanation FROM questions WHERE uuid = $3",
+                question_copy,
+                uuid,
+                question.uuid
+            )
+            .execute(&mut *tx)
+            .await?;
+
+            let answers = sqlx::query!(
+                "SELECT uuid FROM answers WHERE question_id = $1",
+                question.uuid
+            )
+            .fetch_all(&mut *tx)
+            .await?;
+            for answer in answers {
+                sqlx::query!(
+                    "INSERT INTO answers (uuid, text, is_correct, question_id) SELECT $1, text, is_correct, $2 FROM answers WHERE uuid = $3",
+                    Uuid::new_v4(),
+                    question_copy,
+                    answer.uuid
+                )
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+
+        tx.commit().await?;
+
+        Ok(name)
+    }
 }
 
 impl RetreiveQuiz for Connection {
@@ -216,91 +575,117 @@ impl RetreiveQuiz for Connection {
         id: impl Into<String>,
     ) -> Result<Option<Quiz>, Box<dyn Error + Send + Sync>> {
         let mut tx = self.pool.begin().await?;
+        let quiz = load_quiz(&mut tx, &id.into()).await?;
+        tx.commit().await?;
 
-        let quiz_record = sqlx::query!(
-            "SELECT uuid, name, description, author FROM quizes WHERE name = $1",
-            id.into()
-        )
-        .fetch_one(&mut *tx)
-        .await;
-
-        match quiz_record {
-            Ok(quiz_record) => {
-                let mut quiz = Quiz::retreive(
-                    quiz_record.uuid,
-                    quiz_record.name,
-                    quiz_record.description,
-                    quiz_record.author,
-                );
-
-                let quiz_questions = sqlx::query!(
-                    "SELECT uuid, text, quiz_id FROM questions WHERE quiz_id = $1",
-                    quiz_record.uuid
-                )
-                .fetch_all(&mut *tx)
-                .await?;
+        Ok(quiz)
+    }
 
-                for question_record in quiz_questions {
-                    let mut question =
-                        Question::retreive(question_record.uuid, question_record.text);
+    async fn retreive_quiz_names_page(
+        &self,
+        query: &str,
+        tag: Option<&str>,
+        status: Option<Status>,
+        open_at: Option<DateTime<Utc>>,
+        page: i64,
+    ) -> Result<Page, Box<dyn Error + Send + Sync>> {
+        let quizes_records = sqlx::query!(
+            r#"SELECT name, COUNT(*) OVER() AS "total!" FROM quizes WHERE name ILIKE $1 AND deleted_at IS NULL AND ($2::TIMESTAMPTZ IS NULL OR ((opens_at IS NULL OR opens_at <= $2) AND (closes_at IS NULL OR closes_at > $2))) AND ($5::VARCHAR IS NULL OR EXISTS (SELECT 1 FROM quiz_tags INNER JOIN tags ON tags.uuid = quiz_tags.tag_id WHERE quiz_tags.quiz_id = quizes.uuid AND tags.name = $5)) AND ($6::VARCHAR IS NULL OR status = $6) ORDER BY name LIMIT $3 OFFSET $4"#,
+            contains_pattern(query),
+            open_at,
+            PAGE_SIZE,
+            page * PAGE_SIZE,
+            tag,
+            status.map(|status| status.as_str())
+        )
+        .fetch_all(&self.pool)
+        .await?;
 
-                    let question_answers = sqlx::query!("SELECT uuid, text, is_correct, question_id FROM answers WHERE question_id = $1", question_record.uuid).fetch_all(&mut *tx).await?;
+        let total = quizes_records.first().map_or(0, |q| q.total);
+        Ok(Page::new(
+            quizes_records.into_iter().map(|q| q.name).collect(),
+            total,
+        ))
+    }
 
-                    question_answers.into_iter().for_each(|answer| {
-                        question.add_answer(Answer::retreive(
-                            answer.uuid,
-                            answer.text,
-                            answer.is_correct,
-                        ))
-                    });
+    async fn retreive_tags_page(
+        &self,
+        query: &str,
+        open_at: Option<DateTime<Utc>>,
+        page: i64,
+    ) -> Result<Page, Box<dyn Error + Send + Sync>> {
+        let tags_records = sqlx::query!(
+            r#"SELECT tags.name, COUNT(*) OVER() AS "total!" FROM tags WHERE tags.name ILIKE $1 AND EXISTS (SELECT 1 FROM quiz_tags INNER JOIN quizes ON quizes.uuid = quiz_tags.quiz_id WHERE quiz_tags.tag_id = tags.uuid AND quizes.status = 'published' AND quizes.deleted_at IS NULL AND ($2::TIMESTAMPTZ IS NULL OR ((opens_at IS NULL OR opens_at <= $2) AND (closes_at IS NULL OR closes_at > $2)))) ORDER BY tags.name LIMIT $3 OFFSET $4"#,
+            contains_pattern(query),
+            open_at,
+            PAGE_SIZE,
+            page * PAGE_SIZE
+        )
+        .fetch_all(&self.pool)
+        .await?;
 
-                    quiz.add_question(question);
-                }
+        let total = tags_records.first().map_or(0, |t| t.total);
+        Ok(Page::new(
+            tags_records.into_iter().map(|t| t.name).collect(),
+            total,
+        ))
+    }
 
-                tx.commit().await?;
+    async fn retreive_quiz_name_by_code(
+        &self,
+        code: &str,
+    ) -> Result<Option<String>, Box<dyn Error + Send + Sync>> {
+        let record = sqlx::query!(
+            "SELECT name FROM quizes WHERE code = $1 AND deleted_at IS NULL",
+            code.to_uppercase()
+        )
+        .fetch_optional(&self.pool)
+        .await?;
 
-                Ok(Some(quiz))
-            }
-            Err(e) => match e {
-                sqlx::Error::RowNotFound => Ok(None),
-                e => Err(Box::new(e)),
-            },
-        }
+        Ok(record.map(|r| r.name))
     }
 
-    async fn retreive_all_quiz_names(&self) -> Result<Vec<String>, Box<dyn Error + Send + Sync>> {
-        let quizes_records = sqlx::query!("SELECT name FROM quizes")
-            .fetch_all(&self.pool)
-            .await?;
-
-        let quiz_names: Vec<String> = quizes_records.into_iter().map(|q| q.name).collect();
+    async fn search_quizes(
+        &self,
+        query: &str,
+        limit: i64,
+    ) -> Result<Vec<Quiz>, Box<dyn Error + Send + Sync>> {
+        let pattern = contains_pattern(query);
+        let records = sqlx::query!(
+            "SELECT uuid, name, description, author FROM quizes WHERE (name ILIKE $1 OR description ILIKE $1) AND status = 'published' AND deleted_at IS NULL ORDER BY name LIMIT $2",
+            pattern,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?;
 
-        Ok(quiz_names)
+        Ok(records
+            .into_iter()
+            .map(|r| Quiz::retreive(r.uuid, r.name, r.description, r.author))
+            .collect())
     }
 }
 
 impl DeleteQuiz for Connection {
     async fn delete_quiz(&self, id: impl Into<String>) -> GenericError {
-        let id = id.into();
-        sqlx::query!("DELETE FROM quizes WHERE name = $1", &id)
-            .execute(&self.pool)
-            .await?;
+        let deleted = sqlx::query!(
+            "UPDATE quizes SET deleted_at = NOW() WHERE name = $1 AND deleted_at IS NULL RETURNING name",
+            id.into()
+        )
+        .fetch_one(&self.pool)
+        .await?;
 
-        Ok(id)
+        Ok(deleted.name)
     }
 }
 
 impl EditQuiz for Connection {
     async fn edit_name(&self, id: impl Into<String>, new: impl Into<String>) -> GenericError {
-        let new = sqlx::query!(
-            "UPDATE quizes SET name=$1 WHERE name=$2 RETURNING name",
-            new.into(),
-            id.into()
-        )
-        .fetch_one(&self.pool)
-        .await?;
+        let mut tx = self.pool.begin().await?;
+        let new = rename_quiz(&mut tx, &id.into(), &new.into()).await?;
+        tx.commit().await?;
 
-        Ok(new.name)
+        Ok(new)
     }
 
     async fn edit_description(
@@ -308,15 +693,122 @@ impl EditQuiz for Connection {
         id: impl Into<String>,
         new: impl Into<String>,
     ) -> GenericError {
-        let new = sqlx::query!(
-            "UPDATE quizes SET description=$1 WHERE name=$2 RETURNING description",
-            new.into(),
+        let mut tx = self.pool.begin().await?;
+        let new = set_description(&mut tx, &id.into(), &new.into()).await?;
+        tx.commit().await?;
+
+        Ok(new)
+    }
+
+    async fn edit_availability(
+        &self,
+        id: impl Into<String>,
+        availability: &Availability,
+    ) -> GenericError {
+        let id = id.into();
+        let mut tx = self.pool.begin().await?;
+        revise(&mut tx, &id).await?;
+
+        let updated = sqlx::query!(
+            "UPDATE quizes SET opens_at = $1, closes_at = $2, max_attempts = $3 WHERE name = $4 RETURNING name",
+            availability.opens_at(),
+            availability.closes_at(),
+            availability.max_attempts(),
+            id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(updated.name)
+    }
+
+    async fn edit_delivery(&self, id: impl Into<String>, native_polls: bool) -> GenericError {
+        let id = id.into();
+        let mut tx = self.pool.begin().await?;
+        revise(&mut tx, &id).await?;
+
+        let updated = sqlx::query!(
+            "UPDATE quizes SET native_polls = $1 WHERE name = $2 RETURNING name",
+            native_polls,
+            id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(updated.name)
+    }
+
+    async fn edit_status(&self, id: impl Into<String>, status: Status) -> GenericError {
+        let updated = sqlx::query!(
+            "UPDATE quizes SET status = $1 WHERE name = $2 RETURNING name",
+            status.as_str(),
             id.into()
         )
         .fetch_one(&self.pool)
         .await?;
 
-        Ok(new.description)
+        Ok(updated.name)
+    }
+
+    async fn edit_tags(&self, id: impl Into<String>, tags: &[String]) -> GenericError {
+        let id = id.into();
+        let mut tx = self.pool.begin().await?;
+        revise(&mut tx, &id).await?;
+
+        let quiz = sqlx::query!("SELECT uuid, name FROM quizes WHERE name = $1", id)
+            .fetch_one(&mut *tx)
+            .await?;
+        sqlx::query!("DELETE FROM quiz_tags WHERE quiz_id = $1", quiz.uuid)
+            .execute(&mut *tx)
+            .await?;
+        insert_tags(&mut tx, &quiz.uuid, tags).await?;
+        // Tags no quiz uses any more would show up as empty topics.
+        sqlx::query!(
+            "DELETE FROM tags WHERE NOT EXISTS (SELECT 1 FROM quiz_tags WHERE quiz_tags.tag_id = tags.uuid)"
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(quiz.name)
+    }
+
+    async fn edit_grading(&self, id: impl Into<String>, grading: &Grading) -> GenericError {
+        let id = id.into();
+        let mut tx = self.pool.begin().await?;
+        revise(&mut tx, &id).await?;
+
+        let updated = sqlx::query!(
+            "UPDATE quizes SET pass_mark_kind = $1, pass_mark = $2 WHERE name = $3 RETURNING uuid, name",
+            grading.pass_mark().map(|pass_mark| pass_mark.kind()),
+            grading.pass_mark().map(|pass_mark| pass_mark.value()),
+            id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query!("DELETE FROM grade_bands WHERE quiz_id = $1", updated.uuid)
+            .execute(&mut *tx)
+            .await?;
+        for band in grading.bands() {
+            sqlx::query!(
+                "INSERT INTO grade_bands VALUES ($1, $2, $3)",
+                updated.uuid,
+                band.label(),
+                band.min_percent()
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(updated.name)
     }
 
     // async fn add_question(&self, new: Question) -> GenericError {
@@ -336,10 +828,12 @@ impl RetreiveQuestion for Connection {
     ) -> Result<Option<Question>, Box<dyn Error + Send + Sync>> {
         let mut tx = self.pool.begin().await?;
 
-        let question = sqlx::query!("SELECT questions.uuid, questions.text FROM questions INNER JOIN quizes ON questions.quiz_id = quizes.uuid WHERE quizes.name = $1 AND questions.text = $2", id_quiz.into(), id_question.into()).fetch_one(&mut *tx).await;
+        let question = sqlx::query!("SELECT questions.uuid, questions.text, questions.points, questions.penalty, questions.explanation FROM questions INNER JOIN quizes ON questions.quiz_id = quizes.uuid WHERE quizes.name = $1 AND questions.deleted_at IS NULL AND questions.text = $2", id_quiz.into(), id_question.into()).fetch_one(&mut *tx).await;
         match question {
-            Ok(question) => {
-                let mut question = Question::retreive(question.uuid, question.text);
+            Ok(record) => {
+                let mut question = Question::retreive(record.uuid, record.text);
+                question.set_scoring(record.points, record.penalty);
+                question.set_explanation(record.explanation);
                 let answers = sqlx::query!(
                     "SELECT uuid, text, is_correct FROM answers WHERE question_id = $1",
                     question.uuid()
@@ -362,15 +856,19 @@ impl RetreiveQuestion for Connection {
         }
     }
 
-    async fn retreive_all_question_names(
+    async fn retreive_question_names_page(
         &self,
         id_quiz: impl Into<String>,
-    ) -> Result<Vec<String>, Box<dyn Error + Send + Sync>> {
-        let question_records = sqlx::query!("SELECT text FROM questions INNER JOIN quizes ON questions.quiz_id = quizes.uuid WHERE quizes.name = $1", id_quiz.into()).fetch_all(&self.pool).await?;
-
-        let question_names = question_records.into_iter().map(|q| q.text).collect();
-
-        Ok(question_names)
+        query: &str,
+        page: i64,
+    ) -> Result<Page, Box<dyn Error + Send + Sync>> {
+        let question_records = sqlx::query!(r#"SELECT text, COUNT(*) OVER() AS "total!" FROM questions INNER JOIN quizes ON questions.quiz_id = quizes.uuid WHERE quizes.name = $1 AND questions.deleted_at IS NULL AND text ILIKE $2 ORDER BY text LIMIT $3 OFFSET $4"#, id_quiz.into(), contains_pattern(query), PAGE_SIZE, page * PAGE_SIZE).fetch_all(&self.pool).await?;
+
+        let total = question_records.first().map_or(0, |q| q.total);
+        Ok(Page::new(
+            question_records.into_iter().map(|q| q.text).collect(),
+            total,
+        ))
     }
 }
 
@@ -380,7 +878,13 @@ impl DeleteQuestion for Connection {
         quiz_id: impl Into<String>,
         question_id: impl Into<String>,
     ) -> GenericError {
-        let deleted = sqlx::query!("DELETE FROM questions USING quizes WHERE quizes.uuid = questions.quiz_id AND quizes.name = $1 AND questions.text = $2 RETURNING questions.text", quiz_id.into(), question_id.into()).fetch_one(&self.pool).await?;
+        let quiz_id = quiz_id.into();
+        let mut tx = self.pool.begin().await?;
+        revise(&mut tx, &quiz_id).await?;
+
+        let deleted = sqlx::query!("UPDATE questions SET deleted_at = NOW() FROM quizes WHERE quizes.uuid = questions.quiz_id AND quizes.name = $1 AND questions.deleted_at IS NULL AND questions.text = $2 RETURNING questions.text", quiz_id, question_id.into()).fetch_one(&mut *tx).await?;
+
+        tx.commit().await?;
 
         Ok(deleted.text)
     }
@@ -393,7 +897,27 @@ impl EditQuestion for Connection {
         question_id: impl Into<String>,
         new: impl Into<String>,
     ) -> GenericError {
-        let updated = sqlx::query!("UPDATE questions SET text = $1 FROM quizes WHERE quizes.uuid = questions.quiz_id AND quizes.name = $2 AND questions.text = $3 RETURNING text", new.into(), quiz_id.into(), question_id.into()).fetch_one(&self.pool).await?;
+        let mut tx = self.pool.begin().await?;
+        let updated = set_text(&mut tx, &quiz_id.into(), &question_id.into(), &new.into()).await?;
+        tx.commit().await?;
+
+        Ok(updated)
+    }
+
+    async fn edit_scoring(
+        &self,
+        quiz_id: impl Into<String>,
+        question_id: impl Into<String>,
+        points: f64,
+        penalty: f64,
+    ) -> GenericError {
+        let quiz_id = quiz_id.into();
+        let mut tx = self.pool.begin().await?;
+        revise(&mut tx, &quiz_id).await?;
+
+        let updated = sqlx::query!("UPDATE questions SET points = $1, penalty = $2 FROM quizes WHERE quizes.uuid = questions.quiz_id AND quizes.name = $3 AND questions.deleted_at IS NULL AND questions.text = $4 RETURNING text", points, penalty, quiz_id, question_id.into()).fetch_one(&mut *tx).await?;
+
+        tx.commit().await?;
 
         Ok(updated.text)
     }
@@ -406,7 +930,7 @@ impl RetreiveAnswer for Connection {
         id_question: impl Into<String>,
         id_answer: impl Into<String>,
     ) -> Result<Option<Answer>, Box<dyn Error + Send + Sync>> {
-        let answer = sqlx::query!("SELECT answers.uuid, answers.text, answers.is_correct FROM answers INNER JOIN questions ON answers.question_id = questions.uuid INNER JOIN quizes ON quizes.uuid = questions.quiz_id WHERE quizes.name = $1 AND questions.text = $2 AND answers.text = $3", id_quiz.into(), id_question.into(), id_answer.into()).fetch_one(&self.pool).await?;
+        let answer = sqlx::query!("SELECT answers.uuid, answers.text, answers.is_correct FROM answers INNER JOIN questions ON answers.question_id = questions.uuid INNER JOIN quizes ON quizes.uuid = questions.quiz_id WHERE quizes.name = $1 AND questions.deleted_at IS NULL AND questions.text = $2 AND answers.text = $3", id_quiz.into(), id_question.into(), id_answer.into()).fetch_one(&self.pool).await?;
 
         Ok(Some(Answer::retreive(
             answer.uuid,
@@ -420,7 +944,7 @@ impl RetreiveAnswer for Connection {
         id_quiz: impl Into<String>,
         id_question: impl Into<String>,
     ) -> Result<Vec<String>, Box<dyn Error + Send + Sync>> {
-        let answers = sqlx::query!("SELECT answers.text FROM answers INNER JOIN questions ON answers.question_id = questions.uuid INNER JOIN quizes ON quizes.uuid = questions.quiz_id WHERE questions.text = $1 AND quizes.name = $2", id_question.into(), id_quiz.into()).fetch_all(&self.pool).await?;
+        let answers = sqlx::query!("SELECT answers.text FROM answers INNER JOIN questions ON answers.question_id = questions.uuid INNER JOIN quizes ON quizes.uuid = questions.quiz_id WHERE questions.deleted_at IS NULL AND questions.text = $1 AND quizes.name = $2", id_question.into(), id_quiz.into()).fetch_all(&self.pool).await?;
 
         Ok(answers.into_iter().map(|r| r.text).collect())
     }
@@ -433,7 +957,13 @@ impl DeleteAnswer for Connection {
         question_id: impl Into<String>,
         answer_id: impl Into<String>,
     ) -> GenericError {
-        let answer = sqlx::query!("DELETE FROM answers USING questions, quizes WHERE questions.uuid = answers.question_id AND questions.text = $1 AND questions.quiz_id = quizes.uuid AND quizes.name = $2 AND answers.text = $3 RETURNING answers.text", question_id.into(), quiz_id.into(), answer_id.into()).fetch_one(&self.pool).await?;
+        let quiz_id = quiz_id.into();
+        let mut tx = self.pool.begin().await?;
+        revise(&mut tx, &quiz_id).await?;
+
+        let answer = sqlx::query!("DELETE FROM answers USING questions, quizes WHERE questions.uuid = answers.question_id AND questions.text = $1 AND questions.deleted_at IS NULL AND questions.quiz_id = quizes.uuid AND quizes.name = $2 AND answers.text = $3 RETURNING answers.text", question_id.into(), quiz_id, answer_id.into()).fetch_one(&mut *tx).await?;
+
+        tx.commit().await?;
 
         Ok(answer.text)
     }
@@ -447,7 +977,13 @@ impl EditAnswer for Connection {
         answer_id: impl Into<String>,
         new: impl Into<String>,
     ) -> GenericError {
-        let record = sqlx::query!("UPDATE answers SET text = $1 FROM questions INNER JOIN quizes ON questions.quiz_id = quizes.uuid WHERE questions.uuid = answers.question_id AND quizes.name = $2 AND questions.text = $3 AND answers.text = $4 RETURNING answers.text", new.into(), quiz_id.into(), question_id.into(), answer_id.into()).fetch_one(&self.pool).await?;
+        let quiz_id = quiz_id.into();
+        let mut tx = self.pool.begin().await?;
+        revise(&mut tx, &quiz_id).await?;
+
+        let record = sqlx::query!("UPDATE answers SET text = $1 FROM questions INNER JOIN quizes ON questions.quiz_id = quizes.uuid WHERE questions.uuid = answers.question_id AND quizes.name = $2 AND questions.deleted_at IS NULL AND questions.text = $3 AND answers.text = $4 RETURNING answers.text", new.into(), quiz_id, question_id.into(), answer_id.into()).fetch_one(&mut *tx).await?;
+
+        tx.commit().await?;
 
         Ok(record.text)
     }
@@ -459,9 +995,18 @@ impl EditAnswer for Connection {
         answer_id: impl Into<String>,
         is_correct: bool,
     ) -> GenericError {
-        let record = sqlx::query!("UPDATE answers SET is_correct = $1 FROM questions INNER JOIN quizes ON questions.quiz_id = quizes.uuid WHERE questions.uuid = answers.question_id AND quizes.name = $2 AND questions.text = $3 AND answers.text = $4 RETURNING answers.text", is_correct, quiz_id.into(), question_id.into(), answer_id.into()).fetch_one(&self.pool).await?;
+        let mut tx = self.pool.begin().await?;
+        let updated = set_corectness(
+            &mut tx,
+            &quiz_id.into(),
+            &question_id.into(),
+            &answer_id.into(),
+            is_correct,
+        )
+        .await?;
+        tx.commit().await?;
 
-        Ok(record.text)
+        Ok(updated)
     }
 }
 
@@ -473,61 +1018,1028 @@ impl CreateAnswer for Connection {
         new: impl Into<String>,
         is_correct: bool,
     ) -> GenericError {
+        let mut tx = self.pool.begin().await?;
+        let added = insert_answer(
+            &mut tx,
+            &quiz_id.into(),
+            &question_id.into(),
+            &new.into(),
+            is_correct,
+        )
+        .await?;
+        tx.commit().await?;
+
+        Ok(added)
+    }
+}
+
+impl CreateQuestion for Connection {
+    async fn create_question(&self, quiz_id: impl Into<String>, new: &Question) -> GenericError {
+        let quiz_id = quiz_id.into();
+        let mut tx = self.pool.begin().await?;
+        revise(&mut tx, &quiz_id).await?;
+
         let quiz_uuid = sqlx::query!(
-            "SELECT uuid FROM quizes WHERE quizes.name = $1",
-            quiz_id.into()
+            "SELECT uuid FROM quizes WHERE quizes.name = $1 AND deleted_at IS NULL",
+            quiz_id
         )
-        .fetch_one(&self.pool)
+        .fetch_one(&mut *tx)
         .await?;
 
-        let question_uuid = sqlx::query!(
-            "SELECT uuid FROM questions WHERE questions.quiz_id = $1 AND text = $2",
+        let added = sqlx::query!(
+            "INSERT INTO questions (uuid, text, quiz_id, points, penalty, explanation) VALUES ($1, $2, $3, $4, $5, $6) RETURNING text",
+            new.uuid(),
+            new.text(),
             quiz_uuid.uuid,
-            question_id.into()
+            new.points(),
+            new.penalty(),
+            new.explanation()
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        for answer in new.answers() {
+            sqlx::query!(
+                "INSERT INTO answers VALUES ($1, $2, $3, $4)",
+                answer.uuid(),
+                answer.text(),
+                answer.is_correct(),
+                new.uuid()
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(added.text)
+    }
+}
+
+impl Attempts for Connection {
+    async fn count_attempts(
+        &self,
+        user_id: i64,
+        quiz_id: &Uuid,
+    ) -> Result<i64, Box<dyn Error + Send + Sync>> {
+        let record = sqlx::query!(
+            "SELECT COUNT(*) AS \"count!\" FROM attempts WHERE user_id = $1 AND quiz_id = $2",
+            user_id,
+            quiz_id
         )
         .fetch_one(&self.pool)
         .await?;
 
-        let new_answer = Answer::new(new.into(), is_correct);
+        Ok(record.count)
+    }
 
-        let added = sqlx::query!(
-            "INSERT INTO answers VALUES ($1, $2, $3, $4) RETURNING text",
-            new_answer.uuid(),
-            new_answer.text(),
-            new_answer.is_correct(),
-            question_uuid.uuid
+    async fn start_attempt(
+        &self,
+        user_id: i64,
+        quiz_id: &Uuid,
+        revision: i32,
+    ) -> Result<Uuid, Box<dyn Error + Send + Sync>> {
+        let record = sqlx::query!(
+            "INSERT INTO attempts (uuid, quiz_id, user_id, started_at, revision) VALUES ($1, $2, $3, NOW(), $4) RETURNING uuid",
+            Uuid::new_v4(),
+            quiz_id,
+            user_id,
+            revision
         )
         .fetch_one(&self.pool)
         .await?;
 
-        Ok(added.text)
+        Ok(record.uuid)
+    }
+
+    async fn finish_attempt(
+        &self,
+        attempt_id: &Uuid,
+        score: f64,
+        max_score: f64,
+        outcome: &Outcome,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        sqlx::query!(
+            "UPDATE attempts SET score = $1, max_score = $2, passed = $3, grade = $4, finished_at = NOW() WHERE uuid = $5",
+            score,
+            max_score,
+            outcome.passed(),
+            outcome.grade(),
+            attempt_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn save_progress(
+        &self,
+        attempt_id: &Uuid,
+        curr_idx: usize,
+        progress: &[QuestionProgress],
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        sqlx::query!(
+            "UPDATE attempts SET curr_idx = $1, progress = $2, updated_at = NOW() WHERE uuid = $3",
+            curr_idx as i32,
+            serde_json::to_value(progress)?,
+            attempt_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn retreive_unfinished_attempt(
+        &self,
+        user_id: i64,
+        active_since: DateTime<Utc>,
+    ) -> Result<Option<UnfinishedAttempt>, Box<dyn Error + Send + Sync>> {
+        let record = sqlx::query!("SELECT attempts.uuid, attempts.user_id, quizes.name, attempts.revision, attempts.curr_idx, attempts.progress FROM attempts INNER JOIN quizes ON quizes.uuid = attempts.quiz_id WHERE quizes.deleted_at IS NULL AND attempts.user_id = $1 AND attempts.finished_at IS NULL AND attempts.updated_at > $2 ORDER BY attempts.updated_at DESC LIMIT 1", user_id, active_since).fetch_optional(&self.pool).await?;
+
+        match record {
+            Some(r) => Ok(Some(UnfinishedAttempt::retreive(
+                r.uuid,
+                r.user_id,
+                r.name,
+                r.revision,
+                r.curr_idx as usize,
+                serde_json::from_value(r.progress)?,
+            ))),
+            None => Ok(None),
+        }
+    }
+
+    async fn retreive_expired_attempts(
+        &self,
+        inactive_since: DateTime<Utc>,
+    ) -> Result<Vec<UnfinishedAttempt>, Box<dyn Error + Send + Sync>> {
+        let records = sqlx::query!("SELECT attempts.uuid, attempts.user_id, quizes.name, attempts.revision, attempts.curr_idx, attempts.progress FROM attempts INNER JOIN quizes ON quizes.uuid = attempts.quiz_id WHERE attempts.finished_at IS NULL AND attempts.updated_at <= $1 AND quizes.deleted_at IS NULL", inactive_since).fetch_all(&self.pool).await?;
+
+        let mut attempts = Vec::with_capacity(records.len());
+        for r in records {
+            attempts.push(UnfinishedAttempt::retreive(
+                r.uuid,
+                r.user_id,
+                r.name,
+                r.revision,
+                r.curr_idx as usize,
+                serde_json::from_value(r.progress)?,
+            ));
+        }
+
+        Ok(attempts)
     }
 }
 
-impl CreateQuestion for Connection {
-    async fn create_question(
+impl Trash for Connection {
+    async fn retreive_trashed_quiz_names_page(
+        &self,
+        query: &str,
+        page: i64,
+    ) -> Result<Page, Box<dyn Error + Send + Sync>> {
+        let records = sqlx::query!(
+            r#"SELECT name, COUNT(*) OVER() AS "total!" FROM quizes WHERE deleted_at IS NOT NULL AND name ILIKE $1 ORDER BY deleted_at DESC LIMIT $2 OFFSET $3"#,
+            contains_pattern(query),
+            PAGE_SIZE,
+            page * PAGE_SIZE
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let total = records.first().map_or(0, |r| r.total);
+        Ok(Page::new(
+            records.into_iter().map(|r| r.name).collect(),
+            total,
+        ))
+    }
+
+    async fn retreive_trashed_question_names_page(
+        &self,
+        id_quiz: impl Into<String>,
+        query: &str,
+        page: i64,
+    ) -> Result<Page, Box<dyn Error + Send + Sync>> {
+        let records = sqlx::query!(
+            r#"SELECT questions.text, COUNT(*) OVER() AS "total!" FROM questions INNER JOIN quizes ON questions.quiz_id = quizes.uuid WHERE quizes.name = $1 AND quizes.deleted_at IS NULL AND questions.deleted_at IS NOT NULL AND questions.text ILIKE $2 ORDER BY questions.deleted_at DESC LIMIT $3 OFFSET $4"#,
+            id_quiz.into(),
+            contains_pattern(query),
+            PAGE_SIZE,
+            page * PAGE_SIZE
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let total = records.first().map_or(0, |r| r.total);
+        Ok(Page::new(
+            records.into_iter().map(|r| r.text).collect(),
+            total,
+        ))
+    }
+
+    async fn is_quiz_trashed(
+        &self,
+        id: impl Into<String>,
+    ) -> Result<bool, Box<dyn Error + Send + Sync>> {
+        let record = sqlx::query!(
+            r#"SELECT EXISTS (SELECT 1 FROM quizes WHERE name = $1 AND deleted_at IS NOT NULL) AS "trashed!""#,
+            id.into()
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(record.trashed)
+    }
+
+    async fn restore_quiz(&self, id: impl Into<String>) -> GenericError {
+        let mut tx = self.pool.begin().await?;
+        let restored = untrash_quiz(&mut tx, &id.into()).await?;
+        tx.commit().await?;
+
+        Ok(restored)
+    }
+
+    async fn restore_question(
         &self,
         quiz_id: impl Into<String>,
-        new: impl Into<String>,
+        question_id: impl Into<String>,
     ) -> GenericError {
-        let quiz_uuid = sqlx::query!(
-            "SELECT uuid FROM quizes WHERE quizes.name = $1",
+        let mut tx = self.pool.begin().await?;
+        let restored = untrash_question(&mut tx, &quiz_id.into(), &question_id.into()).await?;
+        tx.commit().await?;
+
+        Ok(restored)
+    }
+
+    async fn purge_trash(
+        &self,
+        deleted_before: DateTime<Utc>,
+    ) -> Result<u64, Box<dyn Error + Send + Sync>> {
+        let mut tx = self.pool.begin().await?;
+
+        let quizes = sqlx::query!("DELETE FROM quizes WHERE deleted_at < $1", deleted_before)
+            .execute(&mut *tx)
+            .await?;
+        let questions = sqlx::query!(
+            "DELETE FROM questions WHERE deleted_at < $1",
+            deleted_before
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(quizes.rows_affected() + questions.rows_affected())
+    }
+}
+
+impl Revisions for Connection {
+    async fn begin_edit_session(
+        &self,
+        id: impl Into<String>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        sqlx::query!(
+            "UPDATE quizes SET revision_pending = TRUE WHERE name = $1",
+            id.into()
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn retreive_revisions(
+        &self,
+        id: impl Into<String>,
+    ) -> Result<Vec<Revision>, Box<dyn Error + Send + Sync>> {
+        let records = sqlx::query!(
+            r#"SELECT quiz_revisions.revision, quiz_revisions.saved_at, jsonb_array_length(quiz_revisions.snapshot->'questions') AS "questions!" FROM quiz_revisions INNER JOIN quizes ON quizes.uuid = quiz_revisions.quiz_id WHERE quizes.name = $1 ORDER BY quiz_revisions.revision DESC"#,
+            id.into()
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(records
+            .into_iter()
+            .map(|r| Revision::retreive(r.revision, r.saved_at, r.questions))
+            .collect())
+    }
+
+    async fn retreive_quiz_at(
+        &self,
+        id: impl Into<String>,
+        revision: i32,
+    ) -> Result<Option<Quiz>, Box<dyn Error + Send + Sync>> {
+        let mut tx = self.pool.begin().await?;
+
+        let Some(quiz) = load_quiz(&mut tx, &id.into()).await? else {
+            return Ok(None);
+        };
+        if quiz.revision() == revision {
+            return Ok(Some(quiz));
+        }
+        let record = sqlx::query!(
+            "SELECT snapshot FROM quiz_revisions WHERE quiz_id = $1 AND revision = $2",
+            quiz.uuid(),
+            revision
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        // Attempts from before revisions were kept have no snapshot to go back to.
+        match record {
+            Some(record) => Ok(Some(serde_json::from_value(record.snapshot)?)),
+            None => Ok(Some(quiz)),
+        }
+    }
+
+    async fn rollback(&self, id: impl Into<String>, revision: i32) -> GenericError {
+        let id = id.into();
+        let mut tx = self.pool.begin().await?;
+
+        // The current content becomes a revision too, so the rollback can be rolled back.
+        let current = load_quiz(&mut tx, &id)
+            .await?
+            .ok_or_else(|| format!("Quiz '{}' not found", id))?;
+        snapshot(&mut tx, &current).await?;
+
+        let record = sqlx::query!(
+            "SELECT quizes.uuid, quizes.name, quiz_revisions.snapshot FROM quiz_revisions INNER JOIN quizes ON quizes.uuid = quiz_revisions.quiz_id WHERE quizes.name = $1 AND quiz_revisions.revision = $2",
+            id,
+            revision
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        let snapshot: Quiz = serde_json::from_value(record.snapshot)?;
+
+        sqlx::query!(
+            "UPDATE quizes SET description = $1, opens_at = $2, closes_at = $3, max_attempts = $4, pass_mark_kind = $5, pass_mark = $6, native_polls = $7 WHERE uuid = $8",
+            snapshot.description(),
+            snapshot.availability().opens_at(),
+            snapshot.availability().closes_at(),
+            snapshot.availability().max_attempts(),
+            snapshot.grading().pass_mark().map(|pass_mark| pass_mark.kind()),
+            snapshot.grading().pass_mark().map(|pass_mark| pass_mark.value()),
+            snapshot.native_polls(),
+            record.uuid
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!("DELETE FROM grade_bands WHERE quiz_id = $1", record.uuid)
+            .execute(&mut *tx)
+            .await?;
+        for band in snapshot.grading().bands() {
+            sqlx::query!(
+                "INSERT INTO grade_bands VALUES ($1, $2, $3)",
+                record.uuid,
+                band.label(),
+                band.min_percent()
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        sqlx::query!("DELETE FROM quiz_tags WHERE quiz_id = $1", record.uuid)
+            .execute(&mut *tx)
+            .await?;
+        insert_tags(&mut tx, &record.uuid, snapshot.tags()).await?;
+
+        // Questions and answers keep their uuids, so study cards of the ones
+        // that survive the rollback are kept. The rest go to the trash.
+        let question_ids: Vec<Uuid> = snapshot.questions().iter().map(|q| *q.uuid()).collect();
+        sqlx::query!(
+            "UPDATE questions SET deleted_at = NOW() WHERE quiz_id = $1 AND uuid <> ALL($2) AND deleted_at IS NULL",
+            record.uuid,
+            &question_ids
+        )
+        .execute(&mut *tx)
+        .await?;
+        for question in snapshot.questions() {
+            sqlx::query!(
+                "INSERT INTO questions (uuid, text, quiz_id, points, penalty, explanation) VALUES ($1, $2, $3, $4, $5, $6) ON CONFLICT (uuid) DO UPDATE SET text = EXCLUDED.text, points = EXCLUDED.points, penalty = EXCLUDED.penalty, explanation = EXCLUDED.explanation, deleted_at = NULL",
+                question.uuid(),
+                question.text(),
+                record.uuid,
+                question.points(),
+                question.penalty(),
+                question.explanation()
+            )
+            .execute(&mut *tx)
+            .await?;
+
+            let answer_ids: Vec<Uuid> = question.answers().iter().map(|a| *a.uuid()).collect();
+            sqlx::query!(
+                "DELETE FROM answers WHERE question_id = $1 AND uuid <> ALL($2)",
+                question.uuid(),
+                &answer_ids
+            )
+            .execute(&mut *tx)
+            .await?;
+            for answer in question.answers() {
+                sqlx::query!(
+                    "INSERT INTO answers VALUES ($1, $2, $3, $4) ON CONFLICT (uuid) DO UPDATE SET text = EXCLUDED.text, is_correct = EXCLUDED.is_correct",
+                    answer.uuid(),
+                    answer.text(),
+                    answer.is_correct(),
+                    question.uuid()
+                )
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+
+        tx.commit().await?;
+
+        Ok(record.name)
+    }
+}
+
+impl Operations for Connection {
+    async fn log_operation(
+        &self,
+        user_id: i64,
+        quiz_id: impl Into<String>,
+        operation: &Operation,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        sqlx::query!(
+            "INSERT INTO operations (user_id, quiz_id, operation) SELECT $1, uuid, $2 FROM quizes WHERE name = $3",
+            user_id,
+            serde_json::to_value(operation)?,
             quiz_id.into()
         )
-        .fetch_one(&self.pool)
+        .execute(&self.pool)
         .await?;
 
-        let new_question = Question::new(new.into(), None);
+        Ok(())
+    }
 
-        let added = sqlx::query!(
-            "INSERT INTO questions VALUES ($1, $2, $3) RETURNING text",
-            new_question.uuid(),
-            new_question.text(),
+    async fn undo_operation(
+        &self,
+        user_id: i64,
+        quiz_id: Option<String>,
+    ) -> Result<Option<Undo>, Box<dyn Error + Send + Sync>> {
+        let mut tx = self.pool.begin().await?;
+
+        let Some(record) = sqlx::query!(
+            r#"WITH popped AS (DELETE FROM operations WHERE id = (SELECT operations.id FROM operations INNER JOIN quizes ON quizes.uuid = operations.quiz_id WHERE operations.user_id = $1 AND ($2::VARCHAR IS NULL OR quizes.name = $2) ORDER BY operations.id DESC LIMIT 1) RETURNING quiz_id, operation) SELECT quizes.name, popped.operation FROM popped INNER JOIN quizes ON quizes.uuid = popped.quiz_id"#,
+            user_id,
+            quiz_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        else {
+            return Ok(None);
+        };
+        let operation: Operation = serde_json::from_value(record.operation)?;
+
+        // Reverted under a savepoint, so that the entry can still be dropped if it fails.
+        let mut revert_tx = tx.begin().await?;
+        let undo = match revert(&mut revert_tx, &record.name, &operation).await {
+            Ok(quiz_name) => {
+                revert_tx.commit().await?;
+                Undo::Done {
+                    quiz_name,
+                    operation,
+                }
+            }
+            Err(error) if is_outdated(error.as_ref()) => {
+                revert_tx.rollback().await?;
+                Undo::Outdated { operation }
+            }
+            Err(error) => return Err(error),
+        };
+
+        tx.commit().await?;
+
+        Ok(Some(undo))
+    }
+}
+
+impl Subscription for Connection {
+    async fn toggle_subscription(
+        &self,
+        user_id: i64,
+        quiz_id: impl Into<String>,
+    ) -> Result<Option<bool>, Box<dyn Error + Send + Sync>> {
+        let mut tx = self.pool.begin().await?;
+
+        let Some(quiz_uuid) = sqlx::query!(
+            "SELECT uuid FROM quizes WHERE quizes.name = $1 AND quizes.deleted_at IS NULL",
+            quiz_id.into()
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        let removed = sqlx::query!(
+            "DELETE FROM subscriptions WHERE user_id = $1 AND quiz_id = $2",
+            user_id,
             quiz_uuid.uuid
         )
+        .execute(&mut *tx)
+        .await?;
+
+        let subscribed = removed.rows_affected() == 0;
+        if subscribed {
+            sqlx::query!(
+                "INSERT INTO subscriptions VALUES ($1, $2)",
+                user_id,
+                quiz_uuid.uuid
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(Some(subscribed))
+    }
+}
+
+impl Study for Connection {
+    async fn retreive_due_card(
+        &self,
+        user_id: i64,
+    ) -> Result<Option<(Question, StudyCard)>, Box<dyn Error + Send + Sync>> {
+        let mut tx = self.pool.begin().await?;
+
+        let record = sqlx::query!("SELECT questions.uuid, questions.text, study_cards.ease AS \"ease?\", study_cards.interval_days AS \"interval_days?\", study_cards.repetitions AS \"repetitions?\", study_cards.due_at AS \"due_at?\" FROM questions INNER JOIN quizes ON quizes.uuid = questions.quiz_id AND quizes.deleted_at IS NULL INNER JOIN subscriptions ON subscriptions.quiz_id = questions.quiz_id LEFT JOIN study_cards ON study_cards.question_id = questions.uuid AND study_cards.user_id = subscriptions.user_id WHERE questions.deleted_at IS NULL AND subscriptions.user_id = $1 AND (study_cards.due_at IS NULL OR study_cards.due_at <= NOW()) AND EXISTS (SELECT 1 FROM answers WHERE answers.question_id = questions.uuid) ORDER BY study_cards.due_at ASC NULLS LAST LIMIT 1", user_id).fetch_optional(&mut *tx).await?;
+
+        let Some(record) = record else {
+            return Ok(None);
+        };
+
+        let card = match (
+            record.ease,
+            record.interval_days,
+            record.repetitions,
+            record.due_at,
+        ) {
+            (Some(ease), Some(interval_days), Some(repetitions), Some(due_at)) => {
+                StudyCard::retreive(record.uuid, ease, interval_days, repetitions, due_at)
+            }
+            _ => StudyCard::new(record.uuid),
+        };
+
+        let mut question = Question::retreive(record.uuid, record.text);
+        let answers = sqlx::query!(
+            "SELECT uuid, text, is_correct FROM answers WHERE question_id = $1",
+            question.uuid()
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        answers
+            .into_iter()
+            .map(|answer| Answer::retreive(answer.uuid, answer.text, answer.is_correct))
+            .for_each(|answer| question.add_answer(answer));
+
+        tx.commit().await?;
+
+        Ok(Some((question, card)))
+    }
+
+    async fn retreive_next_due_time(
+        &self,
+        user_id: i64,
+    ) -> Result<Option<DateTime<Utc>>, Box<dyn Error + Send + Sync>> {
+        let record = sqlx::query!("SELECT MIN(study_cards.due_at) AS due_at FROM study_cards INNER JOIN questions ON questions.uuid = study_cards.question_id AND questions.deleted_at IS NULL INNER JOIN quizes ON quizes.uuid = questions.quiz_id AND quizes.deleted_at IS NULL INNER JOIN subscriptions ON subscriptions.quiz_id = questions.quiz_id AND subscriptions.user_id = study_cards.user_id WHERE study_cards.user_id = $1", user_id).fetch_one(&self.pool).await?;
+
+        Ok(record.due_at)
+    }
+
+    async fn save_card(
+        &self,
+        user_id: i64,
+        card: &StudyCard,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        sqlx::query!("INSERT INTO study_cards VALUES ($1, $2, $3, $4, $5, $6) ON CONFLICT (user_id, question_id) DO UPDATE SET ease = EXCLUDED.ease, interval_days = EXCLUDED.interval_days, repetitions = EXCLUDED.repetitions, due_at = EXCLUDED.due_at", user_id, card.question_id(), card.ease(), card.interval_days(), card.repetitions(), card.due_at()).execute(&self.pool).await?;
+
+        Ok(())
+    }
+}
+
+impl Schedule for Connection {
+    async fn set_reminder(&self, reminder: &Reminder) -> Result<(), Box<dyn Error + Send + Sync>> {
+        sqlx::query!("INSERT INTO reminders (user_id, time_of_day, time_zone, next_fire_at) VALUES ($1, $2, $3, $4) ON CONFLICT (user_id) DO UPDATE SET time_of_day = EXCLUDED.time_of_day, time_zone = EXCLUDED.time_zone, next_fire_at = EXCLUDED.next_fire_at", reminder.user_id(), reminder.time_of_day(), reminder.time_zone().name(), reminder.next_fire_at()).execute(&self.pool).await?;
+
+        Ok(())
+    }
+
+    async fn remove_reminder(&self, user_id: i64) -> Result<bool, Box<dyn Error + Send + Sync>> {
+        let removed = sqlx::query!("DELETE FROM reminders WHERE user_id = $1", user_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(removed.rows_affected() > 0)
+    }
+
+    async fn retreive_due_reminders(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<Reminder>, Box<dyn Error + Send + Sync>> {
+        let records = sqlx::query!(
+            "SELECT user_id, time_of_day, time_zone, next_fire_at FROM reminders WHERE next_fire_at <= $1",
+            now
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(records
+            .into_iter()
+            .filter_map(|r| {
+                r.time_zone.parse::<Tz>().ok().map(|time_zone| {
+                    Reminder::new(r.user_id, r.time_of_day, time_zone, r.next_fire_at)
+                })
+            })
+            .collect())
+    }
+
+    async fn schedule_push(
+        &self,
+        quiz_id: impl Into<String>,
+        chat_id: Option<i64>,
+        fire_at: DateTime<Utc>,
+    ) -> GenericError {
+        let quiz = sqlx::query!(
+            "SELECT uuid, name FROM quizes WHERE quizes.name = $1 AND quizes.deleted_at IS NULL",
+            quiz_id.into()
+        )
         .fetch_one(&self.pool)
         .await?;
 
-        Ok(added.text)
+        sqlx::query!(
+            "INSERT INTO scheduled_pushes (uuid, quiz_id, chat_id, fire_at) VALUES ($1, $2, $3, $4)",
+            Uuid::new_v4(),
+            quiz.uuid,
+            chat_id,
+            fire_at
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(quiz.name)
+    }
+
+    async fn retreive_due_pushes(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<ScheduledPush>, Box<dyn Error + Send + Sync>> {
+        let records = sqlx::query!("SELECT scheduled_pushes.uuid, scheduled_pushes.quiz_id, quizes.name, quizes.description, scheduled_pushes.chat_id, scheduled_pushes.fire_at FROM scheduled_pushes INNER JOIN quizes ON quizes.uuid = scheduled_pushes.quiz_id WHERE quizes.deleted_at IS NULL AND NOT scheduled_pushes.sent AND scheduled_pushes.fire_at <= $1", now).fetch_all(&self.pool).await?;
+
+        Ok(records
+            .into_iter()
+            .map(|r| {
+                ScheduledPush::retreive(
+                    r.uuid,
+                    r.quiz_id,
+                    r.name,
+                    r.description,
+                    r.chat_id,
+                    r.fire_at,
+                )
+            })
+            .collect())
+    }
+
+    async fn mark_push_sent(
+        &self,
+        push: &ScheduledPush,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        sqlx::query!(
+            "UPDATE scheduled_pushes SET sent = TRUE WHERE uuid = $1",
+            push.uuid()
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
     }
+
+    async fn retreive_subscribers(
+        &self,
+        quiz_id: &Uuid,
+    ) -> Result<Vec<i64>, Box<dyn Error + Send + Sync>> {
+        let records = sqlx::query!(
+            "SELECT user_id FROM subscriptions WHERE quiz_id = $1",
+            quiz_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(records.into_iter().map(|r| r.user_id).collect())
+    }
+}
+
+// Reads the quiz with its questions and answers, `None` if there is no such quiz or it is in the trash.
+async fn load_quiz(
+    tx: &mut PgConnection,
+    name: &str,
+) -> Result<Option<Quiz>, Box<dyn Error + Send + Sync>> {
+    let Some(quiz_record) = sqlx::query!(
+        "SELECT uuid, name, description, author, opens_at, closes_at, max_attempts, pass_mark_kind, pass_mark, native_polls, status, revision FROM quizes WHERE name = $1 AND deleted_at IS NULL",
+        name
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    else {
+        return Ok(None);
+    };
+
+    let mut quiz = Quiz::retreive(
+        quiz_record.uuid,
+        quiz_record.name,
+        quiz_record.description,
+        quiz_record.author,
+    );
+    quiz.set_availability(Availability::new(
+        quiz_record.opens_at,
+        quiz_record.closes_at,
+        quiz_record.max_attempts,
+    ));
+    quiz.set_native_polls(quiz_record.native_polls);
+    quiz.set_status(Status::retreive(&quiz_record.status));
+    quiz.set_revision(quiz_record.revision);
+
+    let tags = sqlx::query!(
+        "SELECT tags.name FROM tags INNER JOIN quiz_tags ON quiz_tags.tag_id = tags.uuid WHERE quiz_tags.quiz_id = $1 ORDER BY tags.name",
+        quiz_record.uuid
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+    quiz.set_tags(tags.into_iter().map(|tag| tag.name).collect());
+
+    let bands = sqlx::query!(
+        "SELECT label, min_percent FROM grade_bands WHERE quiz_id = $1",
+        quiz_record.uuid
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+    let pass_mark = quiz_record
+        .pass_mark_kind
+        .zip(quiz_record.pass_mark)
+        .and_then(|(kind, value)| PassMark::retreive(&kind, value));
+    quiz.set_grading(Grading::new(
+        pass_mark,
+        bands
+            .into_iter()
+            .map(|band| GradeBand::new(band.label, band.min_percent))
+            .collect(),
+    ));
+
+    let quiz_questions = sqlx::query!(
+        "SELECT uuid, text, quiz_id, points, penalty, explanation FROM questions WHERE quiz_id = $1 AND deleted_at IS NULL",
+        quiz_record.uuid
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+
+    for question_record in quiz_questions {
+        let mut question = Question::retreive(question_record.uuid, question_record.text);
+        question.set_scoring(question_record.points, question_record.penalty);
+        question.set_explanation(question_record.explanation);
+
+        let question_answers = sqlx::query!(
+            "SELECT uuid, text, is_correct, question_id FROM answers WHERE question_id = $1",
+            question_record.uuid
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        question_answers.into_iter().for_each(|answer| {
+            question.add_answer(Answer::retreive(
+                answer.uuid,
+                answer.text,
+                answer.is_correct,
+            ))
+        });
+
+        quiz.add_question(question);
+    }
+
+    Ok(Some(quiz))
+}
+
+// Stores the quiz as the snapshot of its revision and moves it to the next one.
+async fn snapshot(tx: &mut PgConnection, quiz: &Quiz) -> Result<i32, Box<dyn Error + Send + Sync>> {
+    sqlx::query!(
+        "INSERT INTO quiz_revisions VALUES ($1, $2, $3, NOW()) ON CONFLICT DO NOTHING",
+        quiz.uuid(),
+        quiz.revision(),
+        serde_json::to_value(quiz)?
+    )
+    .execute(&mut *tx)
+    .await?;
+    let updated = sqlx::query!(
+        "UPDATE quizes SET revision = revision + 1, revision_pending = FALSE WHERE uuid = $1 RETURNING revision",
+        quiz.uuid()
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    Ok(updated.revision)
+}
+
+// Snapshots the quiz before its content changes, if an edit session was opened or
+// attempts were started on the current revision since the last snapshot.
+async fn revise(tx: &mut PgConnection, name: &str) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let due = sqlx::query!(
+        r#"SELECT revision_pending OR EXISTS (SELECT 1 FROM attempts WHERE attempts.quiz_id = quizes.uuid AND attempts.revision = quizes.revision) AS "due!" FROM quizes WHERE name = $1 AND deleted_at IS NULL FOR UPDATE"#,
+        name
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .is_some_and(|record| record.due);
+
+    if due {
+        if let Some(quiz) = load_quiz(tx, name).await? {
+            snapshot(tx, &quiz).await?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn insert_tags(
+    tx: &mut PgConnection,
+    quiz_id: &Uuid,
+    tags: &[String],
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    for tag in tags {
+        let tag_id = sqlx::query!(
+            "INSERT INTO tags (uuid, name) VALUES ($1, $2) ON CONFLICT (name) DO UPDATE SET name = EXCLUDED.name RETURNING uuid",
+            Uuid::new_v4(),
+            tag
+        )
+        .fetch_one(&mut *tx)
+        .await?
+        .uuid;
+
+        sqlx::query!(
+            "INSERT INTO quiz_tags VALUES ($1, $2) ON CONFLICT DO NOTHING",
+            quiz_id,
+            tag_id
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    Ok(())
+}
+
+async fn rename_quiz(tx: &mut PgConnection, name: &str, new: &str) -> GenericError {
+    let renamed = sqlx::query!(
+        "UPDATE quizes SET name=$1 WHERE name=$2 RETURNING name",
+        new,
+        name
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    Ok(renamed.name)
+}
+
+async fn set_description(tx: &mut PgConnection, name: &str, new: &str) -> GenericError {
+    revise(tx, name).await?;
+
+    let updated = sqlx::query!(
+        "UPDATE quizes SET description=$1 WHERE name=$2 RETURNING description",
+        new,
+        name
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    Ok(updated.description)
+}
+
+async fn set_text(tx: &mut PgConnection, quiz: &str, question: &str, new: &str) -> GenericError {
+    revise(tx, quiz).await?;
+
+    let updated = sqlx::query!("UPDATE questions SET text = $1 FROM quizes WHERE quizes.uuid = questions.quiz_id AND quizes.name = $2 AND questions.deleted_at IS NULL AND questions.text = $3 RETURNING text", new, quiz, question).fetch_one(&mut *tx).await?;
+
+    Ok(updated.text)
+}
+
+async fn set_corectness(
+    tx: &mut PgConnection,
+    quiz: &str,
+    question: &str,
+    answer: &str,
+    is_correct: bool,
+) -> GenericError {
+    revise(tx, quiz).await?;
+
+    let updated = sqlx::query!("UPDATE answers SET is_correct = $1 FROM questions INNER JOIN quizes ON questions.quiz_id = quizes.uuid WHERE questions.uuid = answers.question_id AND quizes.name = $2 AND questions.deleted_at IS NULL AND questions.text = $3 AND answers.text = $4 RETURNING answers.text", is_correct, quiz, question, answer).fetch_one(&mut *tx).await?;
+
+    Ok(updated.text)
+}
+
+async fn insert_answer(
+    tx: &mut PgConnection,
+    quiz: &str,
+    question: &str,
+    new: &str,
+    is_correct: bool,
+) -> GenericError {
+    revise(tx, quiz).await?;
+
+    let quiz_uuid = sqlx::query!("SELECT uuid FROM quizes WHERE quizes.name = $1", quiz)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    let question_uuid = sqlx::query!(
+        "SELECT uuid FROM questions WHERE questions.quiz_id = $1 AND text = $2 AND deleted_at IS NULL",
+        quiz_uuid.uuid,
+        question
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let new_answer = Answer::new(new.to_owned(), is_correct);
+
+    let added = sqlx::query!(
+        "INSERT INTO answers VALUES ($1, $2, $3, $4) RETURNING text",
+        new_answer.uuid(),
+        new_answer.text(),
+        new_answer.is_correct(),
+        question_uuid.uuid
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    Ok(added.text)
+}
+
+async fn untrash_quiz(tx: &mut PgConnection, name: &str) -> GenericError {
+    let restored = sqlx::query!(
+        "UPDATE quizes SET deleted_at = NULL WHERE name = $1 AND deleted_at IS NOT NULL RETURNING name",
+        name
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    Ok(restored.name)
+}
+
+async fn untrash_question(tx: &mut PgConnection, quiz: &str, question: &str) -> GenericError {
+    revise(tx, quiz).await?;
+
+    // Several trashed questions may share the text, the latest one comes back.
+    let restored = sqlx::query!(
+        "UPDATE questions SET deleted_at = NULL WHERE uuid = (SELECT questions.uuid FROM questions INNER JOIN quizes ON questions.quiz_id = quizes.uuid WHERE quizes.name = $1 AND questions.text = $2 AND questions.deleted_at IS NOT NULL ORDER BY questions.deleted_at DESC LIMIT 1) RETURNING text",
+        quiz,
+        question
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    Ok(restored.text)
+}
+
+// What the change touched is gone, or its old name is taken again.
+fn is_outdated(error: &(dyn Error + Send + Sync + 'static)) -> bool {
+    match error.downcast_ref::<sqlx::Error>() {
+        Some(sqlx::Error::RowNotFound) => true,
+        Some(sqlx::Error::Database(error)) => error.is_unique_violation(),
+        _ => false,
+    }
+}
+
+// Applies the inverse of the change, returns the name of the quiz afterwards.
+async fn revert(tx: &mut PgConnection, quiz: &str, operation: &Operation) -> GenericError {
+    match operation {
+        Operation::Rename { old, .. } => return rename_quiz(tx, quiz, old).await,
+        Operation::EditDescription { old } => {
+            set_description(tx, quiz, old).await?;
+        }
+        Operation::EditQuestionText { old, new } => {
+            set_text(tx, quiz, new, old).await?;
+        }
+        Operation::EditCorrectness {
+            question,
+            answer,
+            was_correct,
+        } => {
+            set_corectness(tx, quiz, question, answer, *was_correct).await?;
+        }
+        Operation::DeleteQuiz => return untrash_quiz(tx, quiz).await,
+        Operation::DeleteQuestion { question } => {
+            untrash_question(tx, quiz, question).await?;
+        }
+        Operation::DeleteAnswer {
+            question,
+            answer,
+            is_correct,
+        } => {
+            insert_answer(tx, quiz, question, answer, *is_correct).await?;
+        }
+    }
+
+    Ok(quiz.to_owned())
 }
@@ -0,0 +1,205 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Minimal result needed to pass a quiz.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum PassMark {
+    Percentage(f64),
+    Absolute(f64),
+}
+
+/// Label given to every result scoring at least `min_percent`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GradeBand {
+    label: String,
+    min_percent: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Grading {
+    pass_mark: Option<PassMark>,
+    bands: Vec<GradeBand>,
+}
+
+/// How an attempt ended up according to the quiz grading.
+#[derive(Debug, Clone, Default)]
+pub struct Outcome {
+    passed: Option<bool>,
+    grade: Option<String>,
+}
+
+impl fmt::Display for PassMark {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PassMark::Percentage(percentage) => write!(f, "{}%", percentage),
+            PassMark::Absolute(points) => write!(f, "{} point(s)", points),
+        }
+    }
+}
+
+impl fmt::Display for Grading {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(pass_mark) = self.pass_mark {
+            writeln!(f, "Pass mark: {}", pass_mark)?;
+        }
+        if !self.bands.is_empty() {
+            let bands: Vec<String> = self
+                .bands
+                .iter()
+                .map(|band| format!("{} {}%", band.label, band.min_percent))
+                .collect();
+            writeln!(f, "Grades: {}", bands.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.passed {
+            Some(true) => write!(f, "Passed ✅")?,
+            Some(false) => write!(f, "Failed ❌")?,
+            None => (),
+        }
+        if let Some(grade) = &self.grade {
+            if self.passed.is_some() {
+                write!(f, ". ")?;
+            }
+            write!(f, "Grade: {}", grade)?;
+        }
+        Ok(())
+    }
+}
+
+impl PassMark {
+    /// Parses `60%` as a percentage and a bare `12` as points.
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        match text.strip_suffix('%') {
+            Some(percentage) => percentage
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|percentage| (0.0..=100.0).contains(percentage))
+                .map(PassMark::Percentage),
+            None => text
+                .parse::<f64>()
+                .ok()
+                .filter(|points| *points >= 0.0)
+                .map(PassMark::Absolute),
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            PassMark::Percentage(_) => "percentage",
+            PassMark::Absolute(_) => "absolute",
+        }
+    }
+
+    pub fn value(&self) -> f64 {
+        match self {
+            PassMark::Percentage(value) | PassMark::Absolute(value) => *value,
+        }
+    }
+
+    pub fn retreive(kind: &str, value: f64) -> Option<Self> {
+        match kind {
+            "percentage" => Some(PassMark::Percentage(value)),
+            "absolute" => Some(PassMark::Absolute(value)),
+            _ => None,
+        }
+    }
+}
+
+impl GradeBand {
+    pub fn new(label: String, min_percent: f64) -> Self {
+        Self { label, min_percent }
+    }
+
+    /// Parses comma or newline separated bands such as `A 90, B 75%, Needs work 0`.
+    pub fn parse_all(text: &str) -> Option<Vec<Self>> {
+        text.split([',', '\n'])
+            .map(str::trim)
+            .filter(|band| !band.is_empty())
+            .map(|band| {
+                let (label, min_percent) = band.rsplit_once(char::is_whitespace)?;
+                let min_percent = min_percent
+                    .trim_end_matches('%')
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|min_percent| (0.0..=100.0).contains(min_percent))?;
+                Some(Self::new(label.trim().to_owned(), min_percent))
+            })
+            .collect()
+    }
+
+    pub fn label(&self) -> &String {
+        &self.label
+    }
+
+    pub fn min_percent(&self) -> f64 {
+        self.min_percent
+    }
+}
+
+impl Grading {
+    pub fn new(pass_mark: Option<PassMark>, mut bands: Vec<GradeBand>) -> Self {
+        bands.sort_by(|a, b| b.min_percent.total_cmp(&a.min_percent));
+        Self { pass_mark, bands }
+    }
+
+    pub fn pass_mark(&self) -> Option<PassMark> {
+        self.pass_mark
+    }
+
+    pub fn bands(&self) -> &[GradeBand] {
+        &self.bands
+    }
+
+    pub fn evaluate(&self, score: f64, max_score: f64) -> Outcome {
+        let percent = if max_score > 0.0 {
+            score / max_score * 100.0
+        } else {
+            0.0
+        };
+
+        let passed = self.pass_mark.map(|pass_mark| match pass_mark {
+            PassMark::Percentage(min_percent) => percent >= min_percent,
+            PassMark::Absolute(min_score) => score >= min_score,
+        });
+        let grade = self
+            .bands
+            .iter()
+            .find(|band| percent >= band.min_percent)
+            .map(|band| band.label.clone());
+
+        Outcome { passed, grade }
+    }
+}
+
+/// Points rounded to two decimals without trailing zeros, so that sums of
+/// fractional weights read `2.3` rather than `2.3000000000000003`.
+pub fn format_points(points: f64) -> String {
+    let text = format!("{:.2}", points);
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    match text {
+        "-0" => "0".to_owned(),
+        text => text.to_owned(),
+    }
+}
+
+impl Outcome {
+    pub fn passed(&self) -> Option<bool> {
+        self.passed
+    }
+
+    pub fn grade(&self) -> Option<&String> {
+        self.grade.as_ref()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.passed.is_none() && self.grade.is_none()
+    }
+}
@@ -1,2 +1,11 @@
+pub mod attempt;
 pub mod connection;
+pub mod grading;
+pub mod operation;
+pub mod page;
 pub mod quiz;
+pub mod revision;
+pub mod schedule;
+pub mod study;
+pub mod tag;
+pub mod validator;
@@ -0,0 +1,73 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Editor change kept in the operation log, with what it takes to revert it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Operation {
+    Rename {
+        old: String,
+        new: String,
+    },
+    EditDescription {
+        old: String,
+    },
+    EditQuestionText {
+        old: String,
+        new: String,
+    },
+    EditCorrectness {
+        question: String,
+        answer: String,
+        was_correct: bool,
+    },
+    DeleteQuiz,
+    DeleteQuestion {
+        question: String,
+    },
+    DeleteAnswer {
+        question: String,
+        answer: String,
+        is_correct: bool,
+    },
+}
+
+/// Outcome of undoing the latest change.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Undo {
+    /// The change was reverted, the quiz is named `quiz_name` afterwards.
+    Done {
+        quiz_name: String,
+        operation: Operation,
+    },
+    /// What the change touched is gone, so it was dropped without reverting it.
+    Outdated { operation: Operation },
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operation::Rename { old, new } => {
+                write!(f, "renaming the quiz from '{}' to '{}'", old, new)
+            }
+            Operation::EditDescription { .. } => write!(f, "editing the quiz description"),
+            Operation::EditQuestionText { old, new } => {
+                write!(f, "editing question '{}' into '{}'", old, new)
+            }
+            Operation::EditCorrectness {
+                question, answer, ..
+            } => write!(
+                f,
+                "changing correctness of answer '{}' to question '{}'",
+                answer, question
+            ),
+            Operation::DeleteQuiz => write!(f, "deleting the quiz"),
+            Operation::DeleteQuestion { question } => {
+                write!(f, "deleting question '{}'", question)
+            }
+            Operation::DeleteAnswer {
+                question, answer, ..
+            } => write!(f, "deleting answer '{}' to question '{}'", answer, question),
+        }
+    }
+}
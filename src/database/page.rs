@@ -0,0 +1,39 @@
+/// Number of names shown on one page of a listing.
+pub const PAGE_SIZE: i64 = 8;
+
+/// One page of a filtered listing of names.
+#[derive(Debug, Clone, Default)]
+pub struct Page {
+    items: Vec<String>,
+    total: i64,
+}
+
+impl Page {
+    pub fn new(items: Vec<String>, total: i64) -> Self {
+        Self { items, total }
+    }
+
+    pub fn items(&self) -> &[String] {
+        &self.items
+    }
+
+    /// Number of names matching the filter over all pages.
+    pub fn total(&self) -> i64 {
+        self.total
+    }
+
+    pub fn page_count(&self) -> i64 {
+        (self.total + PAGE_SIZE - 1) / PAGE_SIZE
+    }
+}
+
+/// `ILIKE` pattern matching text that contains `query` literally.
+pub fn contains_pattern(query: &str) -> String {
+    format!(
+        "%{}%",
+        query
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_")
+    )
+}
@@ -1,24 +1,71 @@
 use std::{fmt, path::Display, vec};
 
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-#[derive(Debug, Clone)]
+use super::grading::Grading;
+
+const DEFAULT_POINTS: f64 = 1.0;
+
+const QUIZ_CODE_LEN: usize = 8;
+
+/// How long deleted quizes and questions can be restored.
+pub const TRASH_RETENTION: Duration = Duration::days(30);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Quiz {
     uuid: Uuid,
     title: String,
     description: String,
     author: String,
     questions: Vec<Question>,
+    availability: Availability,
+    grading: Grading,
+    native_polls: bool,
+    tags: Vec<String>,
+    status: Status,
+    revision: i32,
+}
+
+/// Only published quizes are offered to takers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum Status {
+    #[default]
+    Draft,
+    Published,
+    Archived,
 }
 
+/// When a quiz can be taken and how many times by the same user.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Availability {
+    opens_at: Option<DateTime<Utc>>,
+    closes_at: Option<DateTime<Utc>>,
+    max_attempts: Option<i32>,
+}
+
+/// Reason why a quiz can't be taken right now.
 #[derive(Debug, Clone)]
+pub enum Unavailable {
+    NotPublished,
+    NotOpenYet(DateTime<Utc>),
+    Closed(DateTime<Utc>),
+    AttemptsExhausted(i32),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Question {
     uuid: Uuid,
     text: String,
     answers: Vec<Answer>,
+    points: f64,
+    penalty: f64,
+    // Shown to the taker once the question is answered.
+    explanation: Option<String>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Answer {
     uuid: Uuid,
     text: String,
@@ -33,24 +80,90 @@ impl fmt::Display for Quiz {
         }
         write!(
             f,
-            "Title: {}\nDescription: {}\n\nBy @{}\n\nQuestions:{}\n",
+            "Title: {}\nDescription: {}\n{}\nBy @{}\nStatus: {}, revision {}\n{}{}{}\nQuestions:{}\n",
             self.title(),
             self.description(),
+            if self.tags.is_empty() {
+                String::new()
+            } else {
+                format!("Tags: {}\n", self.tags.join(", "))
+            },
             self.author(),
+            self.status.as_str(),
+            self.revision,
+            self.availability(),
+            self.grading(),
+            if self.native_polls {
+                "Delivery: native quiz polls\n"
+            } else {
+                ""
+            },
             questions
         )
     }
 }
 
+impl fmt::Display for Availability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(opens_at) = self.opens_at {
+            writeln!(f, "Opens: {}", opens_at.format("%Y-%m-%d %H:%M UTC"))?;
+        }
+        if let Some(closes_at) = self.closes_at {
+            writeln!(f, "Closes: {}", closes_at.format("%Y-%m-%d %H:%M UTC"))?;
+        }
+        if let Some(max_attempts) = self.max_attempts {
+            writeln!(f, "Attempts allowed: {}", max_attempts)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Unavailable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Unavailable::NotPublished => write!(f, "This quiz is not published."),
+            Unavailable::NotOpenYet(opens_at) => write!(
+                f,
+                "This quiz opens at {}.",
+                opens_at.format("%Y-%m-%d %H:%M UTC")
+            ),
+            Unavailable::Closed(closes_at) => write!(
+                f,
+                "This quiz was closed at {}.",
+                closes_at.format("%Y-%m-%d %H:%M UTC")
+            ),
+            Unavailable::AttemptsExhausted(max_attempts) => write!(
+                f,
+                "You have already used all {} allowed attempt(s) of this quiz.",
+                max_attempts
+            ),
+        }
+    }
+}
+
 impl fmt::Display for Question {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut answers = String::new();
         for (i, answer) in self.answers().into_iter().enumerate() {
             answers.push_str(&format!("{}){}\n", i + 1, answer.to_string()));
         }
+        if let Some(explanation) = self.explanation() {
+            answers.push_str(&format!("Explanation: {}\n", explanation));
+        }
         answers.push('\n');
 
-        write!(f, "{}\n{}", self.text(), answers)
+        if self.points() != DEFAULT_POINTS || self.penalty() != 0.0 {
+            write!(
+                f,
+                "{} [{} pt(s), -{} if wrong]\n{}",
+                self.text(),
+                self.points(),
+                self.penalty(),
+                answers
+            )
+        } else {
+            write!(f, "{}\n{}", self.text(), answers)
+        }
     }
 }
 
@@ -78,6 +191,12 @@ impl Quiz {
             description,
             author,
             questions: questions.unwrap_or_default(),
+            availability: Availability::default(),
+            grading: Grading::default(),
+            native_polls: false,
+            tags: vec![],
+            status: Status::default(),
+            revision: 1,
         }
     }
 
@@ -88,6 +207,12 @@ impl Quiz {
             description,
             author,
             questions: vec![],
+            availability: Availability::default(),
+            grading: Grading::default(),
+            native_polls: false,
+            tags: vec![],
+            status: Status::default(),
+            revision: 1,
         }
     }
 
@@ -95,6 +220,11 @@ impl Quiz {
         &self.uuid
     }
 
+    /// Short stable code used in deep links instead of the name.
+    pub fn code(&self) -> String {
+        quiz_code(&self.uuid)
+    }
+
     pub fn title(&self) -> &String {
         &self.title
     }
@@ -115,15 +245,151 @@ impl Quiz {
         self.questions.push(question);
     }
 
+    pub fn availability(&self) -> &Availability {
+        &self.availability
+    }
+
+    pub fn set_availability(&mut self, availability: Availability) {
+        self.availability = availability;
+    }
+
+    /// Best attainable score. Questions without answers can't be played, so they don't count.
+    pub fn max_score(&self) -> f64 {
+        self.questions
+            .iter()
+            .filter(|question| !question.answers().is_empty())
+            .map(|question| question.points())
+            .sum()
+    }
+
+    pub fn grading(&self) -> &Grading {
+        &self.grading
+    }
+
+    pub fn set_grading(&mut self, grading: Grading) {
+        self.grading = grading;
+    }
+
+    /// Whether questions are sent as native Telegram quiz polls instead of buttons.
+    pub fn native_polls(&self) -> bool {
+        self.native_polls
+    }
+
+    pub fn set_native_polls(&mut self, native_polls: bool) {
+        self.native_polls = native_polls;
+    }
+
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    pub fn set_tags(&mut self, tags: Vec<String>) {
+        self.tags = tags;
+    }
+
+    pub fn status(&self) -> Status {
+        self.status
+    }
+
+    pub fn set_status(&mut self, status: Status) {
+        self.status = status;
+    }
+
+    /// Revision edits currently go to; earlier ones are kept as snapshots.
+    pub fn revision(&self) -> i32 {
+        self.revision
+    }
+
+    pub fn set_revision(&mut self, revision: i32) {
+        self.revision = revision;
+    }
+
+    /// Whether the quiz is published and available right now.
+    pub fn check(&self, now: DateTime<Utc>, attempts_taken: i64) -> Result<(), Unavailable> {
+        if self.status != Status::Published {
+            return Err(Unavailable::NotPublished);
+        }
+
+        self.availability.check(now, attempts_taken)
+    }
+
     // pub fn create(title: String, description: String, author: String, questions: Vec<Question>) -> Self
 }
 
+pub fn quiz_code(uuid: &Uuid) -> String {
+    uuid.simple().to_string()[..QUIZ_CODE_LEN].to_uppercase()
+}
+
+impl Status {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Status::Draft => "draft",
+            Status::Published => "published",
+            Status::Archived => "archived",
+        }
+    }
+
+    pub fn retreive(status: &str) -> Self {
+        match status {
+            "published" => Status::Published,
+            "archived" => Status::Archived,
+            _ => Status::Draft,
+        }
+    }
+}
+
+impl Availability {
+    pub fn new(
+        opens_at: Option<DateTime<Utc>>,
+        closes_at: Option<DateTime<Utc>>,
+        max_attempts: Option<i32>,
+    ) -> Self {
+        Self {
+            opens_at,
+            closes_at,
+            max_attempts,
+        }
+    }
+
+    pub fn opens_at(&self) -> Option<DateTime<Utc>> {
+        self.opens_at
+    }
+
+    pub fn closes_at(&self) -> Option<DateTime<Utc>> {
+        self.closes_at
+    }
+
+    pub fn max_attempts(&self) -> Option<i32> {
+        self.max_attempts
+    }
+
+    pub fn check(&self, now: DateTime<Utc>, attempts_taken: i64) -> Result<(), Unavailable> {
+        if let Some(opens_at) = self.opens_at.filter(|opens_at| now < *opens_at) {
+            return Err(Unavailable::NotOpenYet(opens_at));
+        }
+        if let Some(closes_at) = self.closes_at.filter(|closes_at| now >= *closes_at) {
+            return Err(Unavailable::Closed(closes_at));
+        }
+        if let Some(max_attempts) = self
+            .max_attempts
+            .filter(|max_attempts| attempts_taken >= *max_attempts as i64)
+        {
+            return Err(Unavailable::AttemptsExhausted(max_attempts));
+        }
+
+        Ok(())
+    }
+}
+
 impl Question {
     pub fn new(text: String, answers: Option<Vec<Answer>>) -> Self {
         Self {
             uuid: Uuid::new_v4(),
             text,
             answers: answers.unwrap_or_default(),
+            points: DEFAULT_POINTS,
+            penalty: 0.0,
+            explanation: None,
         }
     }
 
@@ -132,6 +398,9 @@ impl Question {
             uuid,
             text,
             answers: vec![],
+            points: DEFAULT_POINTS,
+            penalty: 0.0,
+            explanation: None,
         }
     }
 
@@ -143,10 +412,40 @@ impl Question {
         &self.answers
     }
 
+    pub fn set_text(&mut self, text: String) {
+        self.text = text;
+    }
+
     pub fn add_answer(&mut self, answer: Answer) {
         self.answers.push(answer);
     }
 
+    pub fn answers_mut(&mut self) -> &mut Vec<Answer> {
+        &mut self.answers
+    }
+
+    pub fn points(&self) -> f64 {
+        self.points
+    }
+
+    /// Points taken away for a wrong answer. Skipping the question costs nothing.
+    pub fn penalty(&self) -> f64 {
+        self.penalty
+    }
+
+    pub fn set_scoring(&mut self, points: f64, penalty: f64) {
+        self.points = points;
+        self.penalty = penalty;
+    }
+
+    pub fn explanation(&self) -> Option<&str> {
+        self.explanation.as_deref()
+    }
+
+    pub fn set_explanation(&mut self, explanation: Option<String>) {
+        self.explanation = explanation;
+    }
+
     pub fn uuid(&self) -> &Uuid {
         &self.uuid
     }
@@ -177,6 +476,14 @@ impl Answer {
         self.is_correct
     }
 
+    pub fn set_text(&mut self, text: String) {
+        self.text = text;
+    }
+
+    pub fn set_correct(&mut self, is_correct: bool) {
+        self.is_correct = is_correct;
+    }
+
     pub fn uuid(&self) -> &Uuid {
         &self.uuid
     }
@@ -0,0 +1,37 @@
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+
+/// Snapshot of a quiz taken when an edit session moved it to the next revision.
+#[derive(Debug, Clone)]
+pub struct Revision {
+    number: i32,
+    saved_at: DateTime<Utc>,
+    questions: i32,
+}
+
+impl fmt::Display for Revision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Revision {}: saved {}, {} question(s)",
+            self.number,
+            self.saved_at.format("%Y-%m-%d %H:%M UTC"),
+            self.questions
+        )
+    }
+}
+
+impl Revision {
+    pub fn retreive(number: i32, saved_at: DateTime<Utc>, questions: i32) -> Self {
+        Self {
+            number,
+            saved_at,
+            questions,
+        }
+    }
+
+    pub fn number(&self) -> i32 {
+        self.number
+    }
+}
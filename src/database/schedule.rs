@@ -0,0 +1,99 @@
+use chrono::{DateTime, NaiveTime, Utc};
+use chrono_tz::Tz;
+use uuid::Uuid;
+
+/// Daily reminder a user opted in to.
+#[derive(Debug, Clone)]
+pub struct Reminder {
+    user_id: i64,
+    time_of_day: NaiveTime,
+    time_zone: Tz,
+    next_fire_at: DateTime<Utc>,
+}
+
+/// One-off announcement of a quiz, sent either to a chat or to the quiz subscribers.
+#[derive(Debug, Clone)]
+pub struct ScheduledPush {
+    uuid: Uuid,
+    quiz_id: Uuid,
+    quiz_name: String,
+    quiz_description: String,
+    chat_id: Option<i64>,
+    fire_at: DateTime<Utc>,
+}
+
+impl Reminder {
+    pub fn new(
+        user_id: i64,
+        time_of_day: NaiveTime,
+        time_zone: Tz,
+        next_fire_at: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            user_id,
+            time_of_day,
+            time_zone,
+            next_fire_at,
+        }
+    }
+
+    pub fn user_id(&self) -> i64 {
+        self.user_id
+    }
+
+    pub fn time_of_day(&self) -> &NaiveTime {
+        &self.time_of_day
+    }
+
+    pub fn time_zone(&self) -> &Tz {
+        &self.time_zone
+    }
+
+    pub fn next_fire_at(&self) -> &DateTime<Utc> {
+        &self.next_fire_at
+    }
+}
+
+impl ScheduledPush {
+    pub fn retreive(
+        uuid: Uuid,
+        quiz_id: Uuid,
+        quiz_name: String,
+        quiz_description: String,
+        chat_id: Option<i64>,
+        fire_at: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            uuid,
+            quiz_id,
+            quiz_name,
+            quiz_description,
+            chat_id,
+            fire_at,
+        }
+    }
+
+    pub fn uuid(&self) -> &Uuid {
+        &self.uuid
+    }
+
+    pub fn quiz_id(&self) -> &Uuid {
+        &self.quiz_id
+    }
+
+    pub fn quiz_name(&self) -> &String {
+        &self.quiz_name
+    }
+
+    pub fn quiz_description(&self) -> &String {
+        &self.quiz_description
+    }
+
+    pub fn chat_id(&self) -> Option<i64> {
+        self.chat_id
+    }
+
+    pub fn fire_at(&self) -> &DateTime<Utc> {
+        &self.fire_at
+    }
+}
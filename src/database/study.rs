@@ -0,0 +1,127 @@
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+
+const DEFAULT_EASE: f64 = 2.5;
+const MIN_EASE: f64 = 1.3;
+
+/// Scheduling data of one question for one user, following the SM-2 algorithm.
+#[derive(Debug, Clone)]
+pub struct StudyCard {
+    question_id: Uuid,
+    ease: f64,
+    interval_days: i32,
+    repetitions: i32,
+    due_at: DateTime<Utc>,
+}
+
+impl StudyCard {
+    pub fn new(question_id: Uuid) -> Self {
+        Self {
+            question_id,
+            ease: DEFAULT_EASE,
+            interval_days: 0,
+            repetitions: 0,
+            due_at: Utc::now(),
+        }
+    }
+
+    pub fn retreive(
+        question_id: Uuid,
+        ease: f64,
+        interval_days: i32,
+        repetitions: i32,
+        due_at: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            question_id,
+            ease,
+            interval_days,
+            repetitions,
+            due_at,
+        }
+    }
+
+    /// Reschedules the card after a review graded from 0 (blackout) to 5 (perfect recall).
+    pub fn review(&mut self, quality: u8, now: DateTime<Utc>) {
+        let quality = quality.min(5);
+
+        if quality < 3 {
+            self.repetitions = 0;
+            self.interval_days = 1;
+        } else {
+            self.interval_days = match self.repetitions {
+                0 => 1,
+                1 => 6,
+                _ => (self.interval_days as f64 * self.ease).round() as i32,
+            };
+            self.repetitions += 1;
+        }
+
+        let penalty = (5 - quality) as f64;
+        self.ease = (self.ease + 0.1 - penalty * (0.08 + penalty * 0.02)).max(MIN_EASE);
+        self.due_at = now + Duration::days(self.interval_days as i64);
+    }
+
+    pub fn question_id(&self) -> &Uuid {
+        &self.question_id
+    }
+
+    pub fn ease(&self) -> f64 {
+        self.ease
+    }
+
+    pub fn interval_days(&self) -> i32 {
+        self.interval_days
+    }
+
+    pub fn repetitions(&self) -> i32 {
+        self.repetitions
+    }
+
+    pub fn due_at(&self) -> &DateTime<Utc> {
+        &self.due_at
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn review_follows_sm2() {
+        // (case, ease, interval, repetitions, quality, expected ease, interval, repetitions)
+        let cases = [
+            ("first success", DEFAULT_EASE, 0, 0, 4, 2.5, 1, 1),
+            ("second success", 2.5, 1, 1, 5, 2.6, 6, 2),
+            ("third success", 2.5, 6, 2, 4, 2.5, 15, 3),
+            ("hard success lowers the ease", 2.5, 6, 2, 3, 2.36, 15, 3),
+            ("failure resets the interval", 2.5, 15, 3, 1, 1.96, 1, 0),
+            ("ease stays above the floor", 1.4, 6, 2, 0, MIN_EASE, 1, 0),
+            ("quality above 5 counts as 5", 2.5, 1, 1, 9, 2.6, 6, 2),
+        ];
+        let now = Utc::now();
+
+        for (name, ease, interval, repetitions, quality, new_ease, new_interval, new_repetitions) in
+            cases
+        {
+            let mut card = StudyCard::retreive(Uuid::new_v4(), ease, interval, repetitions, now);
+
+            card.review(quality, now);
+
+            assert!(
+                (card.ease() - new_ease).abs() < 1e-9,
+                "{}: ease {}",
+                name,
+                card.ease()
+            );
+            assert_eq!(card.interval_days(), new_interval, "{}", name);
+            assert_eq!(card.repetitions(), new_repetitions, "{}", name);
+            assert_eq!(
+                *card.due_at(),
+                now + Duration::days(new_interval as i64),
+                "{}",
+                name
+            );
+        }
+    }
+}
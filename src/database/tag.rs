@@ -0,0 +1,18 @@
+const MAX_TAG_LEN: usize = 32;
+
+/// Tags from a comma separated text, lowercased and without duplicates.
+/// `None` if any tag is longer than [`MAX_TAG_LEN`].
+pub fn parse_tags(text: &str) -> Option<Vec<String>> {
+    let mut tags: Vec<String> = vec![];
+    for tag in text.split(',') {
+        let tag = tag.trim().trim_start_matches('#').to_lowercase();
+        if tag.chars().count() > MAX_TAG_LEN {
+            return None;
+        }
+        if !tag.is_empty() && !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+
+    Some(tags)
+}
@@ -0,0 +1,341 @@
+use std::fmt;
+
+use super::quiz::Quiz;
+use crate::runner::question_text;
+
+/// Telegram limit on callback data, which carries answer texts in the private runner.
+const MAX_CALLBACK_DATA_LEN: usize = 64;
+/// Telegram limit on message text.
+pub(crate) const MAX_MESSAGE_LEN: usize = 4096;
+
+/// Structural problem found in a quiz.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Problem {
+    NoQuestions,
+    NoAnswers(String),
+    NoCorrectAnswer(String),
+    DuplicateAnswer { question: String, answer: String },
+    AnswerTooLong { question: String, answer: String },
+    QuestionTooLong(String),
+    DuplicateQuestion(String),
+    EmptyDescription,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    /// The quiz can't be published.
+    Error,
+    /// The quiz works, but likely not as the author intended.
+    Warning,
+}
+
+/// Every problem found in a quiz, in question order.
+#[derive(Debug, Clone, Default)]
+pub struct Report {
+    problems: Vec<Problem>,
+}
+
+impl Problem {
+    pub fn severity(&self) -> Severity {
+        match self {
+            Problem::DuplicateQuestion(_) | Problem::EmptyDescription => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Problem::NoQuestions => write!(f, "The quiz has no questions."),
+            Problem::NoAnswers(question) => write!(
+                f,
+                "Question '{}' has no answers, so it would be skipped.",
+                question
+            ),
+            Problem::NoCorrectAnswer(question) => {
+                write!(f, "Question '{}' has no correct answer.", question)
+            }
+            Problem::DuplicateAnswer { question, answer } => write!(
+                f,
+                "Question '{}' has answer '{}' more than once.",
+                question, answer
+            ),
+            Problem::AnswerTooLong { question, answer } => write!(
+                f,
+                "Answer '{}' of question '{}' is longer than {} bytes and doesn't fit a button.",
+                answer, question, MAX_CALLBACK_DATA_LEN
+            ),
+            Problem::QuestionTooLong(question) => write!(
+                f,
+                "Question '{}...' with its number is longer than {} characters.",
+                question.chars().take(32).collect::<String>(),
+                MAX_MESSAGE_LEN
+            ),
+            Problem::DuplicateQuestion(question) => write!(
+                f,
+                "Question '{}' appears more than once, only the first can be picked in the editor.",
+                question
+            ),
+            Problem::EmptyDescription => write!(f, "The quiz has no description."),
+        }
+    }
+}
+
+impl Report {
+    pub fn problems(&self) -> &[Problem] {
+        &self.problems
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.problems
+            .iter()
+            .any(|problem| problem.severity() == Severity::Error)
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.problems.is_empty() {
+            return write!(f, "No problems found ✅");
+        }
+        for problem in &self.problems {
+            let mark = match problem.severity() {
+                Severity::Error => "❌",
+                Severity::Warning => "⚠️",
+            };
+            writeln!(f, "{} {}", mark, problem)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn validate(quiz: &Quiz) -> Report {
+    let mut problems = vec![];
+
+    if quiz.description().trim().is_empty() {
+        problems.push(Problem::EmptyDescription);
+    }
+    if quiz.questions().is_empty() {
+        problems.push(Problem::NoQuestions);
+    }
+
+    let mut seen_questions: Vec<String> = vec![];
+    for (idx, question) in quiz.questions().iter().enumerate() {
+        let text = question.text();
+        if seen_questions.contains(&text) {
+            problems.push(Problem::DuplicateQuestion(text.clone()));
+        }
+        // Measured as sent, with the question number in front.
+        if question_text(quiz, idx).chars().count() > MAX_MESSAGE_LEN {
+            problems.push(Problem::QuestionTooLong(text.clone()));
+        }
+
+        let answers = question.answers();
+        if answers.is_empty() {
+            problems.push(Problem::NoAnswers(text.clone()));
+        } else if !answers.iter().any(|answer| answer.is_correct()) {
+            problems.push(Problem::NoCorrectAnswer(text.clone()));
+        }
+
+        let mut seen_answers: Vec<String> = vec![];
+        for answer in answers {
+            let answer = answer.text();
+            if seen_answers.contains(&answer) {
+                problems.push(Problem::DuplicateAnswer {
+                    question: text.clone(),
+                    answer: answer.clone(),
+                });
+            }
+            if answer.len() > MAX_CALLBACK_DATA_LEN {
+                problems.push(Problem::AnswerTooLong {
+                    question: text.clone(),
+                    answer: answer.clone(),
+                });
+            }
+            seen_answers.push(answer);
+        }
+
+        seen_questions.push(text);
+    }
+
+    Report { problems }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::quiz::{Answer, Question};
+
+    fn answer(text: &str, is_correct: bool) -> Answer {
+        Answer::new(text.to_owned(), is_correct)
+    }
+
+    fn question(text: &str, answers: Vec<Answer>) -> Question {
+        Question::new(text.to_owned(), Some(answers))
+    }
+
+    fn quiz(description: &str, questions: Vec<Question>) -> Quiz {
+        Quiz::new(
+            "Quiz".to_owned(),
+            description.to_owned(),
+            "author".to_owned(),
+            Some(questions),
+        )
+    }
+
+    fn capital() -> Question {
+        question(
+            "Capital of France?",
+            vec![answer("Paris", true), answer("Rome", false)],
+        )
+    }
+
+    #[test]
+    fn valid_quiz_has_no_problems() {
+        let report = validate(&quiz("Geography", vec![capital()]));
+
+        assert!(report.is_empty());
+        assert!(!report.has_errors());
+    }
+
+    #[test]
+    fn finds_each_problem() {
+        let long_answer = "a".repeat(MAX_CALLBACK_DATA_LEN + 1);
+        let long_question = "q".repeat(MAX_MESSAGE_LEN + 1);
+        let cases = vec![
+            (
+                "no questions",
+                quiz("Geography", vec![]),
+                Problem::NoQuestions,
+            ),
+            (
+                "no answers",
+                quiz("Geography", vec![question("Empty?", vec![])]),
+                Problem::NoAnswers("Empty?".to_owned()),
+            ),
+            (
+                "no correct answer",
+                quiz(
+                    "Geography",
+                    vec![question("Wrong?", vec![answer("No", false)])],
+                ),
+                Problem::NoCorrectAnswer("Wrong?".to_owned()),
+            ),
+            (
+                "duplicate answer",
+                quiz(
+                    "Geography",
+                    vec![question(
+                        "Twice?",
+                        vec![answer("Yes", true), answer("Yes", false)],
+                    )],
+                ),
+                Problem::DuplicateAnswer {
+                    question: "Twice?".to_owned(),
+                    answer: "Yes".to_owned(),
+                },
+            ),
+            (
+                "answer too long",
+                quiz(
+                    "Geography",
+                    vec![question("Long?", vec![answer(&long_answer, true)])],
+                ),
+                Problem::AnswerTooLong {
+                    question: "Long?".to_owned(),
+                    answer: long_answer.clone(),
+                },
+            ),
+            (
+                "question too long",
+                quiz(
+                    "Geography",
+                    vec![question(&long_question, vec![answer("Yes", true)])],
+                ),
+                Problem::QuestionTooLong(long_question.clone()),
+            ),
+            (
+                "duplicate question",
+                quiz("Geography", vec![capital(), capital()]),
+                Problem::DuplicateQuestion("Capital of France?".to_owned()),
+            ),
+            (
+                "empty description",
+                quiz("  ", vec![capital()]),
+                Problem::EmptyDescription,
+            ),
+        ];
+
+        for (name, quiz, expected) in cases {
+            let report = validate(&quiz);
+            assert_eq!(report.problems(), [expected], "{}", name);
+        }
+    }
+
+    #[test]
+    fn answer_length_counts_bytes() {
+        // 32 two-byte characters fit in 64 bytes, 33 don't.
+        let fits = quiz(
+            "Geography",
+            vec![question("Fits?", vec![answer(&"é".repeat(32), true)])],
+        );
+        let too_long = quiz(
+            "Geography",
+            vec![question("Fits?", vec![answer(&"é".repeat(33), true)])],
+        );
+
+        assert!(validate(&fits).is_empty());
+        assert!(validate(&too_long).has_errors());
+    }
+
+    #[test]
+    fn question_length_counts_its_number() {
+        // "Question #1/1\n" takes 14 characters of the message.
+        let fits = quiz(
+            "Geography",
+            vec![question(
+                &"q".repeat(MAX_MESSAGE_LEN - 14),
+                vec![answer("Yes", true)],
+            )],
+        );
+        let too_long = quiz(
+            "Geography",
+            vec![question(
+                &"q".repeat(MAX_MESSAGE_LEN - 13),
+                vec![answer("Yes", true)],
+            )],
+        );
+
+        assert!(validate(&fits).is_empty());
+        assert!(validate(&too_long).has_errors());
+    }
+
+    #[test]
+    fn warnings_alone_are_not_errors() {
+        let report = validate(&quiz("", vec![capital(), capital()]));
+
+        assert_eq!(
+            report.problems(),
+            [
+                Problem::EmptyDescription,
+                Problem::DuplicateQuestion("Capital of France?".to_owned()),
+            ]
+        );
+        assert!(!report.is_empty());
+        assert!(!report.has_errors());
+    }
+
+    #[test]
+    fn any_error_is_an_error() {
+        let report = validate(&quiz("", vec![capital(), question("Empty?", vec![])]));
+
+        assert!(report.has_errors());
+        assert_eq!(Problem::NoQuestions.severity(), Severity::Error);
+        assert_eq!(Problem::EmptyDescription.severity(), Severity::Warning);
+    }
+}
@@ -1,19 +1,37 @@
 use std::sync::Arc;
 
-use teloxide::{payloads::SendMessageSetters, prelude::Requester, types::Message, Bot};
+use chrono::{DateTime, Utc};
+use teloxide::{
+    payloads::SendMessageSetters,
+    prelude::Requester,
+    types::{ChatId, InputFile, Me, Message, ReplyMarkup},
+    Bot,
+};
 use tracing::instrument;
 
 use crate::{
-    database::connection::{
-        CreateAnswer, CreateQuestion, DeleteAnswer, DeleteQuestion, DeleteQuiz, EditAnswer,
-        EditQuestion, EditQuiz, RetreiveAnswer, RetreiveQuestion, RetreiveQuiz,
+    database::{
+        connection::{
+            CreateAnswer, CreateQuestion, CreateQuiz, DeleteAnswer, DeleteQuestion, DeleteQuiz,
+            EditAnswer, EditQuestion, EditQuiz, Operations, RetreiveAnswer, RetreiveQuestion,
+            RetreiveQuiz, Revisions, Schedule, Trash,
+        },
+        grading::{GradeBand, Grading, PassMark},
+        operation::{Operation, Undo},
+        quiz::{Answer, Availability, Question, Status, TRASH_RETENTION},
+        tag::parse_tags,
+        validator::validate,
     },
-    keyboard::{self, edit_question_keyboard, yes_no_keyboard},
-    state::QuizState,
+    formats::aiken::{self, Skipped},
+    inline::quiz_link,
+    keyboard::{self, edit_question_keyboard, yes_no_keyboard, ROLL_BACK_PREFIX},
+    listing, navigation,
+    scheduler::parse_datetime,
+    state::{Listing, QuizState},
     HandlerResult, UserDialogue,
 };
 #[instrument(level = "info", skip(connection, bot, dialogue))]
-pub(crate) async fn edit_corectness<Connect: EditAnswer>(
+pub(crate) async fn edit_corectness<Connect: EditAnswer + RetreiveAnswer + Operations>(
     bot: Bot,
     msg: Message,
     dialogue: UserDialogue,
@@ -22,9 +40,15 @@ pub(crate) async fn edit_corectness<Connect: EditAnswer>(
 ) -> HandlerResult {
     match msg.text() {
         Some("Yes") | Some("Yes✔") => {
-            connection
-                .edit_corectness(&quiz_name, &question_name, &answer_name, true)
-                .await?;
+            set_corectness(
+                connection.as_ref(),
+                msg.chat.id.0,
+                &quiz_name,
+                &question_name,
+                &answer_name,
+                true,
+            )
+            .await?;
             bot.send_message(
                 msg.chat.id,
                 format!("Answer {} is now correct.", &answer_name),
@@ -39,9 +63,15 @@ pub(crate) async fn edit_corectness<Connect: EditAnswer>(
                 .await?;
         }
         Some("No") | Some("No❌") => {
-            connection
-                .edit_corectness(&quiz_name, &question_name, &answer_name, false)
-                .await?;
+            set_corectness(
+                connection.as_ref(),
+                msg.chat.id.0,
+                &quiz_name,
+                &question_name,
+                &answer_name,
+                false,
+            )
+            .await?;
             bot.send_message(
                 msg.chat.id,
                 format!("Answer {} is now incorrect.", &answer_name),
@@ -64,6 +94,37 @@ pub(crate) async fn edit_corectness<Connect: EditAnswer>(
     Ok(())
 }
 
+async fn set_corectness<Connect: EditAnswer + RetreiveAnswer + Operations>(
+    connection: &Connect,
+    user_id: i64,
+    quiz_name: &str,
+    question_name: &str,
+    answer_name: &str,
+    is_correct: bool,
+) -> HandlerResult {
+    let answer = connection
+        .retreive_answer(quiz_name, question_name, answer_name)
+        .await?;
+    connection
+        .edit_corectness(quiz_name, question_name, answer_name, is_correct)
+        .await?;
+    if let Some(answer) = answer.filter(|answer| answer.is_correct() != is_correct) {
+        connection
+            .log_operation(
+                user_id,
+                quiz_name,
+                &Operation::EditCorrectness {
+                    question: question_name.to_owned(),
+                    answer: answer_name.to_owned(),
+                    was_correct: answer.is_correct(),
+                },
+            )
+            .await?;
+    }
+
+    Ok(())
+}
+
 #[instrument(level = "info", skip(connection, bot, dialogue))]
 pub(crate) async fn edit_answer_text<Connect: EditAnswer>(
     bot: Bot,
@@ -101,7 +162,7 @@ pub(crate) async fn edit_answer_text<Connect: EditAnswer>(
 }
 
 #[instrument(level = "info", skip(connection, bot, dialogue))]
-pub(crate) async fn edit_question_text<Connect: EditQuestion>(
+pub(crate) async fn edit_question_text<Connect: EditQuestion + Operations>(
     bot: Bot,
     msg: Message,
     dialogue: UserDialogue,
@@ -113,13 +174,23 @@ pub(crate) async fn edit_question_text<Connect: EditQuestion>(
             let new_text = connection
                 .edit_text(&quiz_name, &question_name, new_text)
                 .await?;
+            connection
+                .log_operation(
+                    msg.chat.id.0,
+                    &quiz_name,
+                    &Operation::EditQuestionText {
+                        old: question_name,
+                        new: new_text.clone(),
+                    },
+                )
+                .await?;
             bot.send_message(msg.chat.id, "Question name updated.")
                 .reply_markup(keyboard::edit_question_keyboard())
                 .await?;
             dialogue
                 .update(QuizState::HandleQuestion {
                     quiz_name,
-                    question_name,
+                    question_name: new_text,
                 })
                 .await?;
         }
@@ -133,23 +204,35 @@ pub(crate) async fn edit_question_text<Connect: EditQuestion>(
 }
 
 #[instrument(level = "info", skip(connection, bot, dialogue))]
-pub(crate) async fn handle_answer<Connect: DeleteAnswer + RetreiveQuestion>(
+pub(crate) async fn edit_question_scoring<Connect: EditQuestion>(
     bot: Bot,
     msg: Message,
     dialogue: UserDialogue,
-    (quiz_name, question_name, answer_name): (String, String, String),
+    (quiz_name, question_name): (String, String),
     connection: Arc<Connect>,
 ) -> HandlerResult {
-    match msg.text() {
-        Some("/back") => {
-            let question = connection
-                .retreive_question(&quiz_name, &question_name)
-                .await?
-                .unwrap();
-            bot.send_message(msg.chat.id, "Returning back.").await?;
-            bot.send_message(msg.chat.id, question.to_string())
-                .reply_markup(keyboard::edit_question_keyboard())
+    let scoring = msg.text().and_then(|text| {
+        let mut numbers = text.split_whitespace().map(|number| number.parse::<f64>());
+        match (numbers.next(), numbers.next(), numbers.next()) {
+            (Some(Ok(points)), penalty, None) => Some((points, penalty.unwrap_or(Ok(0.0)).ok()?)),
+            _ => None,
+        }
+    });
+
+    match scoring {
+        Some((points, penalty)) if points > 0.0 && penalty >= 0.0 => {
+            connection
+                .edit_scoring(&quiz_name, &question_name, points, penalty)
                 .await?;
+            bot.send_message(
+                msg.chat.id,
+                format!(
+                    "Question is now worth {} point(s), {} taken away for a wrong answer.",
+                    points, penalty
+                ),
+            )
+            .reply_markup(keyboard::edit_question_keyboard())
+            .await?;
             dialogue
                 .update(QuizState::HandleQuestion {
                     quiz_name,
@@ -157,10 +240,49 @@ pub(crate) async fn handle_answer<Connect: DeleteAnswer + RetreiveQuestion>(
                 })
                 .await?;
         }
+        _ => {
+            bot.send_message(
+                msg.chat.id,
+                "Invalid input. Send positive points and an optional non-negative penalty, e.g. '2 0.5'.",
+            )
+            .await?;
+        }
+    }
+
+    Ok(())
+}
+
+#[instrument(level = "info", skip(connection, bot, dialogue))]
+pub(crate) async fn handle_answer<
+    Connect: DeleteAnswer + RetreiveAnswer + Operations + RetreiveQuiz + RetreiveQuestion,
+>(
+    bot: Bot,
+    msg: Message,
+    dialogue: UserDialogue,
+    (quiz_name, question_name, answer_name): (String, String, String),
+    connection: Arc<Connect>,
+) -> HandlerResult {
+    match msg.text() {
         Some("Delete answer") => {
+            let answer = connection
+                .retreive_answer(&quiz_name, &question_name, &answer_name)
+                .await?;
             let deleted = connection
                 .delete_answer(&quiz_name, &question_name, &answer_name)
                 .await?;
+            if let Some(answer) = answer {
+                connection
+                    .log_operation(
+                        msg.chat.id.0,
+                        &quiz_name,
+                        &Operation::DeleteAnswer {
+                            question: question_name.clone(),
+                            answer: deleted,
+                            is_correct: answer.is_correct(),
+                        },
+                    )
+                    .await?;
+            }
             bot.send_message(msg.chat.id, "Answer deleted.")
                 .reply_markup(keyboard::edit_question_keyboard())
                 .await?;
@@ -194,17 +316,7 @@ pub(crate) async fn handle_answer<Connect: DeleteAnswer + RetreiveQuestion>(
                 })
                 .await?;
         }
-        Some("Back") => {
-            bot.send_message(msg.chat.id, "Handle question.")
-                .reply_markup(keyboard::edit_question_keyboard())
-                .await?;
-            dialogue
-                .update(QuizState::HandleQuestion {
-                    quiz_name,
-                    question_name,
-                })
-                .await?;
-        }
+        Some("Back") => return navigation::back(bot, msg, dialogue, connection).await,
         _ => {
             bot.send_message(msg.chat.id, "Invalid input. Please try again.")
                 .await?;
@@ -267,7 +379,7 @@ pub(crate) async fn select_answer<Connect: RetreiveAnswer>(
 }
 
 #[instrument(level = "info", skip(connection, bot, dialogue))]
-pub(crate) async fn handle_question<Connect: DeleteQuestion + RetreiveAnswer + RetreiveQuiz>(
+pub(crate) async fn handle_question<Connect: DeleteQuestion + RetreiveAnswer>(
     bot: Bot,
     msg: Message,
     dialogue: UserDialogue,
@@ -275,22 +387,23 @@ pub(crate) async fn handle_question<Connect: DeleteQuestion + RetreiveAnswer + R
     connection: Arc<Connect>,
 ) -> HandlerResult {
     match msg.text() {
-        Some("/back") => {
-            let quiz = connection.retreive_quiz(&quiz_name).await?.unwrap();
-            bot.send_message(msg.chat.id, "Returning back.").await?;
-            bot.send_message(msg.chat.id, quiz.to_string())
-                .reply_markup(keyboard::edit_quiz_keyboard())
-                .await?;
-            dialogue.update(QuizState::HandleQuiz { quiz_name }).await?;
-        }
-        Some("Delete question") => {
-            let deleted = connection
-                .delete_question(&quiz_name, &question_name)
-                .await?;
-            bot.send_message(msg.chat.id, "Question deleted.")
-                .reply_markup(keyboard::edit_quiz_keyboard())
+        Some("Delete question🗑️") => {
+            bot.send_message(
+                msg.chat.id,
+                format!(
+                    "Move question '{}' to the trash? It can be restored for {} days.",
+                    question_name,
+                    TRASH_RETENTION.num_days()
+                ),
+            )
+            .reply_markup(yes_no_keyboard())
+            .await?;
+            dialogue
+                .update(QuizState::DeleteQuestion {
+                    quiz_name,
+                    question_name,
+                })
                 .await?;
-            dialogue.update(QuizState::HandleQuiz { quiz_name }).await?;
         }
         Some("Edit text") => {
             bot.send_message(msg.chat.id, "What's new question text?")
@@ -302,6 +415,19 @@ pub(crate) async fn handle_question<Connect: DeleteQuestion + RetreiveAnswer + R
                 })
                 .await?;
         }
+        Some("Edit scoring") => {
+            bot.send_message(
+                msg.chat.id,
+                "Send points for a correct answer and penalty for a wrong one, e.g. '2 0.5'. Skipping a question never costs points.",
+            )
+            .await?;
+            dialogue
+                .update(QuizState::EditQuestionScoring {
+                    quiz_name,
+                    question_name,
+                })
+                .await?;
+        }
         Some("Add answer") => {
             bot.send_message(msg.chat.id, "What a new answer looks like?")
                 .await?;
@@ -400,7 +526,7 @@ pub(crate) async fn editor_add_corectness<Connect: CreateAnswer>(
         }
         Some("No") | Some("No❌") => {
             match connection
-                .create_answer(&quiz_name, &question_name, &answer_name, true)
+                .create_answer(&quiz_name, &question_name, &answer_name, false)
                 .await
             {
                 Ok(added) => {
@@ -432,26 +558,25 @@ pub(crate) async fn editor_add_corectness<Connect: CreateAnswer>(
     Ok(())
 }
 
-#[instrument(level = "info", skip(connection, dialogue, bot))]
-pub(crate) async fn editor_add_question<Connect: CreateQuestion>(
+#[instrument(level = "info", skip(dialogue, bot))]
+pub(crate) async fn editor_add_question(
     bot: Bot,
     msg: Message,
     dialogue: UserDialogue,
     quiz_name: String,
-    connection: Arc<Connect>,
 ) -> HandlerResult {
     match msg.text() {
-        Some(text) => match connection.create_question(&quiz_name, text).await {
-            Ok(added) => {
-                bot.send_message(msg.chat.id, format!("Question '{}' created.", text))
-                    .await?;
-                dialogue.update(QuizState::HandleQuiz { quiz_name }).await?;
-            }
-            Err(e) => {
-                bot.send_message(msg.chat.id, "Error occured. Please try again later.")
-                    .await?;
-            }
-        },
+        Some(text) => {
+            bot.send_message(msg.chat.id, "OK. What's the answer to your question?")
+                .await?;
+            dialogue
+                .update(QuizState::AddQuestionReceiveAnswer {
+                    quiz_name,
+                    text: text.to_owned(),
+                    answers: Vec::default(),
+                })
+                .await?;
+        }
         None => {
             bot.send_message(msg.chat.id, "Invalid input. Please try again.")
                 .await?;
@@ -461,50 +586,29 @@ pub(crate) async fn editor_add_question<Connect: CreateQuestion>(
     Ok(())
 }
 
-#[instrument(level = "info", skip(connection, bot, dialogue))]
-pub(crate) async fn select_question<Connect: RetreiveQuestion>(
+#[instrument(level = "info", skip(dialogue, bot))]
+pub(crate) async fn editor_add_question_answer(
     bot: Bot,
     msg: Message,
     dialogue: UserDialogue,
-    quiz_name: String,
-    connection: Arc<Connect>,
+    (quiz_name, text, answers): (String, String, Vec<Answer>),
 ) -> HandlerResult {
     match msg.text() {
-        Some(question_name) => {
-            match connection
-                .retreive_question(&quiz_name, question_name)
-                .await
-            {
-                Ok(Some(question)) => {
-                    bot.send_message(
-                        msg.chat.id,
-                        format!(
-                            "Question '{}' selected. Please select an action:",
-                            question.text()
-                        ),
-                    )
-                    .reply_markup(keyboard::edit_question_keyboard())
-                    .await?;
-                    bot.send_message(msg.chat.id, question.to_string()).await?;
-                    dialogue
-                        .update(QuizState::HandleQuestion {
-                            quiz_name,
-                            question_name: question_name.to_owned(),
-                        })
-                        .await?;
-                }
-                Ok(None) => {
-                    bot.send_message(
-                        msg.chat.id,
-                        format!("Question '{}' not found. Try again.", question_name),
-                    )
-                    .await?;
-                }
-                _ => (),
-            }
+        Some(new_answer) => {
+            bot.send_message(msg.chat.id, "Got it. Is that answer correct?(Yes/No)")
+                .reply_markup(yes_no_keyboard())
+                .await?;
+            dialogue
+                .update(QuizState::AddQuestionCorectness {
+                    quiz_name,
+                    text,
+                    answers,
+                    new_answer: new_answer.to_owned(),
+                })
+                .await?;
         }
         None => {
-            bot.send_message(msg.chat.id, "Please, select a question.")
+            bot.send_message(msg.chat.id, "Please, enter a valid answer.")
                 .await?;
         }
     }
@@ -512,157 +616,525 @@ pub(crate) async fn select_question<Connect: RetreiveQuestion>(
     Ok(())
 }
 
-#[instrument(level = "info", skip(connection, bot, dialogue))]
-pub(crate) async fn select_quiz<QuizRetriever: RetreiveQuiz>(
+#[instrument(level = "info", skip(dialogue, bot))]
+pub(crate) async fn editor_add_question_corectness(
     bot: Bot,
     msg: Message,
     dialogue: UserDialogue,
-    connection: Arc<QuizRetriever>,
+    (quiz_name, text, mut answers, new_answer): (String, String, Vec<Answer>, String),
 ) -> HandlerResult {
-    match msg.text() {
-        Some(quiz_name) => match connection.retreive_quiz(quiz_name).await {
-            Ok(Some(quiz)) => {
-                bot.send_message(
-                    msg.chat.id,
-                    format!("Quiz '{}' chosen. Please, select an action:", quiz.title()),
-                )
-                .reply_markup(keyboard::edit_quiz_keyboard())
-                .await?;
-                bot.send_message(msg.chat.id, quiz.to_string()).await?;
-                dialogue
-                    .update(QuizState::HandleQuiz {
-                        quiz_name: quiz_name.into(),
-                    })
-                    .await?;
-            }
-            Ok(None) => {
-                bot.send_message(
-                    msg.chat.id,
-                    format!("Quiz '{}' not found. Try again.", quiz_name),
-                )
-                .await?;
-            }
-            Err(e) => (),
-        },
-        None => {
-            bot.send_message(msg.chat.id, "Please, select a quiz.")
+    let is_correct = match msg.text() {
+        Some("Yes") | Some("Yes✔️") => true,
+        Some("No") | Some("No❌") => false,
+        _ => {
+            bot.send_message(msg.chat.id, "Please, choose Yes or No.")
+                .reply_markup(yes_no_keyboard())
                 .await?;
+            return Ok(());
         }
-    }
+    };
+
+    answers.push(Answer::new(new_answer, is_correct));
+    bot.send_message(
+        msg.chat.id,
+        format!(
+            "Okay, that answer is {}. Do you want to add another answer?(Yes/No)",
+            if is_correct { "correct" } else { "incorrect" }
+        ),
+    )
+    .reply_markup(yes_no_keyboard())
+    .await?;
+    dialogue
+        .update(QuizState::AddQuestionReceiveAnotherAnswer {
+            quiz_name,
+            text,
+            answers,
+        })
+        .await?;
 
     Ok(())
 }
 
 #[instrument(level = "info", skip(connection, dialogue, bot))]
-pub(crate) async fn handle_quiz<Connect: DeleteQuiz + RetreiveQuestion>(
+pub(crate) async fn editor_add_another_answer<Connect: CreateQuestion>(
     bot: Bot,
     msg: Message,
     dialogue: UserDialogue,
-    quiz_name: String,
+    (quiz_name, text, answers): (String, String, Vec<Answer>),
     connection: Arc<Connect>,
 ) -> HandlerResult {
     match msg.text() {
-        Some("/back") => {
-            bot.send_message(msg.chat.id, "Returning back.")
-                .reply_markup(keyboard::action_keyboard(
-                    msg.chat.username().unwrap_or("anonymous"),
-                ))
-                .await?;
-            dialogue.update(QuizState::Start).await?;
-        }
-        Some("Delete quiz🗑️") => {
-            let deleted = connection.delete_quiz(&quiz_name).await?;
-            bot.send_message(msg.chat.id, format!("Quiz '{}' deleted.", deleted))
-                .reply_markup(keyboard::action_keyboard(msg.chat.username().unwrap()))
-                .await?;
-            dialogue.update(QuizState::Start).await?;
-        }
-        Some("Edit name") => {
-            bot.send_message(msg.chat.id, "What's new quiz name?")
-                .await?;
-            dialogue.update(QuizState::EditName { quiz_name }).await?;
-        }
-        Some("Edit description") => {
-            bot.send_message(msg.chat.id, "What's new quiz description?")
+        Some("Yes") | Some("Yes✔️") => {
+            bot.send_message(msg.chat.id, "Great. What's the another answer?")
+                .reply_markup(ReplyMarkup::kb_remove())
                 .await?;
             dialogue
-                .update(QuizState::EditDescription { quiz_name })
+                .update(QuizState::AddQuestionReceiveAnswer {
+                    quiz_name,
+                    text,
+                    answers,
+                })
                 .await?;
         }
-        Some("Add question") => {
-            bot.send_message(msg.chat.id, "Choose question text?")
-                .await?;
-            dialogue
-                .update(QuizState::AddQuestion { quiz_name })
+        Some("No") | Some("No❌") => {
+            let question = Question::new(text, Some(answers));
+            let added = connection.create_question(&quiz_name, &question).await?;
+            bot.send_message(
+                msg.chat.id,
+                format!(
+                    "Question '{}' saved with {} answer(s).",
+                    added,
+                    question.answers().len()
+                ),
+            )
+            .reply_markup(keyboard::edit_quiz_keyboard())
+            .await?;
+            dialogue.update(QuizState::HandleQuiz { quiz_name }).await?;
+        }
+        _ => {
+            bot.send_message(msg.chat.id, "Please, choose Yes or No.")
+                .reply_markup(yes_no_keyboard())
                 .await?;
         }
-        Some("Edit question") => {
-            let questions = connection.retreive_all_question_names(&quiz_name).await?;
-            if questions.len() < 1 {
-                bot.send_message(msg.chat.id, "No available questions.")
-                    .await?;
-            } else {
-                bot.send_message(msg.chat.id, "Choose question to edit")
-                    .reply_markup(keyboard::questions_keyboard(&questions))
-                    .await?;
-            }
+    }
+
+    Ok(())
+}
+
+/// Shows the chosen question and its editing actions.
+pub(crate) async fn open_question<Connect: RetreiveQuestion>(
+    bot: &Bot,
+    chat_id: ChatId,
+    dialogue: &UserDialogue,
+    quiz_name: String,
+    question_name: &str,
+    connection: &Connect,
+) -> HandlerResult {
+    match connection
+        .retreive_question(&quiz_name, question_name)
+        .await?
+    {
+        Some(question) => {
+            bot.send_message(
+                chat_id,
+                format!(
+                    "Question '{}' selected. Please select an action:",
+                    question.text()
+                ),
+            )
+            .reply_markup(keyboard::edit_question_keyboard())
+            .await?;
+            bot.send_message(chat_id, question.to_string()).await?;
             dialogue
-                .update(QuizState::SelectQuestion { quiz_name })
+                .update(QuizState::HandleQuestion {
+                    quiz_name,
+                    question_name: question_name.to_owned(),
+                })
                 .await?;
         }
-        _ => {
-            bot.send_message(msg.chat.id, "Invalid input. Try again.")
-                .await?;
+        None => {
+            bot.send_message(
+                chat_id,
+                format!("Question '{}' not found. Try again.", question_name),
+            )
+            .await?;
         }
     }
+
     Ok(())
 }
 
-#[instrument(level = "info", skip(connection, bot, dialogue))]
-pub(crate) async fn edit_name<Connect: EditQuiz>(
-    bot: Bot,
-    msg: Message,
-    dialogue: UserDialogue,
-    quiz_name: String,
-    connection: Arc<Connect>,
+/// Shows the chosen quiz and its editing actions. Opens an edit session, so
+/// that its first change keeps the quiz as it was in a new revision.
+pub(crate) async fn open_quiz<Connect: RetreiveQuiz + Revisions>(
+    bot: &Bot,
+    chat_id: ChatId,
+    dialogue: &UserDialogue,
+    quiz_name: &str,
+    connection: &Connect,
 ) -> HandlerResult {
-    match msg.text() {
-        Some(new_name) => {
-            if let Ok(new_name) = connection.edit_name(&quiz_name, new_name).await {
-                bot.send_message(msg.chat.id, "Quiz name succesfully updated.")
-                    .await?;
-                dialogue
-                    .update(QuizState::HandleQuiz {
-                        quiz_name: new_name,
-                    })
+    match connection.retreive_quiz(quiz_name).await? {
+        Some(quiz) => {
+            connection.begin_edit_session(quiz_name).await?;
+            bot.send_message(
+                chat_id,
+                format!("Quiz '{}' chosen. Please, select an action:", quiz.title()),
+            )
+            .reply_markup(keyboard::edit_quiz_keyboard())
+            .await?;
+            bot.send_message(chat_id, quiz.to_string()).await?;
+            let report = validate(&quiz);
+            if !report.is_empty() {
+                bot.send_message(chat_id, format!("Problems found:\n{}", report))
                     .await?;
-            } else {
-                bot.send_message(msg.chat.id, "Sorry, error occured")
-                    .await?; // add better error handling
-                dialogue.update(QuizState::HandleQuiz { quiz_name }).await?;
             }
+            dialogue
+                .update(QuizState::HandleQuiz {
+                    quiz_name: quiz_name.into(),
+                })
+                .await?;
         }
-
         None => {
-            bot.send_message(msg.chat.id, "Nothing entered. Try again.")
-                .await?;
+            bot.send_message(
+                chat_id,
+                format!("Quiz '{}' not found. Try again.", quiz_name),
+            )
+            .await?;
         }
     }
 
     Ok(())
 }
 
-#[instrument(level = "info", skip(connection, bot, dialogue))]
-pub(crate) async fn edit_description<Connect: EditQuiz>(
+#[instrument(level = "info", skip(connection, dialogue, bot, me))]
+pub(crate) async fn handle_quiz<
+    Connect: CreateQuiz + DeleteQuiz + RetreiveQuestion + RetreiveQuiz + EditQuiz + Revisions + Trash,
+>(
     bot: Bot,
     msg: Message,
     dialogue: UserDialogue,
     quiz_name: String,
+    me: Me,
     connection: Arc<Connect>,
 ) -> HandlerResult {
     match msg.text() {
-        Some(new_name) => {
+        Some("Share link🔗") => {
+            let Some(quiz) = connection.retreive_quiz(&quiz_name).await? else {
+                bot.send_message(msg.chat.id, format!("Quiz '{}' not found.", quiz_name))
+                    .await?;
+                return Ok(());
+            };
+            bot.send_message(
+                msg.chat.id,
+                format!(
+                    "Share this link to open '{}' directly:\n{}",
+                    quiz.title(),
+                    quiz_link(&me, &quiz)?
+                ),
+            )
+            .reply_markup(keyboard::edit_quiz_keyboard())
+            .await?;
+        }
+        Some("Export to Aiken📤") => {
+            let Some(quiz) = connection.retreive_quiz(&quiz_name).await? else {
+                bot.send_message(msg.chat.id, format!("Quiz '{}' not found.", quiz_name))
+                    .await?;
+                return Ok(());
+            };
+            let export = aiken::export(&quiz);
+            let skipped = skipped_report(export.skipped());
+            if export.is_empty() {
+                bot.send_message(
+                    msg.chat.id,
+                    format!(
+                        "Nothing to export, Aiken needs exactly one correct answer per question.\n{}",
+                        skipped
+                    ),
+                )
+                .reply_markup(keyboard::edit_quiz_keyboard())
+                .await?;
+                return Ok(());
+            }
+
+            bot.send_document(
+                msg.chat.id,
+                InputFile::memory(export.text().to_owned())
+                    .file_name(format!("{}.aiken", quiz.title())),
+            )
+            .await?;
+            if !export.skipped().is_empty() {
+                bot.send_message(
+                    msg.chat.id,
+                    format!(
+                        "⚠️ Left out {} question(s) Aiken can't express:\n{}",
+                        export.skipped().len(),
+                        skipped
+                    ),
+                )
+                .reply_markup(keyboard::edit_quiz_keyboard())
+                .await?;
+            }
+        }
+        Some("Duplicate quiz📄") => {
+            let copy = connection.duplicate_quiz(&quiz_name).await?;
+            bot.send_message(
+                msg.chat.id,
+                format!("Quiz '{}' copied as draft '{}'.", quiz_name, copy),
+            )
+            .await?;
+            open_quiz(&bot, msg.chat.id, &dialogue, &copy, connection.as_ref()).await?;
+        }
+        Some("Delete quiz🗑️") => {
+            bot.send_message(
+                msg.chat.id,
+                format!(
+                    "Move quiz '{}' to the trash? It can be restored for {} days.",
+                    quiz_name,
+                    TRASH_RETENTION.num_days()
+                ),
+            )
+            .reply_markup(yes_no_keyboard())
+            .await?;
+            dialogue.update(QuizState::DeleteQuiz { quiz_name }).await?;
+        }
+        Some("Deleted questions♻️") => {
+            listing::open(
+                &bot,
+                msg.chat.id,
+                &dialogue,
+                QuizState::QuestionTrashSelection {
+                    quiz_name,
+                    listing: Listing::default(),
+                },
+                "Choose a question to restore.",
+                "No deleted questions.",
+                connection.as_ref(),
+            )
+            .await?;
+        }
+        Some("Edit name") => {
+            bot.send_message(msg.chat.id, "What's new quiz name?")
+                .await?;
+            dialogue.update(QuizState::EditName { quiz_name }).await?;
+        }
+        Some("Edit description") => {
+            bot.send_message(msg.chat.id, "What's new quiz description?")
+                .await?;
+            dialogue
+                .update(QuizState::EditDescription { quiz_name })
+                .await?;
+        }
+        Some("Publish✅") => {
+            let Some(quiz) = connection.retreive_quiz(&quiz_name).await? else {
+                bot.send_message(msg.chat.id, format!("Quiz '{}' not found.", quiz_name))
+                    .await?;
+                return Ok(());
+            };
+            let report = validate(&quiz);
+            let text = if report.has_errors() {
+                format!(
+                    "Quiz '{}' can't be published until the errors are fixed:\n{}",
+                    quiz_name, report
+                )
+            } else {
+                connection
+                    .edit_status(&quiz_name, Status::Published)
+                    .await?;
+                format!("Quiz '{}' is published and open to takers.", quiz_name)
+            };
+            bot.send_message(msg.chat.id, text)
+                .reply_markup(keyboard::edit_quiz_keyboard())
+                .await?;
+        }
+        Some("Check quiz🔍") => {
+            let Some(quiz) = connection.retreive_quiz(&quiz_name).await? else {
+                bot.send_message(msg.chat.id, format!("Quiz '{}' not found.", quiz_name))
+                    .await?;
+                return Ok(());
+            };
+            bot.send_message(msg.chat.id, validate(&quiz).to_string())
+                .reply_markup(keyboard::edit_quiz_keyboard())
+                .await?;
+        }
+        Some("History🕓") => {
+            let revisions = connection.retreive_revisions(&quiz_name).await?;
+            if revisions.is_empty() {
+                bot.send_message(msg.chat.id, "No earlier revisions yet.")
+                    .await?;
+                return Ok(());
+            }
+            let history: Vec<String> = revisions.iter().map(|r| r.to_string()).collect();
+            bot.send_message(
+                msg.chat.id,
+                format!(
+                    "{}\n\nChoose a revision to roll back to. Past results keep pointing at the revision they were taken on.",
+                    history.join("\n")
+                ),
+            )
+            .reply_markup(keyboard::revisions_keyboard(&revisions))
+            .await?;
+            dialogue
+                .update(QuizState::SelectRevision { quiz_name })
+                .await?;
+        }
+        Some("Unpublish") => {
+            connection.edit_status(&quiz_name, Status::Draft).await?;
+            bot.send_message(
+                msg.chat.id,
+                format!(
+                    "Quiz '{}' is a draft again and hidden from takers.",
+                    quiz_name
+                ),
+            )
+            .reply_markup(keyboard::edit_quiz_keyboard())
+            .await?;
+        }
+        Some("Archive🗄️") => {
+            connection.edit_status(&quiz_name, Status::Archived).await?;
+            bot.send_message(
+                msg.chat.id,
+                format!("Quiz '{}' is archived and hidden from takers.", quiz_name),
+            )
+            .reply_markup(keyboard::edit_quiz_keyboard())
+            .await?;
+        }
+        Some("Tags🏷️") => {
+            bot.send_message(
+                msg.chat.id,
+                "Send the new tags separated by commas, or choose 'No tags' to remove them.",
+            )
+            .reply_markup(keyboard::no_tags_keyboard())
+            .await?;
+            dialogue.update(QuizState::EditTags { quiz_name }).await?;
+        }
+        Some("Add question") => {
+            bot.send_message(msg.chat.id, "What's the text of the new question?")
+                .reply_markup(ReplyMarkup::kb_remove())
+                .await?;
+            dialogue
+                .update(QuizState::AddQuestion { quiz_name })
+                .await?;
+        }
+        Some("Availability🗓️") => {
+            bot.send_message(
+                msg.chat.id,
+                "When does the quiz open? Send YYYY-MM-DD HH:MM [timezone] or choose 'No limit'.",
+            )
+            .reply_markup(keyboard::no_limit_keyboard())
+            .await?;
+            dialogue
+                .update(QuizState::EditOpensAt { quiz_name })
+                .await?;
+        }
+        Some("Grading🎓") => {
+            bot.send_message(
+                msg.chat.id,
+                "What's the pass mark? Send a percentage (e.g. 60%) or points (e.g. 12), or choose 'No pass mark'.",
+            )
+            .reply_markup(keyboard::no_pass_mark_keyboard())
+            .await?;
+            dialogue
+                .update(QuizState::EditPassMark { quiz_name })
+                .await?;
+        }
+        Some("Delivery📨") => {
+            let Some(quiz) = connection.retreive_quiz(&quiz_name).await? else {
+                bot.send_message(msg.chat.id, format!("Quiz '{}' not found.", quiz_name))
+                    .await?;
+                return Ok(());
+            };
+            let native_polls = !quiz.native_polls();
+            connection.edit_delivery(&quiz_name, native_polls).await?;
+            let text = if native_polls {
+                "Questions will be sent as native quiz polls. Questions with several correct answers or too long for a poll still get buttons."
+            } else {
+                "Questions will be sent with answer buttons."
+            };
+            bot.send_message(msg.chat.id, text)
+                .reply_markup(keyboard::edit_quiz_keyboard())
+                .await?;
+        }
+        Some("Schedule push⏰") => {
+            bot.send_message(
+                msg.chat.id,
+                "When should the quiz be pushed? Send date and time as YYYY-MM-DD HH:MM [timezone], e.g. 2024-10-01 09:00 UTC+2.",
+            )
+            .reply_markup(ReplyMarkup::kb_remove())
+            .await?;
+            dialogue
+                .update(QuizState::SchedulePushTime { quiz_name })
+                .await?;
+        }
+        Some("Edit question") => {
+            listing::open(
+                &bot,
+                msg.chat.id,
+                &dialogue,
+                QuizState::SelectQuestion {
+                    quiz_name,
+                    listing: Listing::default(),
+                },
+                "Choose question to edit",
+                "No available questions.",
+                connection.as_ref(),
+            )
+            .await?;
+        }
+        _ => {
+            bot.send_message(msg.chat.id, "Invalid input. Try again.")
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+#[instrument(level = "info", skip(connection, bot, dialogue))]
+pub(crate) async fn edit_name<Connect: EditQuiz + Operations>(
+    bot: Bot,
+    msg: Message,
+    dialogue: UserDialogue,
+    quiz_name: String,
+    connection: Arc<Connect>,
+) -> HandlerResult {
+    match msg.text() {
+        Some(new_name) => {
+            if let Ok(new_name) = connection.edit_name(&quiz_name, new_name).await {
+                connection
+                    .log_operation(
+                        msg.chat.id.0,
+                        &new_name,
+                        &Operation::Rename {
+                            old: quiz_name,
+                            new: new_name.clone(),
+                        },
+                    )
+                    .await?;
+                bot.send_message(msg.chat.id, "Quiz name succesfully updated.")
+                    .await?;
+                dialogue
+                    .update(QuizState::HandleQuiz {
+                        quiz_name: new_name,
+                    })
+                    .await?;
+            } else {
+                bot.send_message(msg.chat.id, "Sorry, error occured")
+                    .await?; // add better error handling
+                dialogue.update(QuizState::HandleQuiz { quiz_name }).await?;
+            }
+        }
+
+        None => {
+            bot.send_message(msg.chat.id, "Nothing entered. Try again.")
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+
+#[instrument(level = "info", skip(connection, bot, dialogue))]
+pub(crate) async fn edit_description<Connect: EditQuiz + RetreiveQuiz + Operations>(
+    bot: Bot,
+    msg: Message,
+    dialogue: UserDialogue,
+    quiz_name: String,
+    connection: Arc<Connect>,
+) -> HandlerResult {
+    match msg.text() {
+        Some(new_name) => {
+            let old = connection.retreive_quiz(&quiz_name).await?;
             let new_descriptrion = connection.edit_description(&quiz_name, new_name).await?;
+            if let Some(old) = old {
+                connection
+                    .log_operation(
+                        msg.chat.id.0,
+                        &quiz_name,
+                        &Operation::EditDescription {
+                            old: old.description().clone(),
+                        },
+                    )
+                    .await?;
+            }
             bot.send_message(msg.chat.id, "Quiz description successfully updated.")
                 .await?;
             dialogue.update(QuizState::HandleQuiz { quiz_name }).await?;
@@ -675,3 +1147,595 @@ pub(crate) async fn edit_description<Connect: EditQuiz>(
 
     Ok(())
 }
+
+#[instrument(level = "info", skip(connection, bot, dialogue))]
+pub(crate) async fn edit_tags<Connect: EditQuiz>(
+    bot: Bot,
+    msg: Message,
+    dialogue: UserDialogue,
+    quiz_name: String,
+    connection: Arc<Connect>,
+) -> HandlerResult {
+    let tags = match msg.text() {
+        Some("No tags") => Some(vec![]),
+        Some(text) => parse_tags(text),
+        None => None,
+    };
+
+    match tags {
+        Some(tags) => {
+            connection.edit_tags(&quiz_name, &tags).await?;
+            bot.send_message(msg.chat.id, "Quiz tags successfully updated.")
+                .reply_markup(keyboard::edit_quiz_keyboard())
+                .await?;
+            dialogue.update(QuizState::HandleQuiz { quiz_name }).await?;
+        }
+        None => {
+            bot.send_message(
+                msg.chat.id,
+                "Please, send short tags separated by commas or choose 'No tags'.",
+            )
+            .await?;
+        }
+    }
+
+    Ok(())
+}
+
+#[instrument(level = "info", skip(connection, bot, dialogue))]
+pub(crate) async fn delete_quiz<Connect: DeleteQuiz + Operations>(
+    bot: Bot,
+    msg: Message,
+    dialogue: UserDialogue,
+    quiz_name: String,
+    connection: Arc<Connect>,
+) -> HandlerResult {
+    match msg.text() {
+        Some("Yes") | Some("Yes✔️") => {
+            let deleted = connection.delete_quiz(&quiz_name).await?;
+            connection
+                .log_operation(msg.chat.id.0, &deleted, &Operation::DeleteQuiz)
+                .await?;
+            bot.send_message(
+                msg.chat.id,
+                format!("Quiz '{}' moved to the trash.", deleted),
+            )
+            .reply_markup(keyboard::action_keyboard(
+                msg.chat.username().unwrap_or_default(),
+            ))
+            .await?;
+            dialogue.update(QuizState::Start).await?;
+        }
+        Some("No") | Some("No❌") => {
+            bot.send_message(msg.chat.id, "Quiz kept.")
+                .reply_markup(keyboard::edit_quiz_keyboard())
+                .await?;
+            dialogue.update(QuizState::HandleQuiz { quiz_name }).await?;
+        }
+        _ => {
+            bot.send_message(msg.chat.id, "Please, choose Yes or No.")
+                .reply_markup(yes_no_keyboard())
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+
+#[instrument(level = "info", skip(connection, bot, dialogue))]
+pub(crate) async fn delete_question<Connect: DeleteQuestion + Operations>(
+    bot: Bot,
+    msg: Message,
+    dialogue: UserDialogue,
+    (quiz_name, question_name): (String, String),
+    connection: Arc<Connect>,
+) -> HandlerResult {
+    match msg.text() {
+        Some("Yes") | Some("Yes✔️") => {
+            let deleted = connection
+                .delete_question(&quiz_name, &question_name)
+                .await?;
+            connection
+                .log_operation(
+                    msg.chat.id.0,
+                    &quiz_name,
+                    &Operation::DeleteQuestion { question: deleted },
+                )
+                .await?;
+            bot.send_message(msg.chat.id, "Question moved to the trash.")
+                .reply_markup(keyboard::edit_quiz_keyboard())
+                .await?;
+            dialogue.update(QuizState::HandleQuiz { quiz_name }).await?;
+        }
+        Some("No") | Some("No❌") => {
+            bot.send_message(msg.chat.id, "Question kept.")
+                .reply_markup(keyboard::edit_question_keyboard())
+                .await?;
+            dialogue
+                .update(QuizState::HandleQuestion {
+                    quiz_name,
+                    question_name,
+                })
+                .await?;
+        }
+        _ => {
+            bot.send_message(msg.chat.id, "Please, choose Yes or No.")
+                .reply_markup(yes_no_keyboard())
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+
+// Questions left out of an export can be many and long, so the report has to fit one message.
+const MAX_LISTED_SKIPPED: usize = 20;
+const MAX_SKIPPED_LEN: usize = 150;
+
+fn skipped_report(skipped: &[Skipped]) -> String {
+    let mut report = skipped
+        .iter()
+        .take(MAX_LISTED_SKIPPED)
+        .map(|skipped| {
+            let line = skipped.to_string();
+            if line.chars().count() > MAX_SKIPPED_LEN {
+                format!(
+                    "{}…",
+                    line.chars().take(MAX_SKIPPED_LEN).collect::<String>()
+                )
+            } else {
+                line
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    if skipped.len() > MAX_LISTED_SKIPPED {
+        report.push_str(&format!(
+            "\n...and {} more.",
+            skipped.len() - MAX_LISTED_SKIPPED
+        ));
+    }
+
+    report
+}
+
+// Quiz the user is editing, if any, to limit /undo to it.
+fn edited_quiz(state: QuizState) -> Option<String> {
+    match state {
+        QuizState::HandleQuiz { quiz_name }
+        | QuizState::HandleQuestion { quiz_name, .. }
+        | QuizState::SelectQuestion { quiz_name, .. }
+        | QuizState::QuestionTrashSelection { quiz_name, .. }
+        | QuizState::SelectAnswer { quiz_name, .. }
+        | QuizState::HandleAnswer { quiz_name, .. }
+        | QuizState::SelectRevision { quiz_name } => Some(quiz_name),
+        _ => None,
+    }
+}
+
+/// Reverts the latest change the user made on the edited quiz, or on any quiz outside the editor.
+#[instrument(level = "info", skip(connection, bot, dialogue))]
+pub(crate) async fn undo<Connect: Operations>(
+    bot: Bot,
+    msg: Message,
+    dialogue: UserDialogue,
+    connection: Arc<Connect>,
+) -> HandlerResult {
+    let edited = dialogue.get().await?.and_then(edited_quiz);
+    let in_editor = edited.is_some();
+
+    match connection.undo_operation(msg.chat.id.0, edited).await {
+        Ok(Some(Undo::Done {
+            quiz_name,
+            operation,
+        })) => {
+            let text = format!("Undone {} in quiz '{}'.", operation, quiz_name);
+            // Outside the editor the user stays where they were.
+            if in_editor {
+                bot.send_message(msg.chat.id, text)
+                    .reply_markup(keyboard::edit_quiz_keyboard())
+                    .await?;
+                dialogue.update(QuizState::HandleQuiz { quiz_name }).await?;
+            } else {
+                bot.send_message(msg.chat.id, text).await?;
+            }
+        }
+        Ok(Some(Undo::Outdated { operation })) => {
+            bot.send_message(
+                msg.chat.id,
+                format!(
+                    "Can't undo {} anymore, what it changed is gone. It was dropped from the history.",
+                    operation
+                ),
+            )
+            .await?;
+        }
+        Ok(None) => {
+            bot.send_message(msg.chat.id, "Nothing to undo.").await?;
+        }
+        Err(e) => {
+            tracing::error!("Undo failed: {}", e);
+            bot.send_message(
+                msg.chat.id,
+                "Couldn't undo the latest change, nothing was reverted. Please try again later.",
+            )
+            .await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Brings the quiz back from the trash and opens it for editing.
+pub(crate) async fn restore_quiz<Connect: Trash + RetreiveQuiz + Revisions>(
+    bot: &Bot,
+    chat_id: ChatId,
+    dialogue: &UserDialogue,
+    quiz_name: &str,
+    connection: &Connect,
+) -> HandlerResult {
+    let restored = connection.restore_quiz(quiz_name).await?;
+    bot.send_message(chat_id, format!("Quiz '{}' restored.", restored))
+        .await?;
+
+    open_quiz(bot, chat_id, dialogue, &restored, connection).await
+}
+
+pub(crate) async fn restore_question<Connect: Trash>(
+    bot: &Bot,
+    chat_id: ChatId,
+    dialogue: &UserDialogue,
+    quiz_name: String,
+    question_name: &str,
+    connection: &Connect,
+) -> HandlerResult {
+    let restored = connection
+        .restore_question(&quiz_name, question_name)
+        .await?;
+    bot.send_message(chat_id, format!("Question '{}' restored.", restored))
+        .reply_markup(keyboard::edit_quiz_keyboard())
+        .await?;
+    dialogue.update(QuizState::HandleQuiz { quiz_name }).await?;
+
+    Ok(())
+}
+
+#[instrument(level = "info", skip(connection, bot, dialogue))]
+pub(crate) async fn select_revision<Connect: Revisions + RetreiveQuiz + RetreiveQuestion>(
+    bot: Bot,
+    msg: Message,
+    dialogue: UserDialogue,
+    quiz_name: String,
+    connection: Arc<Connect>,
+) -> HandlerResult {
+    match msg.text() {
+        Some("Back") => return navigation::back(bot, msg, dialogue, connection).await,
+        Some(text) => match text
+            .strip_prefix(ROLL_BACK_PREFIX)
+            .and_then(|revision| revision.parse::<i32>().ok())
+        {
+            Some(revision) => {
+                connection.rollback(&quiz_name, revision).await?;
+                bot.send_message(
+                    msg.chat.id,
+                    format!(
+                        "Quiz '{}' rolled back to revision {}. Its content before the rollback is kept in the history.",
+                        quiz_name, revision
+                    ),
+                )
+                .reply_markup(keyboard::edit_quiz_keyboard())
+                .await?;
+                dialogue.update(QuizState::HandleQuiz { quiz_name }).await?;
+            }
+            None => {
+                bot.send_message(msg.chat.id, "Please, choose a revision.")
+                    .await?;
+            }
+        },
+        None => {
+            bot.send_message(msg.chat.id, "Please, choose a revision.")
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+
+#[instrument(level = "info", skip(bot, dialogue))]
+pub(crate) async fn schedule_push_time(
+    bot: Bot,
+    msg: Message,
+    dialogue: UserDialogue,
+    quiz_name: String,
+) -> HandlerResult {
+    match msg.text().and_then(parse_datetime) {
+        Some(fire_at) if fire_at > Utc::now() => {
+            bot.send_message(
+                msg.chat.id,
+                "Who should receive it? Choose 'Subscribers' or send a group chat id.",
+            )
+            .reply_markup(keyboard::push_target_keyboard())
+            .await?;
+            dialogue
+                .update(QuizState::SchedulePushTarget { quiz_name, fire_at })
+                .await?;
+        }
+        Some(_) => {
+            bot.send_message(msg.chat.id, "That time has already passed. Try again.")
+                .await?;
+        }
+        None => {
+            bot.send_message(
+                msg.chat.id,
+                "Invalid input. Please use YYYY-MM-DD HH:MM [timezone].",
+            )
+            .await?;
+        }
+    }
+
+    Ok(())
+}
+
+#[instrument(level = "info", skip(connection, bot, dialogue))]
+pub(crate) async fn schedule_push_target<Connect: Schedule>(
+    bot: Bot,
+    msg: Message,
+    dialogue: UserDialogue,
+    (quiz_name, fire_at): (String, DateTime<Utc>),
+    connection: Arc<Connect>,
+) -> HandlerResult {
+    let chat_id = match msg.text() {
+        Some("Subscribers") => None,
+        Some(chat_id) => match chat_id.trim().parse::<i64>() {
+            Ok(chat_id) => Some(chat_id),
+            Err(_) => {
+                bot.send_message(msg.chat.id, "Invalid chat id. Try again.")
+                    .await?;
+                return Ok(());
+            }
+        },
+        None => {
+            bot.send_message(msg.chat.id, "Invalid input. Try again.")
+                .await?;
+            return Ok(());
+        }
+    };
+
+    connection
+        .schedule_push(&quiz_name, chat_id, fire_at)
+        .await?;
+    bot.send_message(
+        msg.chat.id,
+        format!(
+            "Push of '{}' scheduled for {}.",
+            quiz_name,
+            fire_at.format("%Y-%m-%d %H:%M UTC")
+        ),
+    )
+    .reply_markup(keyboard::edit_quiz_keyboard())
+    .await?;
+    dialogue.update(QuizState::HandleQuiz { quiz_name }).await?;
+
+    Ok(())
+}
+
+// Reads an optional limit from the message: `Ok(None)` for "No limit", `Err(())` for garbage.
+fn parse_limit<T>(msg: &Message, parse: impl Fn(&str) -> Option<T>) -> Result<Option<T>, ()> {
+    match msg.text() {
+        Some("No limit") => Ok(None),
+        Some(text) => parse(text).map(Some).ok_or(()),
+        None => Err(()),
+    }
+}
+
+#[instrument(level = "info", skip(bot, dialogue))]
+pub(crate) async fn edit_opens_at(
+    bot: Bot,
+    msg: Message,
+    dialogue: UserDialogue,
+    quiz_name: String,
+) -> HandlerResult {
+    match parse_limit(&msg, parse_datetime) {
+        Ok(opens_at) => {
+            bot.send_message(
+                msg.chat.id,
+                "When does the quiz close? Send YYYY-MM-DD HH:MM [timezone] or choose 'No limit'.",
+            )
+            .reply_markup(keyboard::no_limit_keyboard())
+            .await?;
+            dialogue
+                .update(QuizState::EditClosesAt {
+                    quiz_name,
+                    opens_at,
+                })
+                .await?;
+        }
+        Err(()) => {
+            bot.send_message(
+                msg.chat.id,
+                "Invalid input. Please use YYYY-MM-DD HH:MM [timezone].",
+            )
+            .await?;
+        }
+    }
+
+    Ok(())
+}
+
+#[instrument(level = "info", skip(bot, dialogue))]
+pub(crate) async fn edit_closes_at(
+    bot: Bot,
+    msg: Message,
+    dialogue: UserDialogue,
+    (quiz_name, opens_at): (String, Option<DateTime<Utc>>),
+) -> HandlerResult {
+    match parse_limit(&msg, parse_datetime) {
+        Ok(Some(closes_at)) if opens_at.is_some_and(|opens_at| closes_at <= opens_at) => {
+            bot.send_message(
+                msg.chat.id,
+                "The quiz must close after it opens. Try again.",
+            )
+            .await?;
+        }
+        Ok(closes_at) => {
+            bot.send_message(
+                msg.chat.id,
+                "How many attempts does every user have? Send a number or choose 'No limit'.",
+            )
+            .reply_markup(keyboard::no_limit_keyboard())
+            .await?;
+            dialogue
+                .update(QuizState::EditMaxAttempts {
+                    quiz_name,
+                    opens_at,
+                    closes_at,
+                })
+                .await?;
+        }
+        Err(()) => {
+            bot.send_message(
+                msg.chat.id,
+                "Invalid input. Please use YYYY-MM-DD HH:MM [timezone].",
+            )
+            .await?;
+        }
+    }
+
+    Ok(())
+}
+
+#[instrument(level = "info", skip(connection, bot, dialogue))]
+pub(crate) async fn edit_max_attempts<Connect: EditQuiz>(
+    bot: Bot,
+    msg: Message,
+    dialogue: UserDialogue,
+    (quiz_name, opens_at, closes_at): (String, Option<DateTime<Utc>>, Option<DateTime<Utc>>),
+    connection: Arc<Connect>,
+) -> HandlerResult {
+    let parse_attempts = |text: &str| text.trim().parse::<i32>().ok().filter(|max| *max > 0);
+    match parse_limit(&msg, parse_attempts) {
+        Ok(max_attempts) => {
+            let availability = Availability::new(opens_at, closes_at, max_attempts);
+            connection
+                .edit_availability(&quiz_name, &availability)
+                .await?;
+            bot.send_message(
+                msg.chat.id,
+                format!("Availability updated.\n{}", availability),
+            )
+            .reply_markup(keyboard::edit_quiz_keyboard())
+            .await?;
+            dialogue.update(QuizState::HandleQuiz { quiz_name }).await?;
+        }
+        Err(()) => {
+            bot.send_message(msg.chat.id, "Please, send a positive number.")
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+
+#[instrument(level = "info", skip(bot, dialogue))]
+pub(crate) async fn edit_pass_mark(
+    bot: Bot,
+    msg: Message,
+    dialogue: UserDialogue,
+    quiz_name: String,
+) -> HandlerResult {
+    let pass_mark = match msg.text() {
+        Some("No pass mark") => None,
+        Some(text) => match PassMark::parse(text) {
+            Some(pass_mark) => Some(pass_mark),
+            None => {
+                bot.send_message(
+                    msg.chat.id,
+                    "Invalid input. Send a percentage like 60% or a number of points.",
+                )
+                .await?;
+                return Ok(());
+            }
+        },
+        None => {
+            bot.send_message(msg.chat.id, "Invalid input. Try again.")
+                .await?;
+            return Ok(());
+        }
+    };
+
+    bot.send_message(
+        msg.chat.id,
+        "Send grade bands as label and minimal percentage, e.g. 'A 90, B 75, C 60', or choose 'No grades'.",
+    )
+    .reply_markup(keyboard::no_grades_keyboard())
+    .await?;
+    dialogue
+        .update(QuizState::EditGradeBands {
+            quiz_name,
+            pass_mark,
+        })
+        .await?;
+
+    Ok(())
+}
+
+#[instrument(level = "info", skip(connection, bot, dialogue))]
+pub(crate) async fn edit_grade_bands<Connect: EditQuiz>(
+    bot: Bot,
+    msg: Message,
+    dialogue: UserDialogue,
+    (quiz_name, pass_mark): (String, Option<PassMark>),
+    connection: Arc<Connect>,
+) -> HandlerResult {
+    let bands = match msg.text() {
+        Some("No grades") => Some(Vec::new()),
+        Some(text) => GradeBand::parse_all(text),
+        None => None,
+    };
+
+    match bands {
+        Some(bands) => {
+            let grading = Grading::new(pass_mark, bands);
+            connection.edit_grading(&quiz_name, &grading).await?;
+            bot.send_message(msg.chat.id, format!("Grading updated.\n{}", grading))
+                .reply_markup(keyboard::edit_quiz_keyboard())
+                .await?;
+            dialogue.update(QuizState::HandleQuiz { quiz_name }).await?;
+        }
+        None => {
+            bot.send_message(
+                msg.chat.id,
+                "Invalid input. Use label and percentage pairs like 'A 90, B 75'.",
+            )
+            .await?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::validator::MAX_MESSAGE_LEN;
+
+    #[test]
+    fn skipped_report_fits_one_message() {
+        let skipped: Vec<Skipped> = (0..100)
+            .map(|i| Skipped::NoCorrect(format!("{} {}", i, "q".repeat(4000))))
+            .collect();
+
+        let report = skipped_report(&skipped);
+
+        assert!(report.chars().count() < MAX_MESSAGE_LEN);
+        assert_eq!(report.lines().count(), MAX_LISTED_SKIPPED + 1);
+        assert!(report.ends_with("...and 80 more."));
+    }
+
+    #[test]
+    fn short_skipped_report_is_complete() {
+        let report = skipped_report(&[Skipped::NoCorrect("Neither?".to_owned())]);
+
+        assert_eq!(report, "Question 'Neither?' has no correct answer.");
+    }
+}
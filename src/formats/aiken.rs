@@ -0,0 +1,416 @@
+use std::fmt;
+
+use crate::database::quiz::{Answer, Question, Quiz};
+
+use super::{Import, ImportProblem};
+
+const ANSWER_PREFIX: &str = "ANSWER:";
+const LETTERS: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// Question left out of an export because Aiken can't express it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Skipped {
+    MultipleCorrect(String),
+    NoCorrect(String),
+    TooManyAnswers(String),
+}
+
+/// Aiken text of a quiz with the questions that were left out.
+#[derive(Debug, Clone, Default)]
+pub struct Export {
+    text: String,
+    skipped: Vec<Skipped>,
+}
+
+impl fmt::Display for Skipped {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Skipped::MultipleCorrect(question) => write!(
+                f,
+                "Question '{}' has more than one correct answer.",
+                question
+            ),
+            Skipped::NoCorrect(question) => {
+                write!(f, "Question '{}' has no correct answer.", question)
+            }
+            Skipped::TooManyAnswers(question) => write!(
+                f,
+                "Question '{}' has more answers than letters A to Z.",
+                question
+            ),
+        }
+    }
+}
+
+impl Export {
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn skipped(&self) -> &[Skipped] {
+        &self.skipped
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+}
+
+// Question read so far, waiting for its ANSWER line.
+struct Draft {
+    line: usize,
+    text: String,
+    options: Vec<(char, String)>,
+}
+
+/// Reads questions in the Aiken format: a question line, lettered options and `ANSWER: X`.
+pub fn parse(text: &str) -> Import {
+    let mut import = Import::default();
+    let mut current: Option<Draft> = None;
+
+    for (idx, line) in text.trim_start_matches('\u{feff}').lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(answer) = line.strip_prefix(ANSWER_PREFIX) {
+            match current.take() {
+                Some(draft) => finish(draft, answer.trim(), &mut import),
+                None => import.push_problem(ImportProblem::error(
+                    idx + 1,
+                    "ANSWER line without a question.",
+                )),
+            }
+            continue;
+        }
+
+        match (&mut current, option(line)) {
+            (Some(draft), Some(option)) => draft.options.push(option),
+            (Some(draft), None) if draft.options.is_empty() => {
+                draft.text.push(' ');
+                draft.text.push_str(line);
+            }
+            (draft, _) => {
+                if let Some(draft) = draft.take() {
+                    no_answer_line(&draft, &mut import);
+                }
+                current = Some(Draft {
+                    line: idx + 1,
+                    text: line.to_owned(),
+                    options: vec![],
+                });
+            }
+        }
+    }
+    if let Some(draft) = current {
+        no_answer_line(&draft, &mut import);
+    }
+
+    import
+}
+
+fn no_answer_line(draft: &Draft, import: &mut Import) {
+    import.push_problem(ImportProblem::error(
+        draft.line,
+        "The question has no ANSWER line.",
+    ));
+}
+
+// `A. text` or `A) text`.
+fn option(line: &str) -> Option<(char, String)> {
+    let mut chars = line.chars();
+    let letter = chars.next().filter(char::is_ascii_uppercase)?;
+    let rest = chars.as_str();
+    let text = rest
+        .strip_prefix(". ")
+        .or_else(|| rest.strip_prefix(") "))?
+        .trim();
+
+    (!text.is_empty()).then(|| (letter, text.to_owned()))
+}
+
+fn finish(draft: Draft, answer: &str, import: &mut Import) {
+    if draft.options.is_empty() {
+        import.push_problem(ImportProblem::error(
+            draft.line,
+            "The question has no options.",
+        ));
+        return;
+    }
+
+    let mut letters = answer.chars();
+    let (Some(correct), None) = (letters.next(), letters.next()) else {
+        import.push_problem(ImportProblem::error(
+            draft.line,
+            format!(
+                "ANSWER '{}' is not a single letter, Aiken questions have one correct answer.",
+                answer
+            ),
+        ));
+        return;
+    };
+    if !draft.options.iter().any(|(letter, _)| *letter == correct) {
+        import.push_problem(ImportProblem::error(
+            draft.line,
+            format!("ANSWER '{}' is not one of the options.", correct),
+        ));
+        return;
+    }
+    if !draft
+        .options
+        .iter()
+        .zip(LETTERS.chars())
+        .all(|((letter, _), expected)| *letter == expected)
+    {
+        import.push_problem(ImportProblem::warning(
+            draft.line,
+            "Options are not lettered A, B, C... in order.",
+        ));
+    }
+
+    let answers = draft
+        .options
+        .into_iter()
+        .map(|(letter, text)| Answer::new(text, letter == correct))
+        .collect();
+    import.push_question(Question::new(draft.text, Some(answers)));
+}
+
+/// Writes the quiz in the Aiken format. Questions without exactly one correct
+/// answer can't be expressed and are left out.
+pub fn export(quiz: &Quiz) -> Export {
+    let mut export = Export::default();
+
+    for question in quiz.questions() {
+        let text = one_line(&question.text());
+        let answers = question.answers();
+        let correct: Vec<usize> = answers
+            .iter()
+            .enumerate()
+            .filter(|(_, answer)| answer.is_correct())
+            .map(|(idx, _)| idx)
+            .collect();
+
+        let correct = match correct[..] {
+            _ if answers.len() > LETTERS.len() => {
+                export.skipped.push(Skipped::TooManyAnswers(text));
+                continue;
+            }
+            [correct] => correct,
+            [] => {
+                export.skipped.push(Skipped::NoCorrect(text));
+                continue;
+            }
+            _ => {
+                export.skipped.push(Skipped::MultipleCorrect(text));
+                continue;
+            }
+        };
+
+        export.text.push_str(&format!("{}\n", text));
+        for (answer, letter) in answers.iter().zip(LETTERS.chars()) {
+            export
+                .text
+                .push_str(&format!("{}. {}\n", letter, one_line(&answer.text())));
+        }
+        export.text.push_str(&format!(
+            "{} {}\n\n",
+            ANSWER_PREFIX,
+            LETTERS.chars().nth(correct).unwrap_or('A')
+        ));
+    }
+
+    export
+}
+
+// Aiken has one line per question and option.
+fn one_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::validator::Severity;
+
+    fn question(text: &str, answers: &[(&str, bool)]) -> Question {
+        Question::new(
+            text.to_owned(),
+            Some(
+                answers
+                    .iter()
+                    .map(|(text, is_correct)| Answer::new(text.to_string(), *is_correct))
+                    .collect(),
+            ),
+        )
+    }
+
+    fn quiz(questions: Vec<Question>) -> Quiz {
+        Quiz::new(
+            "Quiz".to_owned(),
+            "Geography".to_owned(),
+            "author".to_owned(),
+            Some(questions),
+        )
+    }
+
+    fn summary(questions: &[Question]) -> Vec<(String, Vec<(String, bool)>)> {
+        questions
+            .iter()
+            .map(|question| {
+                (
+                    question.text().to_owned(),
+                    question
+                        .answers()
+                        .iter()
+                        .map(|answer| (answer.text().to_owned(), answer.is_correct()))
+                        .collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn round_trip() {
+        let questions = vec![
+            question(
+                "Capital of France?",
+                &[("Paris", true), ("Rome", false), ("Berlin", false)],
+            ),
+            question("Largest ocean?", &[("Atlantic", false), ("Pacific", true)]),
+        ];
+
+        let export = export(&quiz(questions.clone()));
+        let import = parse(export.text());
+
+        assert!(export.skipped().is_empty());
+        assert!(import.problems().is_empty(), "{:?}", import.problems());
+        assert_eq!(summary(import.questions()), summary(&questions));
+    }
+
+    #[test]
+    fn export_puts_each_question_on_one_line() {
+        let export = export(&quiz(vec![question(
+            "Capital\nof  France?",
+            &[("Paris\ncity", true), ("Rome", false)],
+        )]));
+
+        assert_eq!(
+            export.text(),
+            "Capital of France?\nA. Paris city\nB. Rome\nANSWER: A\n\n"
+        );
+    }
+
+    #[test]
+    fn export_skips_what_aiken_cant_hold() {
+        let letters: Vec<String> = (0..=LETTERS.len())
+            .map(|i| format!("Option {}", i))
+            .collect();
+        let too_many: Vec<(&str, bool)> = letters
+            .iter()
+            .enumerate()
+            .map(|(i, text)| (text.as_str(), i == 0))
+            .collect();
+
+        let export = export(&quiz(vec![
+            question("Both?", &[("A", true), ("B", true)]),
+            question("Neither?", &[("A", false), ("B", false)]),
+            question("Many?", &too_many),
+            question("Fine?", &[("Yes", true), ("No", false)]),
+        ]));
+
+        assert_eq!(
+            export.skipped(),
+            [
+                Skipped::MultipleCorrect("Both?".to_owned()),
+                Skipped::NoCorrect("Neither?".to_owned()),
+                Skipped::TooManyAnswers("Many?".to_owned()),
+            ]
+        );
+        assert_eq!(export.text(), "Fine?\nA. Yes\nB. No\nANSWER: A\n\n");
+    }
+
+    #[test]
+    fn export_of_only_skipped_questions_is_empty() {
+        let export = export(&quiz(vec![question("Neither?", &[("A", false)])]));
+
+        assert!(export.is_empty());
+    }
+
+    #[test]
+    fn reports_broken_questions() {
+        let cases = [
+            (
+                "answer without a question",
+                "ANSWER: A",
+                1,
+                Severity::Error,
+                "without a question",
+            ),
+            (
+                "letter that isn't an option",
+                "Capital?\nA. Paris\nB. Rome\nANSWER: C",
+                1,
+                Severity::Error,
+                "'C' is not one of the options",
+            ),
+            (
+                "several letters",
+                "Capital?\nA. Paris\nB. Rome\nANSWER: AB",
+                1,
+                Severity::Error,
+                "not a single letter",
+            ),
+            (
+                "no answer line",
+                "Capital?\nA. Paris\nB. Rome",
+                1,
+                Severity::Error,
+                "no ANSWER line",
+            ),
+            (
+                "no options",
+                "\nCapital?\nANSWER: A",
+                2,
+                Severity::Error,
+                "no options",
+            ),
+        ];
+
+        for (name, text, line, severity, message) in cases {
+            let import = parse(text);
+
+            assert!(import.questions().is_empty(), "{}", name);
+            assert_eq!(import.problems().len(), 1, "{}", name);
+            let problem = &import.problems()[0];
+            assert_eq!(problem.line(), line, "{}", name);
+            assert_eq!(problem.severity(), severity, "{}", name);
+            assert!(problem.message().contains(message), "{}: {}", name, problem);
+        }
+    }
+
+    #[test]
+    fn options_out_of_order_are_imported_with_a_warning() {
+        let import = parse("Capital?\nB. Rome\nA. Paris\nANSWER: A");
+
+        assert_eq!(
+            summary(import.questions()),
+            summary(&[question("Capital?", &[("Rome", false), ("Paris", true)])])
+        );
+        assert_eq!(import.problems().len(), 1);
+        assert_eq!(import.problems()[0].severity(), Severity::Warning);
+    }
+
+    #[test]
+    fn next_question_starts_after_a_missing_answer_line() {
+        let import = parse("First?\nA. Yes\n\nSecond?\nA. Yes\nB. No\nANSWER: B");
+
+        assert_eq!(
+            summary(import.questions()),
+            summary(&[question("Second?", &[("Yes", false), ("No", true)])])
+        );
+        assert_eq!(import.problems().len(), 1);
+        assert_eq!(import.problems()[0].line(), 1);
+    }
+}
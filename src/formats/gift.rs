@@ -0,0 +1,672 @@
+use crate::database::quiz::{Answer, Question};
+
+use super::{Import, ImportProblem};
+
+/// Characters that GIFT lets escape with a backslash.
+const SPECIAL: [char; 7] = ['~', '=', '#', '{', '}', ':', '\\'];
+
+/// Reads multiple-choice and true/false questions from Moodle GIFT text.
+pub fn parse(text: &str) -> Import {
+    let mut import = Import::default();
+    for (line, record) in records(text.trim_start_matches('\u{feff}'), &mut import) {
+        if let Some(question) = parse_question(line, &record, &mut import) {
+            import.push_question(question);
+        }
+    }
+
+    import
+}
+
+// Splits the text into questions at blank lines, dropping comments and categories.
+fn records(text: &str, import: &mut Import) -> Vec<(usize, String)> {
+    let mut records = vec![];
+    let mut current: Option<(usize, String)> = None;
+
+    for (idx, line) in text.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("//") {
+            continue;
+        }
+        if trimmed.starts_with("$CATEGORY:") {
+            import.push_problem(ImportProblem::warning(
+                idx + 1,
+                "Categories are not supported, the line is ignored.",
+            ));
+            continue;
+        }
+        if trimmed.is_empty() {
+            // A blank line inside an answer block doesn't end the question.
+            match current.take() {
+                Some((start, record)) if is_open(&record) => current = Some((start, record)),
+                Some(record) => records.push(record),
+                None => (),
+            }
+            continue;
+        }
+
+        match &mut current {
+            Some((_, record)) => {
+                record.push('\n');
+                record.push_str(trimmed);
+            }
+            None => current = Some((idx + 1, trimmed.to_owned())),
+        }
+    }
+    records.extend(current);
+
+    records
+}
+
+fn parse_question(line: usize, record: &str, import: &mut Import) -> Option<Question> {
+    let mut text = record.trim();
+
+    if let Some(rest) = text.strip_prefix("::") {
+        let Some(end) = find_double_colon(rest) else {
+            import.push_problem(ImportProblem::error(
+                line,
+                "The question title is not closed with '::'.",
+            ));
+            return None;
+        };
+        text = rest[end + 2..].trim_start();
+    }
+
+    for format in ["[plain]", "[moodle]", "[html]", "[markdown]"] {
+        if let Some(rest) = text.strip_prefix(format) {
+            if format == "[html]" || format == "[markdown]" {
+                import.push_problem(ImportProblem::warning(
+                    line,
+                    format!(
+                        "{} formatting is not supported, the text is kept as it is.",
+                        format
+                    ),
+                ));
+            }
+            text = rest.trim_start();
+        }
+    }
+
+    let Some(open) = find_unescaped(text, '{') else {
+        import.push_problem(ImportProblem::error(
+            line,
+            "No answers in braces. Descriptions are not supported.",
+        ));
+        return None;
+    };
+    let Some(close) = find_unescaped(&text[open..], '}').map(|close| open + close) else {
+        import.push_problem(ImportProblem::error(
+            line,
+            "The answer block is not closed with '}'.",
+        ));
+        return None;
+    };
+
+    let before = unescape(text[..open].trim());
+    let (block, explanation) = split_general_feedback(text[open + 1..close].trim());
+    let after = text[close + 1..].trim();
+    if find_unescaped(after, '{').is_some() {
+        import.push_problem(ImportProblem::error(
+            line,
+            "Only one answer block per question is supported.",
+        ));
+        return None;
+    }
+
+    // Missing word questions keep a blank where the answer block was.
+    let stem = if after.is_empty() {
+        before
+    } else {
+        let after = unescape(after);
+        let space = if after.starts_with(char::is_alphanumeric) {
+            " "
+        } else {
+            ""
+        };
+        format!("{} _____{}{}", before, space, after)
+    };
+    if stem.trim().is_empty() {
+        import.push_problem(ImportProblem::error(line, "The question has no text."));
+        return None;
+    }
+
+    let answers = if block.is_empty() {
+        import.push_problem(ImportProblem::error(
+            line,
+            "Essay questions are not supported.",
+        ));
+        return None;
+    } else if block.starts_with('#') {
+        // Takers can only choose from the listed answers, so a typed number can't be graded.
+        import.push_problem(ImportProblem::error(
+            line,
+            "Numeric questions are not supported.",
+        ));
+        return None;
+    } else if let Some(answers) = true_false_answers(line, block, import) {
+        answers
+    } else {
+        choice_answers(line, block, import)?
+    };
+
+    let mut question = Question::new(stem.trim().to_owned(), Some(answers));
+    question.set_explanation(explanation);
+
+    Some(question)
+}
+
+// General feedback after `####` becomes the explanation of the question.
+fn split_general_feedback(block: &str) -> (&str, Option<String>) {
+    match positions(block, '#')
+        .into_iter()
+        .find(|idx| block[*idx..].starts_with("####"))
+    {
+        Some(idx) => {
+            let feedback = unescape(block[idx + 4..].trim());
+            (
+                block[..idx].trim(),
+                (!feedback.is_empty()).then_some(feedback),
+            )
+        }
+        None => (block, None),
+    }
+}
+
+// `{T}`, `{TRUE}`, `{F}` or `{FALSE}`, maybe with feedback.
+fn true_false_answers(line: usize, block: &str, import: &mut Import) -> Option<Vec<Answer>> {
+    let (value, feedback) = split_feedback(block);
+    let is_true = match value.trim().to_uppercase().as_str() {
+        "T" | "TRUE" => true,
+        "F" | "FALSE" => false,
+        _ => return None,
+    };
+    if feedback.is_some() {
+        feedback_dropped(line, import);
+    }
+
+    Some(vec![
+        Answer::new("True".to_owned(), is_true),
+        Answer::new("False".to_owned(), !is_true),
+    ])
+}
+
+// Multiple choice with `=` right and `~` wrong answers.
+fn choice_answers(line: usize, block: &str, import: &mut Import) -> Option<Vec<Answer>> {
+    let mut starts = positions(block, '=');
+    starts.extend(positions(block, '~'));
+    starts.sort_unstable();
+    if starts.first() != Some(&0) {
+        import.push_problem(ImportProblem::error(
+            line,
+            "Answers have to start with '=' or '~'.",
+        ));
+        return None;
+    }
+
+    let mut answers = vec![];
+    let mut has_wrong = false;
+    let mut partial = false;
+    let mut feedback = false;
+    for (i, start) in starts.iter().enumerate() {
+        let end = starts.get(i + 1).copied().unwrap_or(block.len());
+        let is_right = block[*start..].starts_with('=');
+        let body = block[start + 1..end].trim();
+
+        if body.contains("->") {
+            import.push_problem(ImportProblem::error(
+                line,
+                "Matching questions are not supported.",
+            ));
+            return None;
+        }
+        let Some((body, weight)) = split_weight(body) else {
+            import.push_problem(ImportProblem::error(
+                line,
+                format!("Answer weight in '{}' is not a number.", body),
+            ));
+            return None;
+        };
+        let (text, answer_feedback) = split_feedback(body);
+        feedback |= answer_feedback.is_some();
+
+        let text = unescape(text.trim());
+        if text.is_empty() {
+            import.push_problem(ImportProblem::error(line, "An answer has no text."));
+            return None;
+        }
+
+        let is_correct = if is_right {
+            true
+        } else {
+            has_wrong = true;
+            match weight {
+                Some(weight) => {
+                    partial |= weight > 0.0 && weight < 100.0;
+                    weight > 0.0
+                }
+                None => false,
+            }
+        };
+        answers.push(Answer::new(text, is_correct));
+    }
+
+    if partial {
+        import.push_problem(ImportProblem::warning(
+            line,
+            "Partial credit is not supported, answers with a positive weight count as correct.",
+        ));
+    }
+    // Without wrong answers it is a short-answer question, every choice would be correct.
+    if !has_wrong {
+        import.push_problem(ImportProblem::error(
+            line,
+            "Short-answer questions are not supported.",
+        ));
+        return None;
+    }
+    if feedback {
+        feedback_dropped(line, import);
+    }
+
+    Some(answers)
+}
+
+fn feedback_dropped(line: usize, import: &mut Import) {
+    import.push_problem(ImportProblem::warning(
+        line,
+        "Feedback on answers is not supported and was dropped. General feedback after '####' is kept as the explanation.",
+    ));
+}
+
+// Splits a leading `%50%` weight off an answer, `None` if it isn't a number.
+fn split_weight(text: &str) -> Option<(&str, Option<f64>)> {
+    let Some(rest) = text.strip_prefix('%') else {
+        return Some((text, None));
+    };
+    let (weight, rest) = rest.split_once('%')?;
+    let weight = weight.trim().parse::<f64>().ok()?;
+
+    Some((rest.trim_start(), Some(weight)))
+}
+
+fn split_feedback(text: &str) -> (&str, Option<&str>) {
+    match find_unescaped(text, '#') {
+        Some(idx) => (&text[..idx], Some(&text[idx + 1..])),
+        None => (text, None),
+    }
+}
+
+// Whether the record has an answer block that isn't closed yet.
+fn is_open(record: &str) -> bool {
+    positions(record, '{').len() > positions(record, '}').len()
+}
+
+fn find_double_colon(text: &str) -> Option<usize> {
+    positions(text, ':')
+        .into_iter()
+        .find(|idx| text[idx + 1..].starts_with(':'))
+}
+
+fn find_unescaped(text: &str, target: char) -> Option<usize> {
+    positions(text, target).first().copied()
+}
+
+// Byte offsets of `target` not escaped with a backslash.
+fn positions(text: &str, target: char) -> Vec<usize> {
+    let mut positions = vec![];
+    let mut escaped = false;
+    for (idx, c) in text.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == target {
+            positions.push(idx);
+        }
+    }
+
+    positions
+}
+
+fn unescape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some(next) if SPECIAL.contains(&next) => result.push(next),
+            Some(next) => {
+                result.push('\\');
+                result.push(next);
+            }
+            None => result.push('\\'),
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::validator::Severity;
+
+    struct Case {
+        name: &'static str,
+        gift: &'static str,
+        questions: Vec<(&'static str, Vec<(&'static str, bool)>)>,
+        problems: Vec<(usize, Severity, &'static str)>,
+    }
+
+    fn case(
+        name: &'static str,
+        gift: &'static str,
+        questions: Vec<(&'static str, Vec<(&'static str, bool)>)>,
+        problems: Vec<(usize, Severity, &'static str)>,
+    ) -> Case {
+        Case {
+            name,
+            gift,
+            questions,
+            problems,
+        }
+    }
+
+    fn question(
+        stem: &'static str,
+        answers: &[(&'static str, bool)],
+    ) -> (&'static str, Vec<(&'static str, bool)>) {
+        (stem, answers.to_vec())
+    }
+
+    fn error(line: usize, message: &'static str) -> (usize, Severity, &'static str) {
+        (line, Severity::Error, message)
+    }
+
+    fn warning(line: usize, message: &'static str) -> (usize, Severity, &'static str) {
+        (line, Severity::Warning, message)
+    }
+
+    fn check(case: Case) {
+        let import = parse(case.gift);
+
+        let questions: Vec<(String, Vec<(String, bool)>)> = import
+            .questions()
+            .iter()
+            .map(|question| {
+                (
+                    question.text().to_owned(),
+                    question
+                        .answers()
+                        .iter()
+                        .map(|answer| (answer.text().to_owned(), answer.is_correct()))
+                        .collect(),
+                )
+            })
+            .collect();
+        let expected: Vec<(String, Vec<(String, bool)>)> = case
+            .questions
+            .iter()
+            .map(|(stem, answers)| {
+                (
+                    stem.to_string(),
+                    answers
+                        .iter()
+                        .map(|(text, is_correct)| (text.to_string(), *is_correct))
+                        .collect(),
+                )
+            })
+            .collect();
+        assert_eq!(questions, expected, "{}: questions", case.name);
+
+        assert_eq!(
+            import.problems().len(),
+            case.problems.len(),
+            "{}: problems {:?}",
+            case.name,
+            import.problems()
+        );
+        for (problem, (line, severity, message)) in import.problems().iter().zip(&case.problems) {
+            assert_eq!(problem.line(), *line, "{}: {}", case.name, problem);
+            assert_eq!(problem.severity(), *severity, "{}: {}", case.name, problem);
+            assert!(
+                problem.message().contains(message),
+                "{}: '{}' doesn't mention '{}'",
+                case.name,
+                problem,
+                message
+            );
+        }
+    }
+
+    #[test]
+    fn imports_supported_questions() {
+        let cases = vec![
+            case(
+                "multiple choice",
+                "What is 2+2? {=4 ~3 ~5}",
+                vec![question(
+                    "What is 2+2?",
+                    &[("4", true), ("3", false), ("5", false)],
+                )],
+                vec![],
+            ),
+            case(
+                "true",
+                "The sky is blue. {T}",
+                vec![question(
+                    "The sky is blue.",
+                    &[("True", true), ("False", false)],
+                )],
+                vec![],
+            ),
+            case(
+                "false",
+                "The sun is cold. {FALSE}",
+                vec![question(
+                    "The sun is cold.",
+                    &[("True", false), ("False", true)],
+                )],
+                vec![],
+            ),
+            case(
+                "weights",
+                "Pick {=A ~%50%B ~%-50%C}",
+                vec![question("Pick", &[("A", true), ("B", true), ("C", false)])],
+                vec![warning(1, "Partial credit")],
+            ),
+            case(
+                "answer feedback",
+                "Q? {=A#Right ~B#Wrong}",
+                vec![question("Q?", &[("A", true), ("B", false)])],
+                vec![warning(1, "Feedback")],
+            ),
+            case(
+                "general feedback",
+                "Q? {=A ~B ####Read the chapter again}",
+                vec![question("Q?", &[("A", true), ("B", false)])],
+                vec![],
+            ),
+            case(
+                "escapes",
+                r"1\:2 \= \{half\}\nok? {=a\=b ~c\#d}",
+                vec![question(
+                    "1:2 = {half}\nok?",
+                    &[("a=b", true), ("c#d", false)],
+                )],
+                vec![],
+            ),
+            case(
+                "title",
+                "::Q1:: What? {=A ~B}",
+                vec![question("What?", &[("A", true), ("B", false)])],
+                vec![],
+            ),
+            case(
+                "plain",
+                "[plain]Plain? {=A ~B}",
+                vec![question("Plain?", &[("A", true), ("B", false)])],
+                vec![],
+            ),
+            case(
+                "html",
+                "[html]<b>Bold</b>? {=A ~B}",
+                vec![question("<b>Bold</b>?", &[("A", true), ("B", false)])],
+                vec![warning(1, "[html] formatting")],
+            ),
+            case(
+                "markdown",
+                "::T::[markdown]*Bold*? {=A ~B}",
+                vec![question("*Bold*?", &[("A", true), ("B", false)])],
+                vec![warning(1, "[markdown] formatting")],
+            ),
+            case(
+                "category and comments",
+                "$CATEGORY: geography\n\n// capitals\nCapital of France? {=Paris ~Rome}\n\n// the end",
+                vec![question(
+                    "Capital of France?",
+                    &[("Paris", true), ("Rome", false)],
+                )],
+                vec![warning(1, "Categories")],
+            ),
+            case(
+                "blank lines in the answer block",
+                "Q? {\n=A\n\n~B\n}\n\nNext? {T}",
+                vec![
+                    question("Q?", &[("A", true), ("B", false)]),
+                    question("Next?", &[("True", true), ("False", false)]),
+                ],
+                vec![],
+            ),
+            case(
+                "missing word",
+                "Mount {=Everest ~K2} is the highest.",
+                vec![question(
+                    "Mount _____ is the highest.",
+                    &[("Everest", true), ("K2", false)],
+                )],
+                vec![],
+            ),
+            case(
+                "missing word before punctuation",
+                "The capital of France is {=Paris ~Rome}.",
+                vec![question(
+                    "The capital of France is _____.",
+                    &[("Paris", true), ("Rome", false)],
+                )],
+                vec![],
+            ),
+        ];
+
+        for case in cases {
+            check(case);
+        }
+    }
+
+    #[test]
+    fn keeps_general_feedback_as_explanation() {
+        let cases = [
+            (
+                "Q? {=A ~B ####Read chapter 2\\: it explains why}",
+                Some("Read chapter 2: it explains why"),
+            ),
+            (
+                "Q? {T ####Water boils at 100°C.}",
+                Some("Water boils at 100°C."),
+            ),
+            ("Q? {=A#Right ~B#Wrong}", None),
+            ("Q? {=A ~B ####}", None),
+        ];
+
+        for (gift, explanation) in cases {
+            let import = parse(gift);
+            assert_eq!(import.questions()[0].explanation(), explanation, "{}", gift);
+        }
+    }
+
+    #[test]
+    fn reports_unsupported_questions() {
+        let cases = vec![
+            case(
+                "matching",
+                "Match {=cat -> meow =dog -> woof}",
+                vec![],
+                vec![error(1, "Matching questions")],
+            ),
+            case(
+                "essay",
+                "Write about your summer. {}",
+                vec![],
+                vec![error(1, "Essay questions")],
+            ),
+            case(
+                "description",
+                "Just some text.",
+                vec![],
+                vec![error(1, "No answers in braces")],
+            ),
+            case(
+                "unclosed answer block",
+                "Q? {=A ~B",
+                vec![],
+                vec![error(1, "not closed with '}'")],
+            ),
+            case(
+                "two answer blocks",
+                "Q {=A ~B} and {=C ~D}",
+                vec![],
+                vec![error(1, "Only one answer block")],
+            ),
+            case(
+                "unclosed title",
+                "::Q1 What? {=A ~B}",
+                vec![],
+                vec![error(1, "not closed with '::'")],
+            ),
+            case(
+                "answer without a marker",
+                "Q? {A ~B}",
+                vec![],
+                vec![error(1, "start with '=' or '~'")],
+            ),
+            case(
+                "weight not a number",
+                "Q? {=A ~%half%B}",
+                vec![],
+                vec![error(1, "weight")],
+            ),
+            case(
+                "short answer",
+                "Who wrote Hamlet? {=Shakespeare =William Shakespeare}",
+                vec![],
+                vec![error(1, "Short-answer questions")],
+            ),
+            case(
+                "numeric",
+                "2+2? {#4}",
+                vec![],
+                vec![error(1, "Numeric questions")],
+            ),
+            case(
+                "numeric range",
+                "Between one and five? {#1..5}",
+                vec![],
+                vec![error(1, "Numeric questions")],
+            ),
+            case("no text", "{=A ~B}", vec![], vec![error(1, "no text")]),
+            case(
+                "only the broken question is skipped",
+                "Q? {}\n\nR? {=A ~B}",
+                vec![question("R?", &[("A", true), ("B", false)])],
+                vec![error(1, "Essay questions")],
+            ),
+        ];
+
+        for case in cases {
+            check(case);
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use std::fmt;
+
+use crate::database::{quiz::Question, validator::Severity};
+
+pub mod aiken;
+pub mod gift;
+
+/// Something in an imported file that the quiz model can't hold as it is.
+/// Errors skip the question, warnings import it with a loss.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportProblem {
+    line: usize,
+    severity: Severity,
+    message: String,
+}
+
+/// Questions read from a file, with everything that didn't fit on the way.
+#[derive(Debug, Clone, Default)]
+pub struct Import {
+    questions: Vec<Question>,
+    problems: Vec<ImportProblem>,
+}
+
+impl ImportProblem {
+    pub fn error(line: usize, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            severity: Severity::Error,
+            message: message.into(),
+        }
+    }
+
+    pub fn warning(line: usize, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            severity: Severity::Warning,
+            message: message.into(),
+        }
+    }
+
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for ImportProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mark = match self.severity {
+            Severity::Error => "❌",
+            Severity::Warning => "⚠️",
+        };
+        write!(f, "{} Line {}: {}", mark, self.line, self.message)
+    }
+}
+
+impl Import {
+    pub fn questions(&self) -> &[Question] {
+        &self.questions
+    }
+
+    pub fn into_questions(self) -> Vec<Question> {
+        self.questions
+    }
+
+    pub fn problems(&self) -> &[ImportProblem] {
+        &self.problems
+    }
+
+    pub(crate) fn push_question(&mut self, question: Question) {
+        self.questions.push(question);
+    }
+
+    pub(crate) fn push_problem(&mut self, problem: ImportProblem) {
+        self.problems.push(problem);
+    }
+}
+
+/// Reads the file as Aiken or GIFT, by its extension or else by its content.
+pub fn parse(file_name: &str, text: &str) -> Import {
+    let extension = file_name
+        .rsplit_once('.')
+        .map(|(_, extension)| extension.to_lowercase());
+    let is_aiken = match extension.as_deref() {
+        Some("aiken") => true,
+        Some("gift") => false,
+        _ => text
+            .lines()
+            .any(|line| line.trim_start().starts_with("ANSWER:")),
+    };
+
+    if is_aiken {
+        aiken::parse(text)
+    } else {
+        gift::parse(text)
+    }
+}
+
+impl fmt::Display for Import {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Imported {} question(s), {} problem(s).",
+            self.questions.len(),
+            self.problems.len()
+        )?;
+        for problem in &self.problems {
+            writeln!(f, "{}", problem)?;
+        }
+        Ok(())
+    }
+}
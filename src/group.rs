@@ -0,0 +1,494 @@
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    error::Error,
+    sync::{Arc, Mutex},
+};
+
+use chrono::Utc;
+use teloxide::{
+    payloads::{AnswerCallbackQuerySetters, SendMessageSetters},
+    prelude::Requester,
+    types::{CallbackQuery, ChatId, Message, User, UserId},
+    Bot,
+};
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::{
+    database::{
+        attempt::{score, QuestionProgress},
+        connection::{Attempts, RetreiveQuiz},
+        grading::format_points,
+        quiz::{Quiz, Unavailable},
+    },
+    keyboard::{group_answers_keyboard, GROUP_PREFIX, NEXT_QUESTION},
+    poll::{send_quiz_poll, PollMode, PollRegistry},
+    runner::{next_playable, question_text},
+    HandlerResult,
+};
+
+/// Answers of a single member of the group.
+#[derive(Debug)]
+struct Participant {
+    name: String,
+    attempt: Uuid,
+    progress: Vec<QuestionProgress>,
+}
+
+/// Quiz played by every member of a group chat at once.
+///
+/// Unlike `UserDialogue`, which holds one state per chat, answers are kept per user.
+#[derive(Debug)]
+pub(crate) struct GroupSession {
+    quiz: Quiz,
+    curr_idx: usize,
+    host: UserId,
+    participants: HashMap<UserId, Participant>,
+    // Correct answers given to the current question.
+    correct: usize,
+}
+
+/// Running group sessions keyed by chat.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct GroupSessions(Arc<Mutex<HashMap<ChatId, GroupSession>>>);
+
+// What is left to do after an update of the session, decided while holding the lock.
+enum Step {
+    Question(Quiz, usize),
+    Finished(GroupSession),
+}
+
+#[instrument(level = "info", skip(connection, bot, sessions, polls))]
+pub(crate) async fn start<QuizRetriever: RetreiveQuiz>(
+    bot: Bot,
+    msg: Message,
+    quiz_name: String,
+    sessions: GroupSessions,
+    polls: PollRegistry,
+    connection: Arc<QuizRetriever>,
+) -> HandlerResult {
+    if msg.chat.is_private() {
+        bot.send_message(
+            msg.chat.id,
+            "Group mode only works in group chats. Add the bot to a group and use /group <quiz name> there.",
+        )
+        .await?;
+        return Ok(());
+    }
+    let Some(host) = &msg.from else {
+        return Ok(());
+    };
+    if !bot
+        .get_chat_member(msg.chat.id, host.id)
+        .await?
+        .kind
+        .is_privileged()
+    {
+        bot.send_message(msg.chat.id, "Only group admins can start a quiz.")
+            .await?;
+        return Ok(());
+    }
+
+    let quiz_name = quiz_name.trim();
+    if quiz_name.is_empty() {
+        bot.send_message(msg.chat.id, "Usage: /group <quiz name>")
+            .await?;
+        return Ok(());
+    }
+    let Some(quiz) = connection.retreive_quiz(quiz_name).await? else {
+        bot.send_message(msg.chat.id, format!("Quiz '{}' not found.", quiz_name))
+            .await?;
+        return Ok(());
+    };
+    // Attempt limits are checked for every member on their first answer.
+    if let Err(reason) = quiz.check(Utc::now(), 0) {
+        bot.send_message(
+            msg.chat.id,
+            format!("Sorry, you can't start '{}'. {}", quiz.title(), reason),
+        )
+        .await?;
+        return Ok(());
+    }
+    let Some(curr_idx) = next_playable(&quiz, 0) else {
+        bot.send_message(msg.chat.id, "Sorry, no questions for that quiz available.")
+            .await?;
+        return Ok(());
+    };
+
+    let started = {
+        let mut sessions = sessions.0.lock().unwrap();
+        match sessions.entry(msg.chat.id) {
+            Entry::Occupied(_) => false,
+            Entry::Vacant(entry) => {
+                entry.insert(GroupSession {
+                    quiz: quiz.clone(),
+                    curr_idx,
+                    host: host.id,
+                    participants: HashMap::new(),
+                    correct: 0,
+                });
+                true
+            }
+        }
+    };
+
+    if !started {
+        bot.send_message(msg.chat.id, "A quiz is already running in this chat.")
+            .await?;
+        return Ok(());
+    }
+
+    bot.send_message(
+        msg.chat.id,
+        format!(
+            "Group quiz '{}' is starting! Everyone can answer, once per question.",
+            quiz.title()
+        ),
+    )
+    .await?;
+    send_question(&bot, &polls, msg.chat.id, &quiz, curr_idx).await?;
+
+    Ok(())
+}
+
+/// Ends the group quiz early and shows the ranking so far. Only for the host or a group admin.
+#[instrument(level = "info", skip(connection, bot, sessions, polls))]
+pub(crate) async fn stop<Connect: Attempts>(
+    bot: Bot,
+    msg: Message,
+    sessions: GroupSessions,
+    polls: PollRegistry,
+    connection: Arc<Connect>,
+) -> HandlerResult {
+    let Some(user) = &msg.from else {
+        return Ok(());
+    };
+    let is_admin = !msg.chat.is_private()
+        && bot
+            .get_chat_member(msg.chat.id, user.id)
+            .await?
+            .kind
+            .is_privileged();
+
+    let stopped = {
+        let mut sessions = sessions.0.lock().unwrap();
+        match sessions.get(&msg.chat.id) {
+            Some(session) if session.host == user.id || is_admin => {
+                sessions.remove(&msg.chat.id).map(Ok)
+            }
+            Some(_) => Some(Err(())),
+            None => None,
+        }
+    };
+
+    match stopped {
+        Some(Ok(session)) => {
+            polls.forget(msg.chat.id, PollMode::Group);
+            let ranking = finish(connection.as_ref(), &session).await?;
+            bot.send_message(msg.chat.id, format!("The quiz was stopped.\n{}", ranking))
+                .await?;
+        }
+        Some(Err(())) => {
+            bot.send_message(
+                msg.chat.id,
+                "Only the host or a group admin can stop the quiz.",
+            )
+            .await?;
+        }
+        None => {
+            bot.send_message(msg.chat.id, "No quiz is running in this chat.")
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+
+#[instrument(level = "info", skip(connection, bot, sessions, polls))]
+pub(crate) async fn take_answer<Connect: Attempts>(
+    bot: Bot,
+    q: CallbackQuery,
+    sessions: GroupSessions,
+    polls: PollRegistry,
+    connection: Arc<Connect>,
+) -> HandlerResult {
+    let (Some(data), Some(message)) = (&q.data, &q.message) else {
+        return Ok(());
+    };
+    let chat_id = message.chat().id;
+
+    if data == NEXT_QUESTION {
+        return next_question(&bot, &q, chat_id, &sessions, &polls, connection.as_ref()).await;
+    }
+
+    let Some((question_idx, answer_idx)) = data
+        .strip_prefix(GROUP_PREFIX)
+        .and_then(|data| data.split_once(':'))
+        .and_then(|(question_idx, answer_idx)| {
+            Some((
+                question_idx.parse::<usize>().ok()?,
+                answer_idx.parse::<usize>().ok()?,
+            ))
+        })
+    else {
+        return Ok(());
+    };
+
+    if let Err(reason) = admit(&sessions, chat_id, &q.from, connection.as_ref()).await? {
+        bot.answer_callback_query(&q.id)
+            .text(reason.to_string())
+            .show_alert(true)
+            .await?;
+        return Ok(());
+    }
+    let reply = record_answer(&sessions, chat_id, &q.from, question_idx, answer_idx);
+    bot.answer_callback_query(&q.id).text(reply).await?;
+
+    Ok(())
+}
+
+/// Checks that the quiz is available to the member before their first answer,
+/// the same way as when taking it alone, and starts their attempt.
+pub(crate) async fn admit<Connect: Attempts>(
+    sessions: &GroupSessions,
+    chat_id: ChatId,
+    user: &User,
+    connection: &Connect,
+) -> Result<Result<(), Unavailable>, Box<dyn Error + Send + Sync>> {
+    let quiz = match sessions.0.lock().unwrap().get(&chat_id) {
+        Some(session) if !session.participants.contains_key(&user.id) => session.quiz.clone(),
+        _ => return Ok(Ok(())),
+    };
+    let attempts_taken = connection
+        .count_attempts(user.id.0 as i64, quiz.uuid())
+        .await?;
+    if let Err(reason) = quiz.check(Utc::now(), attempts_taken) {
+        return Ok(Err(reason));
+    }
+
+    let attempt = connection
+        .start_attempt(user.id.0 as i64, quiz.uuid(), quiz.revision())
+        .await?;
+    if let Some(session) = sessions.0.lock().unwrap().get_mut(&chat_id) {
+        session
+            .participants
+            .entry(user.id)
+            .or_insert_with(|| Participant {
+                name: user.full_name(),
+                attempt,
+                progress: vec![QuestionProgress::Unanswered; quiz.questions().len()],
+            });
+    }
+
+    Ok(Ok(()))
+}
+
+/// Records the answer of a member, given by a button or by voting in a quiz poll.
+pub(crate) fn record_answer(
+    sessions: &GroupSessions,
+    chat_id: ChatId,
+    user: &User,
+    question_idx: usize,
+    answer_idx: usize,
+) -> &'static str {
+    let mut sessions = sessions.0.lock().unwrap();
+    match sessions.get_mut(&chat_id) {
+        // Answers to questions that were already closed are ignored.
+        Some(session) if session.curr_idx == question_idx => {
+            let question = &session.quiz.questions()[session.curr_idx];
+            // Members take part once `admit` has started their attempt.
+            let Some(participant) = session.participants.get_mut(&user.id) else {
+                return "This question is closed.";
+            };
+
+            if participant.progress[session.curr_idx] != QuestionProgress::Unanswered {
+                "You have already answered this question."
+            } else if let Some(answer) = question.answers().get(answer_idx) {
+                let points = if answer.is_correct() {
+                    session.correct += 1;
+                    question.points()
+                } else {
+                    -question.penalty()
+                };
+                participant.progress[session.curr_idx] = QuestionProgress::Answered { points };
+                "Answer recorded."
+            } else {
+                "Unknown answer."
+            }
+        }
+        _ => "This question is closed.",
+    }
+}
+
+// Closes the current question with its statistics and moves the group on.
+async fn next_question<Connect: Attempts>(
+    bot: &Bot,
+    q: &CallbackQuery,
+    chat_id: ChatId,
+    sessions: &GroupSessions,
+    polls: &PollRegistry,
+    connection: &Connect,
+) -> HandlerResult {
+    let is_admin = bot
+        .get_chat_member(chat_id, q.from.id)
+        .await?
+        .kind
+        .is_privileged();
+
+    let step = {
+        let mut sessions = sessions.0.lock().unwrap();
+        match sessions.get_mut(&chat_id) {
+            Some(session) if session.host == q.from.id || is_admin => {
+                let curr_idx = session.curr_idx;
+                let question = &session.quiz.questions()[curr_idx];
+                let answered = session
+                    .participants
+                    .values()
+                    .filter(|participant| {
+                        participant.progress[curr_idx] != QuestionProgress::Unanswered
+                    })
+                    .count();
+                let correct_answers: Vec<String> = question
+                    .answers()
+                    .iter()
+                    .filter(|answer| answer.is_correct())
+                    .map(|answer| answer.text())
+                    .collect();
+                let mut summary = format!(
+                    "{}\n\nCorrect: {}\n✅ {} of {} answered correctly.",
+                    question.text(),
+                    correct_answers.join(", "),
+                    session.correct,
+                    answered
+                );
+                if let Some(explanation) = question.explanation() {
+                    summary.push_str(&format!("\n\n{}", explanation));
+                }
+                session.correct = 0;
+
+                match next_playable(&session.quiz, curr_idx + 1) {
+                    Some(next_idx) => {
+                        session.curr_idx = next_idx;
+                        Some((summary, Step::Question(session.quiz.clone(), next_idx)))
+                    }
+                    None => {
+                        let session = sessions.remove(&chat_id).unwrap();
+                        Some((summary, Step::Finished(session)))
+                    }
+                }
+            }
+            Some(_) => None,
+            None => return Ok(()),
+        }
+    };
+
+    let Some((summary, step)) = step else {
+        bot.answer_callback_query(&q.id)
+            .text("Only the host or a group admin can move on.")
+            .await?;
+        return Ok(());
+    };
+    bot.answer_callback_query(&q.id).await?;
+
+    // Polls can't be edited into the summary, they are closed and followed by it instead.
+    match q
+        .message
+        .as_ref()
+        .and_then(|message| message.regular_message())
+    {
+        Some(message) if message.poll().is_some() => {
+            polls.remove(&message.poll().unwrap().id);
+            bot.stop_poll(chat_id, message.id).await?;
+            bot.send_message(chat_id, summary).await?;
+        }
+        Some(message) => {
+            bot.edit_message_text(chat_id, message.id, summary).await?;
+        }
+        None => (),
+    }
+
+    match step {
+        Step::Question(quiz, next_idx) => {
+            send_question(bot, polls, chat_id, &quiz, next_idx).await?;
+        }
+        Step::Finished(session) => {
+            let ranking = finish(connection, &session).await?;
+            bot.send_message(chat_id, ranking).await?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn send_question(
+    bot: &Bot,
+    polls: &PollRegistry,
+    chat_id: ChatId,
+    quiz: &Quiz,
+    idx: usize,
+) -> HandlerResult {
+    polls.forget(chat_id, PollMode::Group);
+    let controls = group_answers_keyboard(idx, &[]);
+    if send_quiz_poll(bot, polls, PollMode::Group, chat_id, quiz, idx, controls).await? {
+        return Ok(());
+    }
+
+    let answers: Vec<String> = quiz.questions()[idx]
+        .answers()
+        .iter()
+        .map(|answer| answer.text())
+        .collect();
+    bot.send_message(chat_id, question_text(quiz, idx))
+        .reply_markup(group_answers_keyboard(idx, &answers))
+        .await?;
+
+    Ok(())
+}
+
+// Stores the outcome of every member's attempt and returns the ranking.
+async fn finish<Connect: Attempts>(
+    connection: &Connect,
+    session: &GroupSession,
+) -> Result<String, Box<dyn Error + Send + Sync>> {
+    let max_score = session.quiz.max_score();
+    for participant in session.participants.values() {
+        let score = score(&participant.progress);
+        let outcome = session.quiz.grading().evaluate(score, max_score);
+        connection
+            .finish_attempt(&participant.attempt, score, max_score, &outcome)
+            .await?;
+    }
+
+    Ok(ranking(session))
+}
+
+fn ranking(session: &GroupSession) -> String {
+    let mut results: Vec<(&String, f64)> = session
+        .participants
+        .values()
+        .map(|participant| (&participant.name, score(&participant.progress)))
+        .collect();
+    if results.is_empty() {
+        return format!("Quiz '{}' is over. Nobody answered.", session.quiz.title());
+    }
+    results.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    let lines: Vec<String> = results
+        .iter()
+        .enumerate()
+        .map(|(place, (name, score))| {
+            format!(
+                "{}. {} — {}/{}",
+                place + 1,
+                name,
+                format_points(*score),
+                format_points(session.quiz.max_score())
+            )
+        })
+        .collect();
+    format!(
+        "🏆 Quiz '{}' is over! Ranking:\n{}",
+        session.quiz.title(),
+        lines.join("\n")
+    )
+}
@@ -0,0 +1,105 @@
+use std::sync::Arc;
+
+use teloxide::{
+    payloads::{AnswerInlineQuerySetters, SendMessageSetters},
+    prelude::Requester,
+    types::{
+        InlineQuery, InlineQueryResult, InlineQueryResultArticle, InputMessageContent,
+        InputMessageContentText, Me, Message,
+    },
+    Bot,
+};
+use tracing::instrument;
+use url::Url;
+
+use crate::{
+    database::{
+        connection::{Attempts, RetreiveQuiz},
+        quiz::Quiz,
+    },
+    keyboard::{action_keyboard, start_quiz_keyboard},
+    runner::offer_quiz,
+    HandlerResult, UserDialogue,
+};
+
+const MAX_RESULTS: i64 = 20;
+
+/// Lists quizes matching `@bot <search>`, each posting a card that deep-links into a private run.
+#[instrument(level = "info", skip(connection, bot, me))]
+pub(crate) async fn search<QuizRetriever: RetreiveQuiz>(
+    bot: Bot,
+    q: InlineQuery,
+    me: Me,
+    connection: Arc<QuizRetriever>,
+) -> HandlerResult {
+    let quizes = connection
+        .search_quizes(q.query.trim(), MAX_RESULTS)
+        .await?;
+
+    let mut results = Vec::with_capacity(quizes.len());
+    for quiz in quizes {
+        let link = quiz_link(&me, &quiz)?;
+        let card = InputMessageContent::Text(InputMessageContentText::new(format!(
+            "📝 {}\n{}",
+            quiz.title(),
+            quiz.description()
+        )));
+        results.push(InlineQueryResult::Article(
+            InlineQueryResultArticle::new(quiz.uuid().to_string(), quiz.title(), card)
+                .description(quiz.description())
+                .reply_markup(start_quiz_keyboard(link)),
+        ));
+    }
+
+    // Quizes change as they are edited, so results are not cached.
+    bot.answer_inline_query(&q.id, results)
+        .cache_time(0)
+        .await?;
+
+    Ok(())
+}
+
+/// Link that opens a private chat with the bot straight into the quiz.
+pub(crate) fn quiz_link(me: &Me, quiz: &Quiz) -> Result<Url, url::ParseError> {
+    Url::parse(&format!(
+        "https://t.me/{}?start={}",
+        me.username(),
+        quiz.code()
+    ))
+}
+
+/// Offers the quiz from a `/start <code>` deep link.
+#[instrument(level = "info", skip(connection, bot, dialogue))]
+pub(crate) async fn start_from_code<Connect: RetreiveQuiz + Attempts>(
+    bot: Bot,
+    msg: Message,
+    dialogue: UserDialogue,
+    payload: String,
+    connection: Arc<Connect>,
+) -> HandlerResult {
+    let quiz_name = connection
+        .retreive_quiz_name_by_code(payload.trim())
+        .await?;
+    offer_linked_quiz(&bot, &msg, &dialogue, quiz_name, connection.as_ref()).await
+}
+
+async fn offer_linked_quiz<Connect: RetreiveQuiz + Attempts>(
+    bot: &Bot,
+    msg: &Message,
+    dialogue: &UserDialogue,
+    quiz_name: Option<String>,
+    connection: &Connect,
+) -> HandlerResult {
+    match quiz_name {
+        Some(quiz_name) => {
+            offer_quiz(bot, msg.chat.id, dialogue, &quiz_name, connection).await?;
+        }
+        None => {
+            bot.send_message(msg.chat.id, "Sorry, this quiz no longer exists.")
+                .reply_markup(action_keyboard(msg.chat.username().unwrap_or_default()))
+                .await?;
+        }
+    }
+
+    Ok(())
+}
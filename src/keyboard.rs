@@ -1,7 +1,11 @@
 use dotenvy::dotenv;
 use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup, KeyboardButton, KeyboardMarkup};
+use url::Url;
 
-use crate::database::quiz::Answer;
+use crate::{
+    database::{quiz::Answer, revision::Revision},
+    state::Listing,
+};
 
 pub(crate) fn yes_no_keyboard() -> KeyboardMarkup {
     let keyboard: Vec<Vec<KeyboardButton>> = vec![vec![
@@ -21,18 +25,155 @@ pub(crate) fn answers_keyboard(answers: &[Answer]) -> InlineKeyboardMarkup {
     InlineKeyboardMarkup::new(keyboard)
 }
 
-pub(crate) fn quizes_keyboard(quizes: &[String]) -> KeyboardMarkup {
-    let keyboard = quizes
-        .into_iter()
-        .map(|quiz| vec![KeyboardButton::new(quiz)]);
+// Callback data of the buttons shown while running a quiz.
+pub(crate) const SKIP_ANSWER: &str = "Skip⏭️";
+pub(crate) const FINISH_QUIZ: &str = "Finish🏁";
+pub(crate) const CONFIRM_FINISH: &str = "Yes, finish✔️";
+pub(crate) const CANCEL_FINISH: &str = "Back to questions↩️";
 
-    KeyboardMarkup::new(keyboard)
+pub(crate) fn running_answers_keyboard(answers: &[Answer]) -> InlineKeyboardMarkup {
+    answers_keyboard(answers).append_row(vec![
+        InlineKeyboardButton::callback(SKIP_ANSWER, SKIP_ANSWER),
+        InlineKeyboardButton::callback(FINISH_QUIZ, FINISH_QUIZ),
+    ])
 }
 
-pub(crate) fn questions_keyboard(questions: &[String]) -> KeyboardMarkup {
-    let keyboard = questions
-        .into_iter()
-        .map(|question| vec![KeyboardButton::new(question)]);
+// Callback data of the buttons shown during a group quiz.
+pub(crate) const GROUP_PREFIX: &str = "group:";
+pub(crate) const NEXT_QUESTION: &str = "group:next";
+
+/// Answers are referred to by position, so presses on closed questions can be told apart.
+pub(crate) fn group_answers_keyboard(
+    question_idx: usize,
+    answers: &[String],
+) -> InlineKeyboardMarkup {
+    let keyboard: Vec<Vec<InlineKeyboardButton>> = answers
+        .iter()
+        .enumerate()
+        .map(|(answer_idx, answer)| {
+            vec![InlineKeyboardButton::callback(
+                answer,
+                format!("{}{}:{}", GROUP_PREFIX, question_idx, answer_idx),
+            )]
+        })
+        .collect();
+
+    InlineKeyboardMarkup::new(keyboard).append_row(vec![InlineKeyboardButton::callback(
+        "Next question➡️",
+        NEXT_QUESTION,
+    )])
+}
+
+// Callback data of the live quiz buttons is `live:<code>:<action>`.
+pub(crate) const LIVE_PREFIX: &str = "live:";
+
+pub(crate) fn live_lobby_keyboard(code: &str) -> InlineKeyboardMarkup {
+    InlineKeyboardMarkup::new(vec![vec![
+        InlineKeyboardButton::callback("Start▶️", format!("{}{}:start", LIVE_PREFIX, code)),
+        InlineKeyboardButton::callback(
+            "Start with timer⏱️",
+            format!("{}{}:timer", LIVE_PREFIX, code),
+        ),
+    ]])
+}
+
+pub(crate) fn live_host_keyboard(code: &str, question_idx: usize) -> InlineKeyboardMarkup {
+    InlineKeyboardMarkup::new(vec![vec![InlineKeyboardButton::callback(
+        "Next question➡️",
+        format!("{}{}:next:{}", LIVE_PREFIX, code, question_idx),
+    )]])
+}
+
+pub(crate) fn live_answers_keyboard(
+    code: &str,
+    question_idx: usize,
+    answers: &[String],
+) -> InlineKeyboardMarkup {
+    let keyboard: Vec<Vec<InlineKeyboardButton>> = answers
+        .iter()
+        .enumerate()
+        .map(|(answer_idx, answer)| {
+            vec![InlineKeyboardButton::callback(
+                answer,
+                format!("{}{}:{}:{}", LIVE_PREFIX, code, question_idx, answer_idx),
+            )]
+        })
+        .collect();
+
+    InlineKeyboardMarkup::new(keyboard)
+}
+
+pub(crate) fn finish_confirmation_keyboard() -> InlineKeyboardMarkup {
+    InlineKeyboardMarkup::new(vec![vec![
+        InlineKeyboardButton::callback(CONFIRM_FINISH, CONFIRM_FINISH),
+        InlineKeyboardButton::callback(CANCEL_FINISH, CANCEL_FINISH),
+    ]])
+}
+
+pub(crate) fn start_quiz_keyboard(link: Url) -> InlineKeyboardMarkup {
+    InlineKeyboardMarkup::new(vec![vec![InlineKeyboardButton::url("Start quiz▶️", link)]])
+}
+
+// Callback data of the listing buttons, followed by the item index or the page number.
+pub(crate) const PICK_PREFIX: &str = "pick:";
+pub(crate) const PAGE_PREFIX: &str = "page:";
+pub(crate) const ALL_TOPICS: &str = "topics:all";
+pub(crate) const ROLL_BACK_PREFIX: &str = "Roll back to ";
+
+pub(crate) fn listing_keyboard(listing: &Listing) -> InlineKeyboardMarkup {
+    let mut keyboard: Vec<Vec<InlineKeyboardButton>> = listing
+        .names
+        .items()
+        .iter()
+        .enumerate()
+        .map(|(idx, name)| {
+            vec![InlineKeyboardButton::callback(
+                name,
+                format!("{}{}", PICK_PREFIX, idx),
+            )]
+        })
+        .collect();
+
+    let mut navigation = vec![];
+    if listing.page > 0 {
+        navigation.push(InlineKeyboardButton::callback(
+            "◀️ Prev",
+            format!("{}{}", PAGE_PREFIX, listing.page - 1),
+        ));
+    }
+    if listing.page + 1 < listing.names.page_count() {
+        navigation.push(InlineKeyboardButton::callback(
+            "Next ▶️",
+            format!("{}{}", PAGE_PREFIX, listing.page + 1),
+        ));
+    }
+    if !navigation.is_empty() {
+        keyboard.push(navigation);
+    }
+
+    InlineKeyboardMarkup::new(keyboard)
+}
+
+/// Topic listing with a way to skip choosing a topic.
+pub(crate) fn topics_keyboard(listing: &Listing) -> InlineKeyboardMarkup {
+    listing_keyboard(listing).append_row(vec![InlineKeyboardButton::callback(
+        "All quizes📚",
+        ALL_TOPICS,
+    )])
+}
+
+pub(crate) fn revisions_keyboard(revisions: &[Revision]) -> KeyboardMarkup {
+    let mut keyboard: Vec<Vec<KeyboardButton>> = revisions
+        .iter()
+        .map(|revision| {
+            vec![KeyboardButton::new(format!(
+                "{}{}",
+                ROLL_BACK_PREFIX,
+                revision.number()
+            ))]
+        })
+        .collect();
+    keyboard.push(vec![KeyboardButton::new("Back")]);
 
     KeyboardMarkup::new(keyboard)
 }
@@ -53,13 +194,22 @@ pub(crate) fn action_keyboard(username: impl Into<String>) -> KeyboardMarkup {
     let mut keyboard = vec![vec![KeyboardButton::new("Take a quiz📝")]];
 
     if username.into() == admin {
-        keyboard.push(vec![KeyboardButton::new("Create a new quiz🏗️")]);
+        keyboard.push(vec![
+            KeyboardButton::new("Create a new quiz🏗️"),
+            KeyboardButton::new("Import quiz📥"),
+        ]);
         keyboard.push(vec![KeyboardButton::new("Edit an existing quiz✏️️")]);
+        keyboard.push(vec![KeyboardButton::new("Trash♻️")]);
     }
 
     KeyboardMarkup::new(keyboard)
 }
 
+/// Adds the entry that continues an unfinished quiz to the `action_keyboard`.
+pub(crate) fn with_resume(keyboard: KeyboardMarkup) -> KeyboardMarkup {
+    keyboard.append_row(vec![KeyboardButton::new("Continue unfinished quiz⏯️")])
+}
+
 pub(crate) fn edit_quiz_keyboard() -> KeyboardMarkup {
     let keyboard = vec![
         vec![
@@ -69,8 +219,34 @@ pub(crate) fn edit_quiz_keyboard() -> KeyboardMarkup {
         vec![
             KeyboardButton::new("Edit question"),
             KeyboardButton::new("Add question"),
+            KeyboardButton::new("Deleted questions♻️"),
+        ],
+        vec![
+            KeyboardButton::new("Availability🗓️"),
+            KeyboardButton::new("Grading🎓"),
+        ],
+        vec![
+            KeyboardButton::new("Delivery📨"),
+            KeyboardButton::new("Schedule push⏰"),
+        ],
+        vec![
+            KeyboardButton::new("Tags🏷️"),
+            KeyboardButton::new("Share link🔗"),
+            KeyboardButton::new("Export to Aiken📤"),
+        ],
+        vec![
+            KeyboardButton::new("Check quiz🔍"),
+            KeyboardButton::new("History🕓"),
+        ],
+        vec![
+            KeyboardButton::new("Publish✅"),
+            KeyboardButton::new("Unpublish"),
+            KeyboardButton::new("Archive🗄️"),
+        ],
+        vec![
+            KeyboardButton::new("Duplicate quiz📄"),
+            KeyboardButton::new("Delete quiz🗑️"),
         ],
-        vec![KeyboardButton::new("Delete quiz🗑️")],
     ];
 
     KeyboardMarkup::new(keyboard)
@@ -82,7 +258,10 @@ pub(crate) fn edit_question_keyboard() -> KeyboardMarkup {
             KeyboardButton::new("Edit text"),
             KeyboardButton::new("Edit answer"),
         ],
-        vec![KeyboardButton::new("Add answer")],
+        vec![
+            KeyboardButton::new("Add answer"),
+            KeyboardButton::new("Edit scoring"),
+        ],
         vec![KeyboardButton::new("Delete question🗑️")],
     ];
 
@@ -100,3 +279,52 @@ pub(crate) fn edit_answer_keyboard() -> KeyboardMarkup {
 
     KeyboardMarkup::new(keyboard)
 }
+
+pub(crate) fn review_keyboard() -> KeyboardMarkup {
+    let keyboard = vec![
+        vec![
+            KeyboardButton::new("Edit text✏️"),
+            KeyboardButton::new("Toggle correct✅"),
+        ],
+        vec![
+            KeyboardButton::new("Move↕️"),
+            KeyboardButton::new("Delete🗑️"),
+        ],
+        vec![
+            KeyboardButton::new("Add question"),
+            KeyboardButton::new("Save quiz💾"),
+        ],
+    ];
+
+    KeyboardMarkup::new(keyboard)
+}
+
+pub(crate) fn push_target_keyboard() -> KeyboardMarkup {
+    let keyboard = vec![vec![KeyboardButton::new("Subscribers")]];
+
+    KeyboardMarkup::new(keyboard)
+}
+
+pub(crate) fn no_limit_keyboard() -> KeyboardMarkup {
+    let keyboard = vec![vec![KeyboardButton::new("No limit")]];
+
+    KeyboardMarkup::new(keyboard)
+}
+
+pub(crate) fn no_pass_mark_keyboard() -> KeyboardMarkup {
+    let keyboard = vec![vec![KeyboardButton::new("No pass mark")]];
+
+    KeyboardMarkup::new(keyboard)
+}
+
+pub(crate) fn no_tags_keyboard() -> KeyboardMarkup {
+    let keyboard = vec![vec![KeyboardButton::new("No tags")]];
+
+    KeyboardMarkup::new(keyboard)
+}
+
+pub(crate) fn no_grades_keyboard() -> KeyboardMarkup {
+    let keyboard = vec![vec![KeyboardButton::new("No grades")]];
+
+    KeyboardMarkup::new(keyboard)
+}
@@ -5,10 +5,19 @@ pub mod commands;
 pub mod constructor;
 pub mod database;
 pub mod editor;
+pub mod formats;
+pub mod group;
+pub mod inline;
 pub mod keyboard;
+pub mod listing;
+pub mod live;
+pub mod navigation;
+pub mod poll;
 pub mod runner;
+pub mod scheduler;
 pub mod schema;
 pub mod state;
+pub mod study;
 
 type UserDialogue = Dialogue<QuizState, InMemStorage<QuizState>>;
 type HandlerResult = Result<(), Box<dyn std::error::Error + Send + Sync + 'static>>;
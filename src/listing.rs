@@ -0,0 +1,349 @@
+use std::{error::Error, sync::Arc};
+
+use chrono::Utc;
+use teloxide::{
+    dispatching::dialogue::GetChatId,
+    payloads::{AnswerCallbackQuerySetters, EditMessageTextSetters, SendMessageSetters},
+    prelude::Requester,
+    types::{CallbackQuery, ChatId, InlineKeyboardMarkup, Message, ReplyMarkup},
+    Bot,
+};
+use tracing::instrument;
+
+use crate::{
+    database::{
+        connection::{Attempts, RetreiveQuestion, RetreiveQuiz, Revisions, Subscription, Trash},
+        quiz::Status,
+    },
+    editor,
+    keyboard::{listing_keyboard, topics_keyboard, ALL_TOPICS, PAGE_PREFIX, PICK_PREFIX},
+    runner,
+    state::{Listing, QuizState},
+    study, HandlerResult, UserDialogue,
+};
+
+/// Whether the state chooses from a listing, so that texts are searches.
+pub(crate) fn is_listing(state: &QuizState) -> bool {
+    listing(state).is_some()
+}
+
+fn listing(state: &QuizState) -> Option<&Listing> {
+    match state {
+        QuizState::TopicSelection { listing }
+        | QuizState::Selection { listing, .. }
+        | QuizState::StartSelect { listing }
+        | QuizState::SubscribeSelection { listing }
+        | QuizState::SelectQuestion { listing, .. }
+        | QuizState::TrashSelection { listing }
+        | QuizState::QuestionTrashSelection { listing, .. } => Some(listing),
+        _ => None,
+    }
+}
+
+fn with_listing(state: QuizState, listing: Listing) -> QuizState {
+    match state {
+        QuizState::TopicSelection { .. } => QuizState::TopicSelection { listing },
+        QuizState::Selection { tag, .. } => QuizState::Selection { tag, listing },
+        QuizState::StartSelect { .. } => QuizState::StartSelect { listing },
+        QuizState::SubscribeSelection { .. } => QuizState::SubscribeSelection { listing },
+        QuizState::SelectQuestion { quiz_name, .. } => {
+            QuizState::SelectQuestion { quiz_name, listing }
+        }
+        QuizState::TrashSelection { .. } => QuizState::TrashSelection { listing },
+        QuizState::QuestionTrashSelection { quiz_name, .. } => {
+            QuizState::QuestionTrashSelection { quiz_name, listing }
+        }
+        state => state,
+    }
+}
+
+// Loads the page of names the state chooses from.
+async fn fetch<Connect: RetreiveQuiz + RetreiveQuestion + Trash>(
+    connection: &Connect,
+    state: &QuizState,
+    query: String,
+    page: i64,
+) -> Result<Listing, Box<dyn Error + Send + Sync>> {
+    let names = match state {
+        QuizState::TopicSelection { .. } => {
+            connection
+                .retreive_tags_page(&query, Some(Utc::now()), page)
+                .await?
+        }
+        QuizState::Selection { tag, .. } => {
+            connection
+                .retreive_quiz_names_page(
+                    &query,
+                    tag.as_deref(),
+                    Some(Status::Published),
+                    Some(Utc::now()),
+                    page,
+                )
+                .await?
+        }
+        QuizState::SelectQuestion { quiz_name, .. } => {
+            connection
+                .retreive_question_names_page(quiz_name, &query, page)
+                .await?
+        }
+        QuizState::TrashSelection { .. } => {
+            connection
+                .retreive_trashed_quiz_names_page(&query, page)
+                .await?
+        }
+        QuizState::QuestionTrashSelection { quiz_name, .. } => {
+            connection
+                .retreive_trashed_question_names_page(quiz_name, &query, page)
+                .await?
+        }
+        QuizState::SubscribeSelection { .. } => {
+            connection
+                .retreive_quiz_names_page(&query, None, Some(Status::Published), None, page)
+                .await?
+        }
+        _ => {
+            connection
+                .retreive_quiz_names_page(&query, None, None, None, page)
+                .await?
+        }
+    };
+
+    Ok(Listing {
+        query,
+        page,
+        names,
+        message_id: None,
+    })
+}
+
+fn markup(state: &QuizState, listing: &Listing) -> InlineKeyboardMarkup {
+    match state {
+        QuizState::TopicSelection { .. } => topics_keyboard(listing),
+        _ => listing_keyboard(listing),
+    }
+}
+
+fn listing_text(listing: &Listing) -> String {
+    if listing.names.total() == 0 {
+        return format!(
+            "Nothing matches '{}'. Send another text to search.",
+            listing.query
+        );
+    }
+
+    let search = if listing.query.is_empty() {
+        String::new()
+    } else {
+        format!("Search: '{}'. ", listing.query)
+    };
+    format!(
+        "{}Page {} of {}. Choose below or send a text to search.",
+        search,
+        listing.page + 1,
+        listing.names.page_count()
+    )
+}
+
+/// Shows the first page of the listing `state` chooses from and moves to it.
+/// Sends `empty` instead when there is nothing to choose from at all.
+pub(crate) async fn open<Connect: RetreiveQuiz + RetreiveQuestion + Trash>(
+    bot: &Bot,
+    chat_id: ChatId,
+    dialogue: &UserDialogue,
+    state: QuizState,
+    prompt: &str,
+    empty: &str,
+    connection: &Connect,
+) -> HandlerResult {
+    let listing = fetch(connection, &state, String::new(), 0).await?;
+    if listing.names.total() == 0 {
+        bot.send_message(chat_id, empty).await?;
+        return Ok(());
+    }
+
+    show(bot, chat_id, dialogue, state, prompt, listing).await
+}
+
+/// Lets the user narrow quizes down by topic first, unless no open quiz has tags.
+pub(crate) async fn open_topics<Connect: RetreiveQuiz + RetreiveQuestion + Trash>(
+    bot: &Bot,
+    chat_id: ChatId,
+    dialogue: &UserDialogue,
+    connection: &Connect,
+) -> HandlerResult {
+    let state = QuizState::TopicSelection {
+        listing: Listing::default(),
+    };
+    let listing = fetch(connection, &state, String::new(), 0).await?;
+    if listing.names.total() == 0 {
+        return open_quizes(bot, chat_id, dialogue, None, connection).await;
+    }
+
+    show(
+        bot,
+        chat_id,
+        dialogue,
+        state,
+        "Please, choose a topic:",
+        listing,
+    )
+    .await
+}
+
+async fn open_quizes<Connect: RetreiveQuiz + RetreiveQuestion + Trash>(
+    bot: &Bot,
+    chat_id: ChatId,
+    dialogue: &UserDialogue,
+    tag: Option<String>,
+    connection: &Connect,
+) -> HandlerResult {
+    let prompt = match &tag {
+        Some(tag) => format!("Please, choose available quiz about '{}':", tag),
+        None => "Please, choose available quiz:".to_owned(),
+    };
+    let state = QuizState::Selection {
+        tag,
+        listing: Listing::default(),
+    };
+    open(
+        bot,
+        chat_id,
+        dialogue,
+        state,
+        &prompt,
+        "No available quizes.",
+        connection,
+    )
+    .await
+}
+
+async fn show(
+    bot: &Bot,
+    chat_id: ChatId,
+    dialogue: &UserDialogue,
+    state: QuizState,
+    prompt: &str,
+    mut listing: Listing,
+) -> HandlerResult {
+    bot.send_message(chat_id, prompt)
+        .reply_markup(ReplyMarkup::kb_remove())
+        .await?;
+    let message = bot
+        .send_message(chat_id, listing_text(&listing))
+        .reply_markup(markup(&state, &listing))
+        .await?;
+    listing.message_id = Some(message.id);
+    dialogue.update(with_listing(state, listing)).await?;
+
+    Ok(())
+}
+
+#[instrument(level = "info", skip(connection, bot, dialogue))]
+pub(crate) async fn search<Connect: RetreiveQuiz + RetreiveQuestion + Trash>(
+    bot: Bot,
+    msg: Message,
+    dialogue: UserDialogue,
+    connection: Arc<Connect>,
+) -> HandlerResult {
+    let Some(query) = msg.text() else {
+        bot.send_message(msg.chat.id, "Please, send a text to search.")
+            .await?;
+        return Ok(());
+    };
+
+    let state = dialogue.get_or_default().await?;
+    let mut listing = fetch(connection.as_ref(), &state, query.trim().to_owned(), 0).await?;
+    let message = bot
+        .send_message(msg.chat.id, listing_text(&listing))
+        .reply_markup(markup(&state, &listing))
+        .await?;
+    listing.message_id = Some(message.id);
+    dialogue.update(with_listing(state, listing)).await?;
+
+    Ok(())
+}
+
+#[instrument(level = "info", skip(connection, bot, dialogue))]
+pub(crate) async fn browse<
+    Connect: RetreiveQuiz + RetreiveQuestion + Attempts + Subscription + Revisions + Trash,
+>(
+    bot: Bot,
+    q: CallbackQuery,
+    dialogue: UserDialogue,
+    connection: Arc<Connect>,
+) -> HandlerResult {
+    let (Some(data), Some(message), Some(chat_id)) = (&q.data, &q.message, q.chat_id()) else {
+        bot.answer_callback_query(&q.id).await?;
+        return Ok(());
+    };
+    let state = dialogue.get_or_default().await?;
+    // Indexes and pages only make sense for the listing the dialogue holds now.
+    let Some(current) = listing(&state).filter(|current| current.message_id == Some(message.id()))
+    else {
+        bot.answer_callback_query(&q.id)
+            .text("This list is out of date, please use the latest one.")
+            .await?;
+        return Ok(());
+    };
+    bot.answer_callback_query(&q.id).await?;
+
+    if let Some(page) = data
+        .strip_prefix(PAGE_PREFIX)
+        .and_then(|page| page.parse::<i64>().ok())
+    {
+        let mut listing = fetch(connection.as_ref(), &state, current.query.clone(), page).await?;
+        listing.message_id = Some(message.id());
+        bot.edit_message_text(chat_id, message.id(), listing_text(&listing))
+            .reply_markup(markup(&state, &listing))
+            .await?;
+        dialogue.update(with_listing(state, listing)).await?;
+        return Ok(());
+    }
+
+    if data == ALL_TOPICS {
+        bot.edit_message_text(chat_id, message.id(), "Chosen: all quizes")
+            .await?;
+        return open_quizes(&bot, chat_id, &dialogue, None, connection.as_ref()).await;
+    }
+
+    let Some(name) = data
+        .strip_prefix(PICK_PREFIX)
+        .and_then(|idx| idx.parse::<usize>().ok())
+        .and_then(|idx| current.names.items().get(idx))
+        .cloned()
+    else {
+        return Ok(());
+    };
+    bot.edit_message_text(chat_id, message.id(), format!("Chosen: {}", name))
+        .await?;
+
+    let connection = connection.as_ref();
+    match state {
+        QuizState::TopicSelection { .. } => {
+            open_quizes(&bot, chat_id, &dialogue, Some(name), connection).await?;
+        }
+        QuizState::Selection { .. } => {
+            runner::offer_quiz(&bot, chat_id, &dialogue, &name, connection).await?;
+        }
+        QuizState::StartSelect { .. } => {
+            editor::open_quiz(&bot, chat_id, &dialogue, &name, connection).await?;
+        }
+        QuizState::SelectQuestion { quiz_name, .. } => {
+            editor::open_question(&bot, chat_id, &dialogue, quiz_name, &name, connection).await?;
+        }
+        QuizState::SubscribeSelection { .. } => {
+            let username = q.from.username.as_deref().unwrap_or_default();
+            study::toggle(&bot, chat_id, username, &dialogue, &name, connection).await?;
+        }
+        QuizState::TrashSelection { .. } => {
+            editor::restore_quiz(&bot, chat_id, &dialogue, &name, connection).await?;
+        }
+        QuizState::QuestionTrashSelection { quiz_name, .. } => {
+            editor::restore_question(&bot, chat_id, &dialogue, quiz_name, &name, connection)
+                .await?;
+        }
+        _ => (),
+    }
+
+    Ok(())
+}
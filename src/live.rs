@@ -0,0 +1,514 @@
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use chrono::Utc;
+use teloxide::{
+    payloads::{AnswerCallbackQuerySetters, SendMessageSetters},
+    prelude::Requester,
+    types::{CallbackQuery, ChatId, InlineKeyboardMarkup, Message},
+    Bot,
+};
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::{
+    database::{connection::RetreiveQuiz, quiz::Quiz},
+    keyboard::{live_answers_keyboard, live_host_keyboard, live_lobby_keyboard, LIVE_PREFIX},
+    runner::{next_playable, question_text},
+    HandlerResult,
+};
+
+/// Payload of the deep link that joins a lobby, followed by the lobby code.
+pub(crate) const JOIN_PAYLOAD: &str = "join_";
+
+/// Time given to each question when the host starts with a timer.
+const QUESTION_TIME: Duration = Duration::from_secs(20);
+/// Time after which a correct answer earns only half of the points when there is no timer.
+const SPEED_WINDOW: Duration = Duration::from_secs(30);
+/// Time after which a lobby that was never started is closed.
+const LOBBY_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+const POINTS_PER_QUESTION: f64 = 1000.0;
+const CODE_LEN: usize = 6;
+
+#[derive(Debug)]
+struct Player {
+    name: String,
+    score: f64,
+    answered: bool,
+}
+
+/// Quiz hosted from one chat and played from the private chats of the players.
+///
+/// Players are ranked by speed points only; live sessions are not recorded as attempts.
+#[derive(Debug)]
+pub(crate) struct LiveSession {
+    quiz: Quiz,
+    host: ChatId,
+    players: HashMap<ChatId, Player>,
+    // `None` while the lobby is open.
+    curr_idx: Option<usize>,
+    round_started_at: Instant,
+    timer: Option<Duration>,
+    correct: usize,
+}
+
+/// Open lobbies and running live sessions keyed by their join code.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct LiveSessions(Arc<Mutex<HashMap<String, LiveSession>>>);
+
+// Messages of a round, collected while holding the lock and sent after releasing it.
+struct Round {
+    host: ChatId,
+    players: Vec<ChatId>,
+    text: String,
+    answers: Vec<String>,
+    idx: usize,
+    timer: Option<Duration>,
+}
+
+// Stores the session under a fresh code, drawing again if that code is taken.
+fn open_lobby(sessions: &mut HashMap<String, LiveSession>, session: LiveSession) -> String {
+    loop {
+        let code = Uuid::new_v4().simple().to_string()[..CODE_LEN].to_uppercase();
+        if let Entry::Vacant(entry) = sessions.entry(code.clone()) {
+            entry.insert(session);
+            return code;
+        }
+    }
+}
+
+#[instrument(level = "info", skip(connection, bot, sessions))]
+pub(crate) async fn host<QuizRetriever: RetreiveQuiz>(
+    bot: Bot,
+    msg: Message,
+    quiz_name: String,
+    sessions: LiveSessions,
+    connection: Arc<QuizRetriever>,
+) -> HandlerResult {
+    if !msg.chat.is_private() {
+        bot.send_message(
+            msg.chat.id,
+            "Please host live quizes from a private chat with the bot.",
+        )
+        .await?;
+        return Ok(());
+    }
+    let quiz_name = quiz_name.trim();
+    if quiz_name.is_empty() {
+        bot.send_message(msg.chat.id, "Usage: /host <quiz name>")
+            .await?;
+        return Ok(());
+    }
+    let Some(quiz) = connection.retreive_quiz(quiz_name).await? else {
+        bot.send_message(msg.chat.id, format!("Quiz '{}' not found.", quiz_name))
+            .await?;
+        return Ok(());
+    };
+    // Live sessions record no attempts, so only the availability window applies.
+    if let Err(reason) = quiz.check(Utc::now(), 0) {
+        bot.send_message(
+            msg.chat.id,
+            format!("Sorry, you can't host '{}'. {}", quiz.title(), reason),
+        )
+        .await?;
+        return Ok(());
+    }
+    if next_playable(&quiz, 0).is_none() {
+        bot.send_message(msg.chat.id, "Sorry, no questions for that quiz available.")
+            .await?;
+        return Ok(());
+    }
+
+    let title = quiz.title().clone();
+    let session = LiveSession {
+        quiz,
+        host: msg.chat.id,
+        players: HashMap::new(),
+        curr_idx: None,
+        round_started_at: Instant::now(),
+        timer: None,
+        correct: 0,
+    };
+    let code = open_lobby(&mut sessions.0.lock().unwrap(), session);
+
+    let me = bot.get_me().await?;
+    bot.send_message(
+        msg.chat.id,
+        format!(
+            "Lobby for '{}' is open!\nPlayers join with /join {} or via https://t.me/{}?start={}{}\n\nStart when everyone is in.",
+            title,
+            code,
+            me.username(),
+            JOIN_PAYLOAD,
+            code
+        ),
+    )
+    .reply_markup(live_lobby_keyboard(&code))
+    .await?;
+    expire_lobby(&bot, &sessions, code);
+
+    Ok(())
+}
+
+// Closes the lobby once `LOBBY_TIMEOUT` passes without the host starting the quiz.
+fn expire_lobby(bot: &Bot, sessions: &LiveSessions, code: String) {
+    let (bot, sessions) = (bot.clone(), sessions.clone());
+    tokio::spawn(async move {
+        tokio::time::sleep(LOBBY_TIMEOUT).await;
+        let expired = {
+            let mut sessions = sessions.0.lock().unwrap();
+            match sessions.get(&code) {
+                Some(session) if session.curr_idx.is_none() => sessions.remove(&code),
+                _ => None,
+            }
+        };
+        if let Some(session) = expired {
+            let mut recipients: Vec<ChatId> = session.players.keys().copied().collect();
+            recipients.push(session.host);
+            let text = format!(
+                "The lobby of '{}' was closed because the quiz wasn't started in time.",
+                session.quiz.title()
+            );
+            broadcast(&bot, &recipients, &text, None).await;
+        }
+    });
+}
+
+#[instrument(level = "info", skip(bot, sessions))]
+pub(crate) async fn join(
+    bot: Bot,
+    msg: Message,
+    code: String,
+    sessions: LiveSessions,
+) -> HandlerResult {
+    let code = code.trim().to_uppercase();
+    let name = msg
+        .from
+        .as_ref()
+        .map(|user| user.full_name())
+        .unwrap_or_default();
+
+    let joined = {
+        let mut sessions = sessions.0.lock().unwrap();
+        sessions.get_mut(&code).map(|session| {
+            // Players joining mid-round wait for the next question.
+            session.players.entry(msg.chat.id).or_insert(Player {
+                name: name.clone(),
+                score: 0.0,
+                answered: true,
+            });
+            (
+                session.host,
+                session.quiz.title().clone(),
+                session.players.len(),
+            )
+        })
+    };
+
+    match joined {
+        Some((host, title, players)) => {
+            bot.send_message(
+                msg.chat.id,
+                format!(
+                    "You joined '{}'! Questions will appear here once the host moves on.",
+                    title
+                ),
+            )
+            .await?;
+            bot.send_message(host, format!("{} joined. Players: {}.", name, players))
+                .await?;
+        }
+        None => {
+            bot.send_message(msg.chat.id, format!("No live quiz with code '{}'.", code))
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Joins the lobby from a `/start join_<code>` deep link.
+pub(crate) async fn join_link(
+    bot: Bot,
+    msg: Message,
+    payload: String,
+    sessions: LiveSessions,
+) -> HandlerResult {
+    let code = payload
+        .strip_prefix(JOIN_PAYLOAD)
+        .unwrap_or_default()
+        .to_owned();
+    join(bot, msg, code, sessions).await
+}
+
+#[instrument(level = "info", skip(bot, sessions))]
+pub(crate) async fn take_callback(
+    bot: Bot,
+    q: CallbackQuery,
+    sessions: LiveSessions,
+) -> HandlerResult {
+    let Some(data) = q
+        .data
+        .as_deref()
+        .and_then(|data| data.strip_prefix(LIVE_PREFIX))
+    else {
+        return Ok(());
+    };
+    let mut parts = data.split(':');
+    let (Some(code), Some(action)) = (parts.next(), parts.next()) else {
+        return Ok(());
+    };
+    let chat_id = ChatId(q.from.id.0 as i64);
+
+    match (action, parts.next()) {
+        ("start", None) | ("timer", None) => {
+            let timer = (action == "timer").then_some(QUESTION_TIME);
+            let round = {
+                let mut sessions = sessions.0.lock().unwrap();
+                match sessions.get_mut(code) {
+                    Some(session) if session.host == chat_id && session.curr_idx.is_none() => {
+                        session.timer = timer;
+                        next_playable(&session.quiz, 0).map(|idx| open_round(session, idx))
+                    }
+                    _ => None,
+                }
+            };
+            bot.answer_callback_query(&q.id).await?;
+            if let Some(round) = round {
+                if let Some(message) = &q.message {
+                    bot.edit_message_reply_markup(chat_id, message.id()).await?;
+                }
+                start_round(&bot, &sessions, code, round).await;
+            }
+        }
+        ("next", Some(idx)) => {
+            let is_host = sessions
+                .0
+                .lock()
+                .unwrap()
+                .get(code)
+                .is_some_and(|session| session.host == chat_id);
+            if !is_host {
+                bot.answer_callback_query(&q.id)
+                    .text("Only the host can move on.")
+                    .await?;
+                return Ok(());
+            }
+            bot.answer_callback_query(&q.id).await?;
+            if let (Ok(idx), Some(message)) = (idx.parse::<usize>(), &q.message) {
+                bot.edit_message_reply_markup(chat_id, message.id()).await?;
+                if let Some(round) = close_round(&bot, &sessions, code, idx).await {
+                    // A timer of the closed round stops by itself, so the new round gets its own.
+                    start_round(&bot, &sessions, code, round).await;
+                }
+            }
+        }
+        (question_idx, Some(answer_idx)) => {
+            let (Ok(question_idx), Ok(answer_idx)) =
+                (question_idx.parse::<usize>(), answer_idx.parse::<usize>())
+            else {
+                return Ok(());
+            };
+            let reply = take_answer(&sessions, code, chat_id, question_idx, answer_idx);
+            bot.answer_callback_query(&q.id).text(reply).await?;
+            if let Some(message) = &q.message {
+                bot.edit_message_reply_markup(chat_id, message.id()).await?;
+            }
+        }
+        _ => (),
+    }
+
+    Ok(())
+}
+
+// Faster correct answers earn more: from the full points down to half of them.
+fn take_answer(
+    sessions: &LiveSessions,
+    code: &str,
+    chat_id: ChatId,
+    question_idx: usize,
+    answer_idx: usize,
+) -> String {
+    let mut sessions = sessions.0.lock().unwrap();
+    let Some(session) = sessions.get_mut(code) else {
+        return "This live quiz is over.".to_owned();
+    };
+    if session.curr_idx != Some(question_idx) {
+        return "Too late, the question is closed.".to_owned();
+    }
+    let Some(player) = session.players.get_mut(&chat_id) else {
+        return "You are not in this live quiz.".to_owned();
+    };
+    if player.answered {
+        return "You have already answered.".to_owned();
+    }
+    player.answered = true;
+
+    let question = &session.quiz.questions()[question_idx];
+    match question.answers().get(answer_idx) {
+        Some(answer) if answer.is_correct() => {
+            let window = session.timer.unwrap_or(SPEED_WINDOW);
+            let elapsed = session.round_started_at.elapsed().as_secs_f64() / window.as_secs_f64();
+            let points =
+                (POINTS_PER_QUESTION * question.points() * (1.0 - elapsed.min(1.0) / 2.0)).round();
+            player.score += points;
+            session.correct += 1;
+            format!("Correct! +{} points", points)
+        }
+        _ => "Wrong answer.".to_owned(),
+    }
+}
+
+fn open_round(session: &mut LiveSession, idx: usize) -> Round {
+    session.curr_idx = Some(idx);
+    session.round_started_at = Instant::now();
+    session.correct = 0;
+    for player in session.players.values_mut() {
+        player.answered = false;
+    }
+
+    Round {
+        host: session.host,
+        players: session.players.keys().copied().collect(),
+        text: question_text(&session.quiz, idx),
+        answers: session.quiz.questions()[idx]
+            .answers()
+            .iter()
+            .map(|answer| answer.text())
+            .collect(),
+        idx,
+        timer: session.timer,
+    }
+}
+
+// Sends the question and, with a timer, keeps closing rounds until the host
+// moves on first or the session ends.
+async fn start_round(bot: &Bot, sessions: &LiveSessions, code: &str, round: Round) {
+    let (idx, timer) = (round.idx, round.timer);
+    send_round(bot, code, round).await;
+
+    if let Some(timer) = timer {
+        let (bot, sessions, code) = (bot.clone(), sessions.clone(), code.to_owned());
+        tokio::spawn(async move {
+            let mut idx = idx;
+            loop {
+                tokio::time::sleep(timer).await;
+                match close_round(&bot, &sessions, &code, idx).await {
+                    Some(round) => {
+                        idx = round.idx;
+                        send_round(&bot, &code, round).await;
+                    }
+                    None => break,
+                }
+            }
+        });
+    }
+}
+
+async fn send_round(bot: &Bot, code: &str, round: Round) {
+    let text = match round.timer {
+        Some(timer) => format!("{}\n⏱️ {} seconds", round.text, timer.as_secs()),
+        None => round.text,
+    };
+    broadcast(
+        bot,
+        &round.players,
+        &text,
+        Some(live_answers_keyboard(code, round.idx, &round.answers)),
+    )
+    .await;
+    broadcast(
+        bot,
+        &[round.host],
+        &text,
+        Some(live_host_keyboard(code, round.idx)),
+    )
+    .await;
+}
+
+// Broadcasts the results of the round and opens the next one, if any, or ends the session.
+// Does nothing when the round was already closed, e.g. by the host before the timer.
+async fn close_round(bot: &Bot, sessions: &LiveSessions, code: &str, idx: usize) -> Option<Round> {
+    let (recipients, summary, next_round) = {
+        let mut sessions = sessions.0.lock().unwrap();
+        let session = sessions.get_mut(code)?;
+        if session.curr_idx != Some(idx) {
+            return None;
+        }
+
+        let question = &session.quiz.questions()[idx];
+        let correct_answers: Vec<String> = question
+            .answers()
+            .iter()
+            .filter(|answer| answer.is_correct())
+            .map(|answer| answer.text())
+            .collect();
+        let mut recipients: Vec<ChatId> = session.players.keys().copied().collect();
+        recipients.push(session.host);
+
+        match next_playable(&session.quiz, idx + 1) {
+            Some(next_idx) => {
+                let summary = format!(
+                    "Correct: {}\n✅ {} of {} answered correctly.\n\n📊 Standings:\n{}",
+                    correct_answers.join(", "),
+                    session.correct,
+                    session.players.len(),
+                    standings(session)
+                );
+                (recipients, summary, Some(open_round(session, next_idx)))
+            }
+            None => {
+                let summary = format!(
+                    "Correct: {}\n✅ {} of {} answered correctly.\n\n🏆 Final standings of '{}':\n{}",
+                    correct_answers.join(", "),
+                    session.correct,
+                    session.players.len(),
+                    session.quiz.title(),
+                    standings(session)
+                );
+                sessions.remove(code);
+                (recipients, summary, None)
+            }
+        }
+    };
+
+    broadcast(bot, &recipients, &summary, None).await;
+    next_round
+}
+
+fn standings(session: &LiveSession) -> String {
+    let mut players: Vec<&Player> = session.players.values().collect();
+    if players.is_empty() {
+        return "No players.".to_owned();
+    }
+    players.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+    players
+        .iter()
+        .enumerate()
+        .map(|(place, player)| format!("{}. {} — {}", place + 1, player.name, player.score))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+// A single unreachable chat (e.g. the player blocked the bot) must not hold back the others.
+async fn broadcast(
+    bot: &Bot,
+    chats: &[ChatId],
+    text: &str,
+    keyboard: Option<InlineKeyboardMarkup>,
+) {
+    for chat_id in chats {
+        let request = bot.send_message(*chat_id, text);
+        let result = match &keyboard {
+            Some(keyboard) => request.reply_markup(keyboard.clone()).await,
+            None => request.await,
+        };
+        if let Err(e) = result {
+            tracing::warn!("Failed to send to chat {}: {}", chat_id, e);
+        }
+    }
+}
@@ -1,7 +1,10 @@
 use commands::{cancel, help, start};
 use database::connection::{Connection, RetreiveQuiz};
 use dotenvy::dotenv;
-use keyboard::quizes_keyboard;
+use group::GroupSessions;
+use live::LiveSessions;
+use poll::PollRegistry;
+use scheduler::{Scheduler, SystemClock};
 use state::QuizState;
 use std::error::Error;
 use std::net::SocketAddr;
@@ -52,8 +55,16 @@ async fn main() {
         })
         .ok();
 
+    tokio::spawn(Scheduler::new(SystemClock, connection.clone(), bot.clone()).run());
+
     let mut dispatcher = Dispatcher::builder(bot.clone(), schema())
-        .dependencies(dptree::deps![InMemStorage::<QuizState>::new(), connection])
+        .dependencies(dptree::deps![
+            InMemStorage::<QuizState>::new(),
+            GroupSessions::default(),
+            LiveSessions::default(),
+            PollRegistry::default(),
+            connection
+        ])
         .enable_ctrlc_handler()
         .build();
 
@@ -0,0 +1,373 @@
+use std::sync::Arc;
+
+use teloxide::{
+    payloads::SendMessageSetters,
+    prelude::Requester,
+    types::{ChatId, Message, ReplyMarkup},
+    Bot,
+};
+use tracing::instrument;
+
+use crate::{
+    constructor,
+    database::{
+        connection::{RetreiveQuestion, RetreiveQuiz},
+        quiz::Answer,
+    },
+    keyboard::{
+        action_keyboard, edit_answer_keyboard, edit_question_keyboard, edit_quiz_keyboard,
+        no_tags_keyboard, yes_no_keyboard,
+    },
+    state::{QuizData, QuizState},
+    HandlerResult, UserDialogue,
+};
+
+/// Steps one level up: answer → question → quiz → main menu in the editor,
+/// and to the previous step in the constructor.
+#[instrument(level = "info", skip(connection, bot, dialogue))]
+pub(crate) async fn back<Connect: RetreiveQuiz + RetreiveQuestion>(
+    bot: Bot,
+    msg: Message,
+    dialogue: UserDialogue,
+    connection: Arc<Connect>,
+) -> HandlerResult {
+    let chat_id = msg.chat.id;
+    let username = msg.chat.username().unwrap_or_default();
+    let connection = connection.as_ref();
+
+    match dialogue.get_or_default().await? {
+        // Editor.
+        QuizState::HandleQuiz { .. }
+        | QuizState::StartSelect { .. }
+        | QuizState::TrashSelection { .. } => {
+            to_menu(&bot, chat_id, username, &dialogue).await?;
+        }
+        QuizState::HandleQuestion { quiz_name, .. }
+        | QuizState::EditName { quiz_name }
+        | QuizState::EditDescription { quiz_name }
+        | QuizState::EditTags { quiz_name }
+        | QuizState::DeleteQuiz { quiz_name }
+        | QuizState::SelectRevision { quiz_name }
+        | QuizState::SelectQuestion { quiz_name, .. }
+        | QuizState::QuestionTrashSelection { quiz_name, .. }
+        | QuizState::AddQuestion { quiz_name }
+        | QuizState::AddQuestionReceiveAnswer { quiz_name, .. }
+        | QuizState::AddQuestionCorectness { quiz_name, .. }
+        | QuizState::AddQuestionReceiveAnotherAnswer { quiz_name, .. }
+        | QuizState::EditOpensAt { quiz_name }
+        | QuizState::EditClosesAt { quiz_name, .. }
+        | QuizState::EditMaxAttempts { quiz_name, .. }
+        | QuizState::EditPassMark { quiz_name }
+        | QuizState::EditGradeBands { quiz_name, .. }
+        | QuizState::SchedulePushTime { quiz_name }
+        | QuizState::SchedulePushTarget { quiz_name, .. } => {
+            to_quiz(&bot, chat_id, &dialogue, quiz_name, connection).await?;
+        }
+        QuizState::HandleAnswer {
+            quiz_name,
+            question_name,
+            ..
+        }
+        | QuizState::EditQuestionText {
+            quiz_name,
+            question_name,
+        }
+        | QuizState::DeleteQuestion {
+            quiz_name,
+            question_name,
+        }
+        | QuizState::EditQuestionScoring {
+            quiz_name,
+            question_name,
+        }
+        | QuizState::AddAnswer {
+            quiz_name,
+            question_name,
+        }
+        | QuizState::AddAnswerCorrectness {
+            quiz_name,
+            question_name,
+            ..
+        }
+        | QuizState::SelectAnswer {
+            quiz_name,
+            question_name,
+        } => {
+            to_question(
+                &bot,
+                chat_id,
+                &dialogue,
+                quiz_name,
+                question_name,
+                connection,
+            )
+            .await?;
+        }
+        QuizState::EditAnswerText {
+            quiz_name,
+            question_name,
+            answer_name,
+        }
+        | QuizState::EditCorectness {
+            quiz_name,
+            question_name,
+            answer_name,
+        } => {
+            bot.send_message(chat_id, format!("Answer: {}", answer_name))
+                .reply_markup(edit_answer_keyboard())
+                .await?;
+            dialogue
+                .update(QuizState::HandleAnswer {
+                    quiz_name,
+                    question_name,
+                    answer_name,
+                })
+                .await?;
+        }
+
+        // Constructor.
+        QuizState::ReceiveQuizName | QuizState::ImportQuiz => {
+            to_menu(&bot, chat_id, username, &dialogue).await?;
+        }
+        QuizState::ReceiveQuizDescription { .. } => {
+            bot.send_message(chat_id, "What's the title of the new quiz?")
+                .reply_markup(ReplyMarkup::kb_remove())
+                .await?;
+            dialogue.update(QuizState::ReceiveQuizName).await?;
+        }
+        QuizState::ReceiveQuizTags { quiz_info } => {
+            bot.send_message(chat_id, "What is new quiz about?")
+                .reply_markup(ReplyMarkup::kb_remove())
+                .await?;
+            dialogue
+                .update(QuizState::ReceiveQuizDescription {
+                    quiz_name: quiz_info.quiz_name,
+                })
+                .await?;
+        }
+        QuizState::ReceiveQuizAuthor { quiz_info }
+        | QuizState::ReceiveAddAnotherQuestion { quiz_info }
+        | QuizState::ReviewQuiz { quiz_info } => {
+            to_last_question(&bot, chat_id, &dialogue, quiz_info).await?;
+        }
+        QuizState::ReceiveNewQuestion { quiz_info } => {
+            bot.send_message(chat_id, "Do you want to add a question?(Yes/No)")
+                .reply_markup(yes_no_keyboard())
+                .await?;
+            dialogue
+                .update(QuizState::ReceiveQuizAuthor { quiz_info })
+                .await?;
+        }
+        QuizState::ReceiveNewAnswer {
+            quiz_info,
+            new_question,
+            answers,
+        } => {
+            if answers.is_empty() {
+                bot.send_message(chat_id, "Please enter a question.")
+                    .reply_markup(ReplyMarkup::kb_remove())
+                    .await?;
+                dialogue
+                    .update(QuizState::ReceiveNewQuestion { quiz_info })
+                    .await?;
+            } else {
+                to_another_answer(&bot, chat_id, &dialogue, quiz_info, new_question, answers)
+                    .await?;
+            }
+        }
+        QuizState::ReceiveAnswerIsCorrect {
+            quiz_info,
+            new_question,
+            answers,
+            ..
+        } => {
+            bot.send_message(chat_id, "What's the answer to your question?")
+                .reply_markup(ReplyMarkup::kb_remove())
+                .await?;
+            dialogue
+                .update(QuizState::ReceiveNewAnswer {
+                    quiz_info,
+                    new_question,
+                    answers,
+                })
+                .await?;
+        }
+        QuizState::ReceiveAddAnotherAnswer {
+            quiz_info,
+            new_question,
+            mut answers,
+        } => match answers.pop() {
+            Some(answer) => {
+                bot.send_message(
+                    chat_id,
+                    format!("Is answer '{}' correct?(Yes/No)", answer.text()),
+                )
+                .reply_markup(yes_no_keyboard())
+                .await?;
+                dialogue
+                    .update(QuizState::ReceiveAnswerIsCorrect {
+                        quiz_info,
+                        new_question,
+                        answers,
+                        new_answer: answer.text(),
+                    })
+                    .await?;
+            }
+            None => {
+                bot.send_message(chat_id, "What's the answer to your question?")
+                    .reply_markup(ReplyMarkup::kb_remove())
+                    .await?;
+                dialogue
+                    .update(QuizState::ReceiveNewAnswer {
+                        quiz_info,
+                        new_question,
+                        answers,
+                    })
+                    .await?;
+            }
+        },
+        QuizState::ReviewPick { quiz_info, .. } | QuizState::ReviewEditText { quiz_info, .. } => {
+            constructor::show_review(&bot, chat_id, &dialogue, quiz_info).await?;
+        }
+
+        QuizState::Start => {
+            bot.send_message(chat_id, "You are already in the main menu.")
+                .reply_markup(action_keyboard(username))
+                .await?;
+        }
+        _ => {
+            bot.send_message(
+                chat_id,
+                "/back only works in the editor and while creating a quiz. Use /cancel to stop.",
+            )
+            .await?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn to_menu(
+    bot: &Bot,
+    chat_id: ChatId,
+    username: &str,
+    dialogue: &UserDialogue,
+) -> HandlerResult {
+    bot.send_message(chat_id, "Returning to the main menu.")
+        .reply_markup(action_keyboard(username))
+        .await?;
+    dialogue.update(QuizState::Start).await?;
+
+    Ok(())
+}
+
+async fn to_quiz<Connect: RetreiveQuiz>(
+    bot: &Bot,
+    chat_id: ChatId,
+    dialogue: &UserDialogue,
+    quiz_name: String,
+    connection: &Connect,
+) -> HandlerResult {
+    let text = match connection.retreive_quiz(&quiz_name).await? {
+        Some(quiz) => quiz.to_string(),
+        None => format!("Quiz: {}", quiz_name),
+    };
+    bot.send_message(chat_id, text)
+        .reply_markup(edit_quiz_keyboard())
+        .await?;
+    dialogue.update(QuizState::HandleQuiz { quiz_name }).await?;
+
+    Ok(())
+}
+
+async fn to_question<Connect: RetreiveQuestion>(
+    bot: &Bot,
+    chat_id: ChatId,
+    dialogue: &UserDialogue,
+    quiz_name: String,
+    question_name: String,
+    connection: &Connect,
+) -> HandlerResult {
+    let text = match connection
+        .retreive_question(&quiz_name, &question_name)
+        .await?
+    {
+        Some(question) => question.to_string(),
+        None => format!("Question: {}", question_name),
+    };
+    bot.send_message(chat_id, text)
+        .reply_markup(edit_question_keyboard())
+        .await?;
+    dialogue
+        .update(QuizState::HandleQuestion {
+            quiz_name,
+            question_name,
+        })
+        .await?;
+
+    Ok(())
+}
+
+// Reopens the last question of the new quiz for more answers, or goes back to the tags.
+async fn to_last_question(
+    bot: &Bot,
+    chat_id: ChatId,
+    dialogue: &UserDialogue,
+    mut quiz_info: QuizData,
+) -> HandlerResult {
+    match quiz_info.questions.pop() {
+        Some(question) => {
+            to_another_answer(
+                bot,
+                chat_id,
+                dialogue,
+                quiz_info,
+                question.text(),
+                question.answers().to_vec(),
+            )
+            .await
+        }
+        None => {
+            bot.send_message(
+                chat_id,
+                "Which topics is it about? Send tags separated by commas, or choose 'No tags'.",
+            )
+            .reply_markup(no_tags_keyboard())
+            .await?;
+            dialogue
+                .update(QuizState::ReceiveQuizTags { quiz_info })
+                .await?;
+
+            Ok(())
+        }
+    }
+}
+
+async fn to_another_answer(
+    bot: &Bot,
+    chat_id: ChatId,
+    dialogue: &UserDialogue,
+    quiz_info: QuizData,
+    new_question: String,
+    answers: Vec<Answer>,
+) -> HandlerResult {
+    bot.send_message(
+        chat_id,
+        format!(
+            "Question '{}' has {} answer(s). Do you want to add another answer?(Yes/No)",
+            new_question,
+            answers.len()
+        ),
+    )
+    .reply_markup(yes_no_keyboard())
+    .await?;
+    dialogue
+        .update(QuizState::ReceiveAddAnotherAnswer {
+            quiz_info,
+            new_question,
+            answers,
+        })
+        .await?;
+
+    Ok(())
+}
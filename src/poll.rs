@@ -0,0 +1,193 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use teloxide::{
+    dispatching::dialogue::InMemStorage,
+    payloads::SendPollSetters,
+    prelude::{Dialogue, Requester},
+    types::{ChatId, InlineKeyboardMarkup, MessageId, PollAnswer, PollType},
+    Bot,
+};
+use tracing::instrument;
+
+use crate::{
+    database::{connection::Attempts, quiz::Quiz},
+    group::{self, GroupSessions},
+    runner::{self, question_text},
+    state::QuizState,
+    HandlerResult,
+};
+
+// Limits Telegram puts on quiz polls.
+const MAX_QUESTION_LEN: usize = 300;
+const MIN_OPTIONS: usize = 2;
+const MAX_OPTIONS: usize = 10;
+const MAX_OPTION_LEN: usize = 100;
+const MAX_EXPLANATION_LEN: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum PollMode {
+    /// A quiz taken alone, answers go to the dialogue of the chat.
+    Private,
+    /// A group quiz, answers go to the group session.
+    Group,
+}
+
+/// Quiz poll sent in place of a question with answer buttons.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct SentPoll {
+    pub(crate) mode: PollMode,
+    pub(crate) chat_id: ChatId,
+    pub(crate) message_id: MessageId,
+    pub(crate) question_idx: usize,
+}
+
+/// Sent polls keyed by poll id, as `PollAnswer` updates carry nothing else.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PollRegistry(Arc<Mutex<HashMap<String, SentPoll>>>);
+
+impl PollRegistry {
+    fn get(&self, poll_id: &str) -> Option<SentPoll> {
+        self.0.lock().unwrap().get(poll_id).copied()
+    }
+
+    pub(crate) fn remove(&self, poll_id: &str) {
+        self.0.lock().unwrap().remove(poll_id);
+    }
+
+    /// Drops the polls of the chat once their quiz moved on, whether they were answered or not.
+    pub(crate) fn forget(&self, chat_id: ChatId, mode: PollMode) {
+        self.0
+            .lock()
+            .unwrap()
+            .retain(|_, sent| sent.chat_id != chat_id || sent.mode != mode);
+    }
+}
+
+/// Id of the only correct option when the quiz is delivered as polls and the
+/// question fits the poll limits. Other questions fall back to buttons.
+pub(crate) fn poll_option(quiz: &Quiz, idx: usize) -> Option<u8> {
+    if !quiz.native_polls() {
+        return None;
+    }
+
+    let question = &quiz.questions()[idx];
+    let answers = question.answers();
+    let mut correct = answers
+        .iter()
+        .enumerate()
+        .filter(|(_, answer)| answer.is_correct());
+    let (correct_idx, _) = correct.next()?;
+
+    let fits = correct.next().is_none()
+        && question_text(quiz, idx).chars().count() <= MAX_QUESTION_LEN
+        && (MIN_OPTIONS..=MAX_OPTIONS).contains(&answers.len())
+        && answers
+            .iter()
+            .all(|answer| answer.text().chars().count() <= MAX_OPTION_LEN)
+        && question
+            .explanation()
+            .is_none_or(|explanation| explanation.chars().count() <= MAX_EXPLANATION_LEN);
+    fits.then_some(correct_idx as u8)
+}
+
+/// Sends the question as a quiz poll if it can be one, returns `false` otherwise.
+pub(crate) async fn send_quiz_poll(
+    bot: &Bot,
+    polls: &PollRegistry,
+    mode: PollMode,
+    chat_id: ChatId,
+    quiz: &Quiz,
+    question_idx: usize,
+    keyboard: InlineKeyboardMarkup,
+) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+    let Some(correct_option) = poll_option(quiz, question_idx) else {
+        return Ok(false);
+    };
+
+    let question = &quiz.questions()[question_idx];
+    let mut poll = bot
+        .send_poll(
+            chat_id,
+            question_text(quiz, question_idx),
+            question.answers().iter().map(|answer| answer.text()),
+        )
+        .type_(PollType::Quiz)
+        .correct_option_id(correct_option)
+        // Anonymous polls don't report who answered.
+        .is_anonymous(false)
+        .reply_markup(keyboard);
+    // Telegram shows it once the vote is cast.
+    if let Some(explanation) = question.explanation() {
+        poll = poll.explanation(explanation);
+    }
+    let message = poll.await?;
+
+    if let Some(poll) = message.poll() {
+        polls.0.lock().unwrap().insert(
+            poll.id.clone(),
+            SentPoll {
+                mode,
+                chat_id,
+                message_id: message.id,
+                question_idx,
+            },
+        );
+    }
+
+    Ok(true)
+}
+
+#[instrument(level = "info", skip(connection, bot, storage, polls, sessions))]
+pub(crate) async fn take_poll_answer<Connect: Attempts>(
+    bot: Bot,
+    answer: PollAnswer,
+    polls: PollRegistry,
+    storage: Arc<InMemStorage<QuizState>>,
+    sessions: GroupSessions,
+    connection: Arc<Connect>,
+) -> HandlerResult {
+    // A retracted vote comes without options.
+    let (Some(sent), Some(user), Some(option)) = (
+        polls.get(&answer.poll_id),
+        answer.voter.user(),
+        answer.option_ids.first(),
+    ) else {
+        return Ok(());
+    };
+
+    match sent.mode {
+        PollMode::Private => {
+            polls.remove(&answer.poll_id);
+            runner::take_poll_answer(
+                &bot,
+                &Dialogue::new(storage, sent.chat_id),
+                user.username.as_deref().unwrap_or_default(),
+                sent,
+                *option as usize,
+                connection.as_ref(),
+                &polls,
+            )
+            .await
+        }
+        PollMode::Group => {
+            // Polls can't show why a vote is refused, it is just not counted.
+            if group::admit(&sessions, sent.chat_id, user, connection.as_ref())
+                .await?
+                .is_err()
+            {
+                return Ok(());
+            }
+            group::record_answer(
+                &sessions,
+                sent.chat_id,
+                user,
+                sent.question_idx,
+                *option as usize,
+            );
+            Ok(())
+        }
+    }
+}
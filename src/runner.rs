@@ -1,117 +1,121 @@
-use std::sync::Arc;
+use std::{error::Error, sync::Arc};
 
+use chrono::Utc;
 use teloxide::{
     dispatching::dialogue::GetChatId,
     payloads::SendMessageSetters,
     prelude::Requester,
-    types::{CallbackQuery, Message, ReplyMarkup},
+    types::{CallbackQuery, ChatId, Message, ReplyMarkup},
     Bot,
 };
 use tracing::instrument;
+use uuid::Uuid;
 
 use crate::{
-    database::{connection::RetreiveQuiz, quiz::Quiz},
-    keyboard::{action_keyboard, answers_keyboard, yes_no_keyboard},
+    database::{
+        attempt::{score, QuestionProgress, ATTEMPT_EXPIRY},
+        connection::{Attempts, RetreiveQuiz, Revisions},
+        grading::format_points,
+        quiz::Quiz,
+    },
+    keyboard::{
+        action_keyboard, finish_confirmation_keyboard, running_answers_keyboard, yes_no_keyboard,
+        CANCEL_FINISH, CONFIRM_FINISH, FINISH_QUIZ, SKIP_ANSWER,
+    },
+    poll::{send_quiz_poll, PollMode, PollRegistry, SentPoll},
     state::QuizState,
     HandlerResult, UserDialogue,
 };
 
-#[instrument(level = "info", skip(connection, bot, dialogue))]
-pub(crate) async fn selection<Retreiver: RetreiveQuiz>(
-    bot: Bot,
-    dialogue: UserDialogue,
-    msg: Message,
-    connection: Arc<Retreiver>,
+/// Shows the quiz and asks whether to begin, if the user may take it now.
+pub(crate) async fn offer_quiz<Retreiver: RetreiveQuiz + Attempts>(
+    bot: &Bot,
+    chat_id: ChatId,
+    dialogue: &UserDialogue,
+    quiz_name: &str,
+    connection: &Retreiver,
 ) -> HandlerResult {
-    match msg.text() {
-        Some(quiz_name) => match connection.retreive_quiz(quiz_name).await {
-            Ok(res) => match res {
-                Some(quiz) => {
-                    dialogue
-                        .update(QuizState::ReadyToRun {
-                            quiz: quiz.clone(),
-                            curr_idx: 0,
-                        })
-                        .await?;
-                    bot.send_message(msg.chat.id, format!("Title{}\nDescription{}\nBy {}.\nQuestions: {}\n Are you ready to begin? (Yes/No)", quiz.title(), quiz.description(), quiz.author(), quiz.questions().len())).reply_markup(yes_no_keyboard()).await?;
-                }
-                None => {
-                    bot.send_message(
-                        msg.chat.id,
-                        format!("Quiz with name '{}' not found.", quiz_name),
-                    )
-                    .await?;
-                }
-            },
-            Err(e) => {
-                return Err(e);
+    match connection.retreive_quiz(quiz_name).await? {
+        Some(quiz) => {
+            let attempts_taken = connection.count_attempts(chat_id.0, quiz.uuid()).await?;
+            if let Err(reason) = quiz.check(Utc::now(), attempts_taken) {
+                bot.send_message(
+                    chat_id,
+                    format!("Sorry, you can't take '{}'. {}", quiz.title(), reason),
+                )
+                .await?;
+                return Ok(());
             }
-        },
-        None => {
-            bot.send_message(msg.chat.id, "Failed to retreive quiz: no input provided")
+
+            dialogue
+                .update(QuizState::ReadyToRun {
+                    quiz: quiz.clone(),
+                    curr_idx: 0,
+                })
                 .await?;
+            bot.send_message(chat_id, format!("Title{}\nDescription{}\nBy {}.\nQuestions: {}\n Are you ready to begin? (Yes/No)", quiz.title(), quiz.description(), quiz.author(), quiz.questions().len())).reply_markup(yes_no_keyboard()).await?;
         }
-    };
+        None => {
+            bot.send_message(
+                chat_id,
+                format!("Quiz with name '{}' not found.", quiz_name),
+            )
+            .await?;
+        }
+    }
+
     Ok(())
 }
 
-#[instrument(level = "info", skip(bot, dialogue))]
-pub(crate) async fn running_ready(
+#[instrument(level = "info", skip(connection, bot, dialogue))]
+pub(crate) async fn running_ready<Connect: Attempts>(
     bot: Bot,
     dialogue: UserDialogue,
     msg: Message,
-    (quiz, mut curr_idx): (Quiz, usize),
+    (quiz, curr_idx): (Quiz, usize),
+    connection: Arc<Connect>,
+    polls: PollRegistry,
 ) -> HandlerResult {
     match msg.text() {
         Some("Yes") | Some("Yes✔️") => {
-            if quiz.questions().len() < 1 {
+            let progress = vec![QuestionProgress::Unanswered; quiz.questions().len()];
+            let Some(curr_idx) = next_unanswered(&quiz, &progress, curr_idx) else {
                 bot.send_message(msg.chat.id, "Sorry, no questions for that quiz available.")
                     .reply_markup(action_keyboard(msg.chat.username().unwrap_or_default()))
                     .await?;
                 dialogue.update(QuizState::Start).await?;
                 return Ok(());
-            }
-            let mut curr_question = &quiz.questions()[curr_idx];
+            };
+
             bot.send_message(msg.chat.id, "Let's begin!")
                 .reply_markup(ReplyMarkup::kb_remove())
                 .await?;
-
-            let mut answers_keyboard_markup = answers_keyboard(curr_question.answers());
-
-            while answers_keyboard_markup.inline_keyboard.len() < 1 {
+            let unplayable = quiz
+                .questions()
+                .iter()
+                .filter(|question| question.answers().is_empty())
+                .count();
+            if unplayable > 0 {
                 bot.send_message(
                     msg.chat.id,
-                    "Sorry, it seems that current answer doesn't have answers. Skipping...",
+                    format!(
+                        "Sorry, {} question(s) don't have answers. Skipping them...",
+                        unplayable
+                    ),
                 )
                 .await?;
-                curr_idx += 1;
-
-                if curr_idx >= quiz.questions().len() {
-                    bot.send_message(
-                        msg.chat.id,
-                        "Oh, it was the only question. Quitting quiz...",
-                    )
-                    .reply_markup(action_keyboard(msg.chat.username().unwrap_or_default()))
-                    .await?;
-                    dialogue.update(QuizState::Start).await?;
-                    return Ok(());
-                }
-                curr_question = &quiz.questions()[curr_idx];
-                answers_keyboard_markup = answers_keyboard(curr_question.answers());
             }
 
-            bot.send_message(
-                msg.chat.id,
-                format!("Question #{}\n{}", curr_idx + 1, curr_question.text()),
-            )
-            .reply_markup(answers_keyboard_markup)
-            .parse_mode(teloxide::types::ParseMode::Html)
-            .await?;
+            let attempt = connection
+                .start_attempt(msg.chat.id.0, quiz.uuid(), quiz.revision())
+                .await?;
+            send_question(&bot, &polls, msg.chat.id, &quiz, curr_idx).await?;
             dialogue
                 .update(QuizState::Running {
                     quiz,
                     curr_idx,
-                    score: 0,
+                    progress,
+                    attempt,
                 })
                 .await?;
         }
@@ -136,104 +140,424 @@ pub(crate) async fn running_ready(
     Ok(())
 }
 
-#[instrument(level = "info", skip(bot, dialogue))]
-pub(crate) async fn take_answer(
+#[instrument(level = "info", skip(connection, bot, dialogue))]
+pub(crate) async fn take_answer<Connect: Attempts>(
     bot: Bot,
     dialogue: UserDialogue,
     q: CallbackQuery,
-    (quiz, mut curr_idx, mut score): (Quiz, usize, u32),
+    (quiz, curr_idx, mut progress, attempt): (Quiz, usize, Vec<QuestionProgress>, Uuid),
+    connection: Arc<Connect>,
+    polls: PollRegistry,
 ) -> HandlerResult {
-    if let Some(answer_str) = &q.data {
-        let answer_data = quiz.questions()[curr_idx]
-            .answers()
-            .iter()
-            .find(|answer| answer.text() == answer_str.clone())
-            .unwrap();
-        let text = if answer_data.is_correct() {
-            score += 1;
-            format!("Given answer {}. Answer is correct.✅", answer_str)
-        } else {
-            format!("Given answer {}. Answer is incorrect.❌", answer_str)
-        };
-
-        bot.answer_callback_query(&q.id).await?;
+    let Some(answer_str) = &q.data else {
+        return Ok(());
+    };
+    bot.answer_callback_query(&q.id).await?;
 
-        let chat_id = q.chat_id().unwrap();
+    let chat_id = q.chat_id().unwrap();
+    let username = q.from.username.clone().unwrap_or_default();
 
-        if let Some(message) = &q.message {
-            bot.edit_message_text(
-                chat_id,
-                message.id(),
-                format!(
-                    "{}\n{}",
-                    message.regular_message().unwrap().text().unwrap(),
-                    text
-                ),
+    match answer_str.as_str() {
+        FINISH_QUIZ => {
+            if !ask_finish_confirmation(&bot, chat_id, &quiz, &progress).await? {
+                finish_quiz(
+                    &bot,
+                    &username,
+                    &dialogue,
+                    connection.as_ref(),
+                    &polls,
+                    &quiz,
+                    &progress,
+                    &attempt,
+                )
+                .await?;
+            }
+        }
+        CONFIRM_FINISH => {
+            remove_buttons(&bot, &q).await?;
+            finish_quiz(
+                &bot,
+                &username,
+                &dialogue,
+                connection.as_ref(),
+                &polls,
+                &quiz,
+                &progress,
+                &attempt,
             )
             .await?;
         }
+        CANCEL_FINISH => {
+            remove_buttons(&bot, &q).await?;
+            if let Some(next_idx) = next_unanswered(&quiz, &progress, curr_idx) {
+                send_question(&bot, &polls, chat_id, &quiz, next_idx).await?;
+                dialogue
+                    .update(QuizState::Running {
+                        quiz,
+                        curr_idx: next_idx,
+                        progress,
+                        attempt,
+                    })
+                    .await?;
+            }
+        }
+        answer_str => {
+            let text = if answer_str == SKIP_ANSWER {
+                "Question skipped.⏭️ You can come back to it later.".to_owned()
+            } else {
+                let Some(text) = record_answer(&quiz, curr_idx, &mut progress, answer_str) else {
+                    return Ok(());
+                };
+                text
+            };
 
-        if curr_idx + 1 >= quiz.questions().len() {
-            bot.send_message(
-                q.chat_id().unwrap(),
-                "Congratulations! You completed the quiz!",
+            if let Some(message) = &q.message {
+                // Telegram shows the verdict on polls itself, only their buttons go away.
+                match message.regular_message() {
+                    Some(message) if message.poll().is_none() => {
+                        bot.edit_message_text(
+                            chat_id,
+                            message.id,
+                            format!("{}\n{}", message.text().unwrap_or_default(), text),
+                        )
+                        .await?;
+                    }
+                    _ => remove_buttons(&bot, &q).await?,
+                }
+            }
+
+            move_on(
+                &bot,
+                &dialogue,
+                &username,
+                (quiz, curr_idx, progress, attempt),
+                connection.as_ref(),
+                &polls,
             )
             .await?;
-            bot.send_message(
-                q.chat_id().unwrap(),
-                format!("Your result is {}/{}", score, quiz.questions().len()),
+        }
+    }
+
+    Ok(())
+}
+
+/// Answer given by voting in a quiz poll sent in place of answer buttons.
+pub(crate) async fn take_poll_answer<Connect: Attempts>(
+    bot: &Bot,
+    dialogue: &UserDialogue,
+    username: &str,
+    sent: SentPoll,
+    option: usize,
+    connection: &Connect,
+    polls: &PollRegistry,
+) -> HandlerResult {
+    let Some(QuizState::Running {
+        quiz,
+        curr_idx,
+        mut progress,
+        attempt,
+    }) = dialogue.get().await?
+    else {
+        return Ok(());
+    };
+    // The question may have been skipped in the meantime.
+    if curr_idx != sent.question_idx {
+        return Ok(());
+    }
+    let Some(answer) = quiz.questions()[curr_idx].answers().get(option) else {
+        return Ok(());
+    };
+    if record_answer(&quiz, curr_idx, &mut progress, &answer.text()).is_none() {
+        return Ok(());
+    }
+
+    bot.edit_message_reply_markup(sent.chat_id, sent.message_id)
+        .await?;
+    move_on(
+        bot,
+        dialogue,
+        username,
+        (quiz, curr_idx, progress, attempt),
+        connection,
+        polls,
+    )
+    .await
+}
+
+// Stores the points of the given answer and returns the verdict, `None` if there is no such answer.
+fn record_answer(
+    quiz: &Quiz,
+    curr_idx: usize,
+    progress: &mut [QuestionProgress],
+    answer_str: &str,
+) -> Option<String> {
+    let question = &quiz.questions()[curr_idx];
+    let answer_data = question
+        .answers()
+        .iter()
+        .find(|answer| answer.text() == answer_str)?;
+
+    let verdict = if answer_data.is_correct() {
+        progress[curr_idx] = QuestionProgress::Answered {
+            points: question.points(),
+        };
+        format!("Given answer {}. Answer is correct.✅", answer_str)
+    } else {
+        progress[curr_idx] = QuestionProgress::Answered {
+            points: -question.penalty(),
+        };
+        format!("Given answer {}. Answer is incorrect.❌", answer_str)
+    };
+
+    match question.explanation() {
+        Some(explanation) => Some(format!("{}\n\n{}", verdict, explanation)),
+        None => Some(verdict),
+    }
+}
+
+// Sends the next unanswered question, or finishes once there is none left.
+async fn move_on<Connect: Attempts>(
+    bot: &Bot,
+    dialogue: &UserDialogue,
+    username: &str,
+    (quiz, curr_idx, progress, attempt): (Quiz, usize, Vec<QuestionProgress>, Uuid),
+    connection: &Connect,
+    polls: &PollRegistry,
+) -> HandlerResult {
+    let chat_id = dialogue.chat_id();
+
+    match next_unanswered(&quiz, &progress, curr_idx + 1) {
+        None => {
+            finish_quiz(
+                bot, username, dialogue, connection, polls, &quiz, &progress, &attempt,
             )
             .await?;
-            dialogue.update(QuizState::Start).await?;
-            bot.send_message(q.chat_id().unwrap(), "What do you want to do now?")
-                .reply_markup(action_keyboard(q.from.username.unwrap()))
+        }
+        // Only the question just skipped is left, so there is nothing to move on to.
+        Some(next_idx) if next_idx == curr_idx => {
+            connection
+                .save_progress(&attempt, curr_idx, &progress)
                 .await?;
-        } else {
-            let mut curr_question = &quiz.questions()[curr_idx + 1];
-            let mut answers_keyboard_markup = answers_keyboard(curr_question.answers());
-
-            while answers_keyboard_markup.inline_keyboard.len() < 1 {
-                bot.send_message(
-                    q.chat_id().unwrap(),
-                    "Sorry, it seems that current question doesn't have answers. Skipping...",
-                )
+            ask_finish_confirmation(bot, chat_id, &quiz, &progress).await?;
+        }
+        Some(next_idx) => {
+            connection
+                .save_progress(&attempt, next_idx, &progress)
                 .await?;
-                curr_idx += 1;
-
-                if curr_idx + 1 >= quiz.questions().len() {
-                    bot.send_message(
-                        q.chat_id().unwrap(),
-                        format!(
-                            "Oh, no more questions left. Your score is {}/{}",
-                            score,
-                            quiz.questions().len()
-                        ),
-                    )
-                    .reply_markup(action_keyboard(q.from.username.unwrap_or_default()))
-                    .await?;
-                    dialogue.update(QuizState::Start).await?;
-                    return Ok(());
-                }
-                curr_question = &quiz.questions()[curr_idx + 1];
-                answers_keyboard_markup = answers_keyboard(curr_question.answers());
-            }
+            send_question(bot, polls, chat_id, &quiz, next_idx).await?;
+            dialogue
+                .update(QuizState::Running {
+                    quiz,
+                    curr_idx: next_idx,
+                    progress,
+                    attempt,
+                })
+                .await?;
+        }
+    }
 
+    Ok(())
+}
+
+/// Continues the latest unfinished attempt of the user where it was left.
+#[instrument(level = "info", skip(connection, bot, dialogue))]
+pub(crate) async fn resume<Connect: RetreiveQuiz + Attempts + Revisions>(
+    bot: &Bot,
+    msg: &Message,
+    dialogue: &UserDialogue,
+    connection: &Connect,
+    polls: &PollRegistry,
+) -> HandlerResult {
+    let unfinished = connection
+        .retreive_unfinished_attempt(msg.chat.id.0, Utc::now() - ATTEMPT_EXPIRY)
+        .await?;
+    let quiz = match &unfinished {
+        Some(unfinished) => {
+            connection
+                .retreive_quiz_at(unfinished.quiz_name(), unfinished.revision())
+                .await?
+        }
+        None => None,
+    };
+    let (Some(unfinished), Some(quiz)) = (unfinished, quiz) else {
+        bot.send_message(msg.chat.id, "You have no unfinished quiz.")
+            .reply_markup(action_keyboard(msg.chat.username().unwrap_or_default()))
+            .await?;
+        return Ok(());
+    };
+
+    let progress = unfinished.progress(quiz.questions().len());
+    match next_unanswered(&quiz, &progress, unfinished.curr_idx()) {
+        Some(curr_idx) => {
             bot.send_message(
-                q.chat_id().unwrap(),
-                format!("Question #{}\n{}", curr_idx + 1, curr_question.text()),
+                msg.chat.id,
+                format!("Continuing '{}' where you stopped.", quiz.title()),
             )
-            .reply_markup(answers_keyboard_markup)
-            .parse_mode(teloxide::types::ParseMode::Html)
+            .reply_markup(ReplyMarkup::kb_remove())
             .await?;
+            send_question(bot, polls, msg.chat.id, &quiz, curr_idx).await?;
             dialogue
                 .update(QuizState::Running {
                     quiz,
-                    curr_idx: curr_idx + 1,
-                    score,
+                    curr_idx,
+                    progress,
+                    attempt: *unfinished.uuid(),
                 })
                 .await?;
         }
+        None => {
+            finish_quiz(
+                bot,
+                msg.chat.username().unwrap_or_default(),
+                dialogue,
+                connection,
+                polls,
+                &quiz,
+                &progress,
+                unfinished.uuid(),
+            )
+            .await?;
+        }
+    }
+
+    Ok(())
+}
+
+// First playable question without an answer, starting at `from` and wrapping around
+// so that skipped questions are offered again.
+fn next_unanswered(quiz: &Quiz, progress: &[QuestionProgress], from: usize) -> Option<usize> {
+    let len = quiz.questions().len();
+    (0..len).map(|offset| (from + offset) % len).find(|idx| {
+        progress[*idx] == QuestionProgress::Unanswered
+            && !quiz.questions()[*idx].answers().is_empty()
+    })
+}
+
+/// First question from `from` onwards that has answers to choose from.
+pub(crate) fn next_playable(quiz: &Quiz, from: usize) -> Option<usize> {
+    (from..quiz.questions().len()).find(|idx| !quiz.questions()[*idx].answers().is_empty())
+}
+
+pub(crate) fn question_text(quiz: &Quiz, idx: usize) -> String {
+    format!(
+        "Question #{}/{}\n{}",
+        idx + 1,
+        quiz.questions().len(),
+        quiz.questions()[idx].text()
+    )
+}
+
+async fn send_question(
+    bot: &Bot,
+    polls: &PollRegistry,
+    chat_id: ChatId,
+    quiz: &Quiz,
+    idx: usize,
+) -> HandlerResult {
+    polls.forget(chat_id, PollMode::Private);
+    let controls = running_answers_keyboard(&[]);
+    if send_quiz_poll(bot, polls, PollMode::Private, chat_id, quiz, idx, controls).await? {
+        return Ok(());
+    }
+
+    bot.send_message(chat_id, question_text(quiz, idx))
+        .reply_markup(running_answers_keyboard(quiz.questions()[idx].answers()))
+        .parse_mode(teloxide::types::ParseMode::Html)
+        .await?;
+
+    Ok(())
+}
+
+// Lists unanswered questions and asks whether to finish anyway.
+// Returns `false` when everything is answered and there is nothing to confirm.
+async fn ask_finish_confirmation(
+    bot: &Bot,
+    chat_id: ChatId,
+    quiz: &Quiz,
+    progress: &[QuestionProgress],
+) -> Result<bool, Box<dyn Error + Send + Sync>> {
+    let unanswered: Vec<String> = quiz
+        .questions()
+        .iter()
+        .enumerate()
+        .filter(|(idx, question)| {
+            progress[*idx] == QuestionProgress::Unanswered && !question.answers().is_empty()
+        })
+        .map(|(idx, question)| format!("#{} {}", idx + 1, question.text()))
+        .collect();
+
+    if unanswered.is_empty() {
+        return Ok(false);
+    }
+
+    bot.send_message(
+        chat_id,
+        format!(
+            "You still have {} unanswered question(s):\n{}\n\nFinish anyway?",
+            unanswered.len(),
+            unanswered.join("\n")
+        ),
+    )
+    .reply_markup(finish_confirmation_keyboard())
+    .await?;
+
+    Ok(true)
+}
+
+async fn remove_buttons(bot: &Bot, q: &CallbackQuery) -> HandlerResult {
+    if let (Some(chat_id), Some(message)) = (q.chat_id(), &q.message) {
+        bot.edit_message_reply_markup(chat_id, message.id()).await?;
     }
 
     Ok(())
 }
+
+async fn finish_quiz<Connect: Attempts>(
+    bot: &Bot,
+    username: &str,
+    dialogue: &UserDialogue,
+    connection: &Connect,
+    polls: &PollRegistry,
+    quiz: &Quiz,
+    progress: &[QuestionProgress],
+    attempt: &Uuid,
+) -> HandlerResult {
+    let result = finish_attempt(connection, quiz, attempt, score(progress)).await?;
+    let chat_id = dialogue.chat_id();
+    polls.forget(chat_id, PollMode::Private);
+
+    bot.send_message(chat_id, "Congratulations! You completed the quiz!")
+        .await?;
+    bot.send_message(chat_id, result).await?;
+    dialogue.update(QuizState::Start).await?;
+    bot.send_message(chat_id, "What do you want to do now?")
+        .reply_markup(action_keyboard(username))
+        .await?;
+
+    Ok(())
+}
+
+/// Grades the attempt, stores its outcome and returns the text reporting it.
+pub(crate) async fn finish_attempt<Connect: Attempts>(
+    connection: &Connect,
+    quiz: &Quiz,
+    attempt: &Uuid,
+    score: f64,
+) -> Result<String, Box<dyn Error + Send + Sync>> {
+    let max_score = quiz.max_score();
+    let outcome = quiz.grading().evaluate(score, max_score);
+    connection
+        .finish_attempt(attempt, score, max_score, &outcome)
+        .await?;
+
+    let mut result = format!(
+        "Your result is {}/{}",
+        format_points(score),
+        format_points(max_score)
+    );
+    if !outcome.is_empty() {
+        result.push_str(&format!("\n{}", outcome));
+    }
+
+    Ok(result)
+}
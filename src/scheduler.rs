@@ -0,0 +1,824 @@
+use std::{error::Error, sync::Arc, time::Duration};
+
+use chrono::{DateTime, FixedOffset, NaiveDateTime, NaiveTime, TimeDelta, TimeZone, Utc};
+use chrono_tz::Tz;
+use teloxide::{prelude::Requester, types::ChatId, Bot};
+use tracing::instrument;
+
+use crate::{
+    database::{
+        attempt::{score, ATTEMPT_EXPIRY},
+        connection::{Attempts, Revisions, Schedule, Trash},
+        quiz::TRASH_RETENTION,
+        schedule::{Reminder, ScheduledPush},
+    },
+    runner::finish_attempt,
+};
+
+const TICK_PERIOD: Duration = Duration::from_secs(30);
+
+/// Source of the current time, so that the scheduler can be driven by a fake clock.
+pub(crate) trait Clock {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+pub(crate) struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// Delivers scheduled messages to a chat.
+pub(crate) trait Notifier {
+    async fn notify(&self, chat_id: i64, text: String) -> Result<(), Box<dyn Error + Send + Sync>>;
+}
+
+impl Notifier for Bot {
+    async fn notify(&self, chat_id: i64, text: String) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.send_message(ChatId(chat_id), text).await?;
+        Ok(())
+    }
+}
+
+pub(crate) struct Scheduler<C, S, N> {
+    clock: C,
+    store: Arc<S>,
+    notifier: N,
+}
+
+impl<C: Clock, S: Schedule + Attempts + Revisions + Trash, N: Notifier> Scheduler<C, S, N> {
+    pub(crate) fn new(clock: C, store: Arc<S>, notifier: N) -> Self {
+        Self {
+            clock,
+            store,
+            notifier,
+        }
+    }
+
+    pub(crate) async fn run(self) {
+        let mut interval = tokio::time::interval(TICK_PERIOD);
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.tick().await {
+                tracing::error!("Scheduler tick failed: {}", e);
+            }
+        }
+    }
+
+    /// Fires everything that became due by the clock's current time.
+    ///
+    /// Schedules are read from the store on every tick, so anything missed while
+    /// the bot was down fires once on the first tick after a restart.
+    #[instrument(level = "debug", skip(self))]
+    pub(crate) async fn tick(&self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let now = self.clock.now();
+
+        for reminder in self.store.retreive_due_reminders(now).await? {
+            self.send(
+                reminder.user_id(),
+                "⏰ Daily reminder: time to practice! Use /study to review due questions or /start to take a quiz.",
+            )
+            .await;
+
+            let next_fire_at = next_daily_fire(reminder.time_of_day(), reminder.time_zone(), now);
+            self.store
+                .set_reminder(&Reminder::new(
+                    reminder.user_id(),
+                    *reminder.time_of_day(),
+                    *reminder.time_zone(),
+                    next_fire_at,
+                ))
+                .await?;
+        }
+
+        for push in self.store.retreive_due_pushes(now).await? {
+            let recipients = match push.chat_id() {
+                Some(chat_id) => vec![chat_id],
+                None => self.store.retreive_subscribers(push.quiz_id()).await?,
+            };
+
+            for chat_id in recipients {
+                self.send(chat_id, &push_text(&push, chat_id)).await;
+            }
+
+            self.store.mark_push_sent(&push).await?;
+        }
+
+        for unfinished in self
+            .store
+            .retreive_expired_attempts(now - ATTEMPT_EXPIRY)
+            .await?
+        {
+            let Some(quiz) = self
+                .store
+                .retreive_quiz_at(unfinished.quiz_name(), unfinished.revision())
+                .await?
+            else {
+                continue;
+            };
+            let progress = unfinished.progress(quiz.questions().len());
+            let result = finish_attempt(
+                self.store.as_ref(),
+                &quiz,
+                unfinished.uuid(),
+                score(&progress),
+            )
+            .await?;
+            self.send(
+                unfinished.user_id(),
+                &format!(
+                    "Your unfinished attempt at '{}' expired and was closed.\n{}",
+                    quiz.title(),
+                    result
+                ),
+            )
+            .await;
+        }
+
+        let purged = self.store.purge_trash(now - TRASH_RETENTION).await?;
+        if purged > 0 {
+            tracing::info!("Purged {} quizes and questions from the trash", purged);
+        }
+
+        Ok(())
+    }
+
+    // A single unreachable chat (e.g. the user blocked the bot) must not hold back the others.
+    async fn send(&self, chat_id: i64, text: &str) {
+        if let Err(e) = self.notifier.notify(chat_id, text.to_owned()).await {
+            tracing::warn!("Failed to notify chat {}: {}", chat_id, e);
+        }
+    }
+}
+
+// Private chats take the quiz from the menu, groups run it for everyone.
+fn push_text(push: &ScheduledPush, chat_id: i64) -> String {
+    let how = if ChatId(chat_id).is_user() {
+        "Use /start and choose 'Take a quiz📝' to begin.".to_owned()
+    } else {
+        format!("Use /group {} to take it together here.", push.quiz_name())
+    };
+    format!(
+        "📢 Quiz '{}' is waiting for you!\n{}\n\n{}",
+        push.quiz_name(),
+        push.quiz_description(),
+        how
+    )
+}
+
+/// First moment strictly after `after` at which the local clock shows `time`,
+/// resolved in the zone for each day so that it follows daylight saving time.
+pub(crate) fn next_daily_fire(
+    time: &NaiveTime,
+    time_zone: &Tz,
+    after: DateTime<Utc>,
+) -> DateTime<Utc> {
+    let mut date = after.with_timezone(time_zone).date_naive();
+    loop {
+        // A time repeated when clocks go back fires the first time, one skipped
+        // when they go forward fires an hour later.
+        let local = date.and_time(*time);
+        let candidate = time_zone
+            .from_local_datetime(&local)
+            .earliest()
+            .or_else(|| {
+                time_zone
+                    .from_local_datetime(&(local + TimeDelta::hours(1)))
+                    .earliest()
+            })
+            .map(|candidate| candidate.with_timezone(&Utc));
+        if let Some(candidate) = candidate.filter(|candidate| *candidate > after) {
+            return candidate;
+        }
+        date = date.succ_opt().unwrap();
+    }
+}
+
+/// Parses `UTC`, `UTC+2`, `+02:00` or `-05:30` style offsets.
+pub(crate) fn parse_offset(text: &str) -> Option<FixedOffset> {
+    let text = text.trim();
+    let text = text
+        .strip_prefix("UTC")
+        .or_else(|| text.strip_prefix("GMT"))
+        .unwrap_or(text);
+    if text.is_empty() {
+        return FixedOffset::east_opt(0);
+    }
+
+    let (sign, rest) = match text.chars().next()? {
+        '+' => (1, &text[1..]),
+        '-' => (-1, &text[1..]),
+        _ => return None,
+    };
+    let (hours, minutes) = match rest.split_once(':') {
+        Some((hours, minutes)) => (hours.parse::<i32>().ok()?, minutes.parse::<i32>().ok()?),
+        None => (rest.parse::<i32>().ok()?, 0),
+    };
+    if !(0..60).contains(&minutes) {
+        return None;
+    }
+
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+/// Parses `HH:MM [time zone]` with an IANA zone name like `Europe/Berlin`, defaulting to UTC.
+pub(crate) fn parse_reminder(text: &str) -> Option<(NaiveTime, Tz)> {
+    let mut parts = text.split_whitespace();
+    let time = NaiveTime::parse_from_str(parts.next()?, "%H:%M").ok()?;
+    let time_zone = parts.next().unwrap_or("UTC").parse::<Tz>().ok()?;
+    if parts.next().is_some() {
+        return None;
+    }
+
+    Some((time, time_zone))
+}
+
+/// Parses `YYYY-MM-DD HH:MM [offset]`, the offset defaulting to UTC.
+pub(crate) fn parse_datetime(text: &str) -> Option<DateTime<Utc>> {
+    let mut parts = text.split_whitespace();
+    let local = NaiveDateTime::parse_from_str(
+        &format!("{} {}", parts.next()?, parts.next()?),
+        "%Y-%m-%d %H:%M",
+    )
+    .ok()?;
+    let offset = parse_offset(parts.next().unwrap_or("UTC"))?;
+    if parts.next().is_some() {
+        return None;
+    }
+
+    offset
+        .from_local_datetime(&local)
+        .single()
+        .map(|datetime| datetime.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, sync::Mutex};
+
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::database::{
+        attempt::{QuestionProgress, UnfinishedAttempt},
+        grading::Outcome,
+        page::Page,
+        quiz::{Answer, Question, Quiz},
+        revision::Revision,
+    };
+
+    struct FakeClock(DateTime<Utc>);
+
+    impl Clock for FakeClock {
+        fn now(&self) -> DateTime<Utc> {
+            self.0
+        }
+    }
+
+    #[derive(Clone, Default)]
+    struct FakeNotifier {
+        sent: Arc<Mutex<Vec<(i64, String)>>>,
+        unreachable: Option<i64>,
+    }
+
+    impl Notifier for FakeNotifier {
+        async fn notify(
+            &self,
+            chat_id: i64,
+            text: String,
+        ) -> Result<(), Box<dyn Error + Send + Sync>> {
+            if self.unreachable == Some(chat_id) {
+                return Err("bot was blocked by the user".into());
+            }
+            self.sent.lock().unwrap().push((chat_id, text));
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct FakeState {
+        reminders: Vec<Reminder>,
+        pushes: Vec<ScheduledPush>,
+        sent_pushes: Vec<Uuid>,
+        subscribers: HashMap<Uuid, Vec<i64>>,
+        // Attempts with the time of their last activity.
+        attempts: Vec<(UnfinishedAttempt, DateTime<Utc>)>,
+        finished: Vec<(Uuid, f64, f64)>,
+        revisions: HashMap<(String, i32), Quiz>,
+        purged_before: Option<DateTime<Utc>>,
+    }
+
+    #[derive(Default)]
+    struct FakeStore(Mutex<FakeState>);
+
+    impl Schedule for FakeStore {
+        async fn set_reminder(
+            &self,
+            reminder: &Reminder,
+        ) -> Result<(), Box<dyn Error + Send + Sync>> {
+            let mut state = self.0.lock().unwrap();
+            state
+                .reminders
+                .retain(|kept| kept.user_id() != reminder.user_id());
+            state.reminders.push(reminder.clone());
+            Ok(())
+        }
+
+        async fn remove_reminder(
+            &self,
+            _user_id: i64,
+        ) -> Result<bool, Box<dyn Error + Send + Sync>> {
+            Ok(false)
+        }
+
+        async fn retreive_due_reminders(
+            &self,
+            now: DateTime<Utc>,
+        ) -> Result<Vec<Reminder>, Box<dyn Error + Send + Sync>> {
+            let state = self.0.lock().unwrap();
+            Ok(state
+                .reminders
+                .iter()
+                .filter(|reminder| *reminder.next_fire_at() <= now)
+                .cloned()
+                .collect())
+        }
+
+        async fn schedule_push(
+            &self,
+            _quiz_id: impl Into<String>,
+            _chat_id: Option<i64>,
+            _fire_at: DateTime<Utc>,
+        ) -> Result<String, Box<dyn Error + Send + Sync>> {
+            Ok(String::new())
+        }
+
+        async fn retreive_due_pushes(
+            &self,
+            now: DateTime<Utc>,
+        ) -> Result<Vec<ScheduledPush>, Box<dyn Error + Send + Sync>> {
+            let state = self.0.lock().unwrap();
+            Ok(state
+                .pushes
+                .iter()
+                .filter(|push| *push.fire_at() <= now && !state.sent_pushes.contains(push.uuid()))
+                .cloned()
+                .collect())
+        }
+
+        async fn mark_push_sent(
+            &self,
+            push: &ScheduledPush,
+        ) -> Result<(), Box<dyn Error + Send + Sync>> {
+            self.0.lock().unwrap().sent_pushes.push(*push.uuid());
+            Ok(())
+        }
+
+        async fn retreive_subscribers(
+            &self,
+            quiz_id: &Uuid,
+        ) -> Result<Vec<i64>, Box<dyn Error + Send + Sync>> {
+            let state = self.0.lock().unwrap();
+            Ok(state.subscribers.get(quiz_id).cloned().unwrap_or_default())
+        }
+    }
+
+    impl Attempts for FakeStore {
+        async fn count_attempts(
+            &self,
+            _user_id: i64,
+            _quiz_id: &Uuid,
+        ) -> Result<i64, Box<dyn Error + Send + Sync>> {
+            Ok(0)
+        }
+
+        async fn start_attempt(
+            &self,
+            _user_id: i64,
+            _quiz_id: &Uuid,
+            _revision: i32,
+        ) -> Result<Uuid, Box<dyn Error + Send + Sync>> {
+            Ok(Uuid::nil())
+        }
+
+        async fn finish_attempt(
+            &self,
+            attempt_id: &Uuid,
+            score: f64,
+            max_score: f64,
+            _outcome: &Outcome,
+        ) -> Result<(), Box<dyn Error + Send + Sync>> {
+            let mut state = self.0.lock().unwrap();
+            state
+                .attempts
+                .retain(|(attempt, _)| attempt.uuid() != attempt_id);
+            state.finished.push((*attempt_id, score, max_score));
+            Ok(())
+        }
+
+        async fn save_progress(
+            &self,
+            _attempt_id: &Uuid,
+            _curr_idx: usize,
+            _progress: &[QuestionProgress],
+        ) -> Result<(), Box<dyn Error + Send + Sync>> {
+            Ok(())
+        }
+
+        async fn retreive_unfinished_attempt(
+            &self,
+            _user_id: i64,
+            _active_since: DateTime<Utc>,
+        ) -> Result<Option<UnfinishedAttempt>, Box<dyn Error + Send + Sync>> {
+            Ok(None)
+        }
+
+        async fn retreive_expired_attempts(
+            &self,
+            inactive_since: DateTime<Utc>,
+        ) -> Result<Vec<UnfinishedAttempt>, Box<dyn Error + Send + Sync>> {
+            let state = self.0.lock().unwrap();
+            Ok(state
+                .attempts
+                .iter()
+                .filter(|(_, active_at)| *active_at <= inactive_since)
+                .map(|(attempt, _)| attempt.clone())
+                .collect())
+        }
+    }
+
+    impl Revisions for FakeStore {
+        async fn begin_edit_session(
+            &self,
+            _id: impl Into<String>,
+        ) -> Result<(), Box<dyn Error + Send + Sync>> {
+            Ok(())
+        }
+
+        async fn retreive_revisions(
+            &self,
+            _id: impl Into<String>,
+        ) -> Result<Vec<Revision>, Box<dyn Error + Send + Sync>> {
+            Ok(vec![])
+        }
+
+        async fn retreive_quiz_at(
+            &self,
+            id: impl Into<String>,
+            revision: i32,
+        ) -> Result<Option<Quiz>, Box<dyn Error + Send + Sync>> {
+            let state = self.0.lock().unwrap();
+            Ok(state.revisions.get(&(id.into(), revision)).cloned())
+        }
+
+        async fn rollback(
+            &self,
+            _id: impl Into<String>,
+            _revision: i32,
+        ) -> Result<String, Box<dyn Error + Send + Sync>> {
+            Ok(String::new())
+        }
+    }
+
+    impl Trash for FakeStore {
+        async fn retreive_trashed_quiz_names_page(
+            &self,
+            _query: &str,
+            _page: i64,
+        ) -> Result<Page, Box<dyn Error + Send + Sync>> {
+            Ok(Page::new(vec![], 0))
+        }
+
+        async fn retreive_trashed_question_names_page(
+            &self,
+            _id_quiz: impl Into<String>,
+            _query: &str,
+            _page: i64,
+        ) -> Result<Page, Box<dyn Error + Send + Sync>> {
+            Ok(Page::new(vec![], 0))
+        }
+
+        async fn is_quiz_trashed(
+            &self,
+            _id: impl Into<String>,
+        ) -> Result<bool, Box<dyn Error + Send + Sync>> {
+            Ok(false)
+        }
+
+        async fn restore_quiz(
+            &self,
+            _id: impl Into<String>,
+        ) -> Result<String, Box<dyn Error + Send + Sync>> {
+            Ok(String::new())
+        }
+
+        async fn restore_question(
+            &self,
+            _quiz_id: impl Into<String>,
+            _question_id: impl Into<String>,
+        ) -> Result<String, Box<dyn Error + Send + Sync>> {
+            Ok(String::new())
+        }
+
+        async fn purge_trash(
+            &self,
+            deleted_before: DateTime<Utc>,
+        ) -> Result<u64, Box<dyn Error + Send + Sync>> {
+            self.0.lock().unwrap().purged_before = Some(deleted_before);
+            Ok(0)
+        }
+    }
+
+    fn at(text: &str) -> DateTime<Utc> {
+        parse_datetime(text).unwrap()
+    }
+
+    fn scheduler(
+        now: &str,
+        state: FakeState,
+        notifier: FakeNotifier,
+    ) -> (
+        Scheduler<FakeClock, FakeStore, FakeNotifier>,
+        Arc<FakeStore>,
+    ) {
+        let store = Arc::new(FakeStore(Mutex::new(state)));
+        (
+            Scheduler::new(FakeClock(at(now)), store.clone(), notifier),
+            store,
+        )
+    }
+
+    fn sent_to(notifier: &FakeNotifier) -> Vec<i64> {
+        notifier
+            .sent
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(chat_id, _)| *chat_id)
+            .collect()
+    }
+
+    fn quiz(questions: usize) -> Quiz {
+        Quiz::new(
+            "Capitals".to_owned(),
+            "Geography".to_owned(),
+            "author".to_owned(),
+            Some(
+                (0..questions)
+                    .map(|i| {
+                        Question::new(
+                            format!("Question {}", i),
+                            Some(vec![
+                                Answer::new("Yes".to_owned(), true),
+                                Answer::new("No".to_owned(), false),
+                            ]),
+                        )
+                    })
+                    .collect(),
+            ),
+        )
+    }
+
+    fn push(chat_id: Option<i64>, quiz_id: Uuid, fire_at: &str) -> ScheduledPush {
+        ScheduledPush::retreive(
+            Uuid::new_v4(),
+            quiz_id,
+            "Capitals".to_owned(),
+            "Geography".to_owned(),
+            chat_id,
+            at(fire_at),
+        )
+    }
+
+    #[tokio::test]
+    async fn due_reminder_is_sent_once_and_moved_to_the_next_day() {
+        let berlin: Tz = "Europe/Berlin".parse().unwrap();
+        let eight = NaiveTime::from_hms_opt(8, 0, 0).unwrap();
+        let state = FakeState {
+            reminders: vec![
+                Reminder::new(1, eight, berlin, at("2024-10-26 08:00 UTC+2")),
+                Reminder::new(2, eight, berlin, at("2024-10-27 08:00 UTC+1")),
+            ],
+            ..FakeState::default()
+        };
+        let notifier = FakeNotifier::default();
+        let (scheduler, store) = scheduler("2024-10-26 06:30", state, notifier.clone());
+
+        scheduler.tick().await.unwrap();
+        scheduler.tick().await.unwrap();
+
+        assert_eq!(sent_to(&notifier), [1]);
+        assert!(notifier.sent.lock().unwrap()[0]
+            .1
+            .contains("Daily reminder"));
+        let state = store.0.lock().unwrap();
+        let moved = state.reminders.iter().find(|r| r.user_id() == 1).unwrap();
+        // Summer time ends overnight, the reminder stays at 08:00 local time.
+        assert_eq!(*moved.next_fire_at(), at("2024-10-27 08:00 UTC+1"));
+    }
+
+    #[test]
+    fn daily_fire_follows_daylight_saving_time() {
+        let berlin: Tz = "Europe/Berlin".parse().unwrap();
+        let new_york: Tz = "America/New_York".parse().unwrap();
+        let time = |text| NaiveTime::parse_from_str(text, "%H:%M").unwrap();
+        let cases = [
+            (
+                "same day",
+                "08:00",
+                berlin,
+                "2024-10-26 05:00",
+                "2024-10-26 08:00 UTC+2",
+            ),
+            (
+                "next day",
+                "08:00",
+                berlin,
+                "2024-10-26 06:00",
+                "2024-10-27 08:00 UTC+1",
+            ),
+            (
+                "summer time starts",
+                "08:00",
+                berlin,
+                "2024-03-30 07:00",
+                "2024-03-31 08:00 UTC+2",
+            ),
+            (
+                "skipped time",
+                "02:30",
+                new_york,
+                "2024-03-09 08:00",
+                "2024-03-10 03:30 UTC-4",
+            ),
+            (
+                "repeated time",
+                "01:30",
+                new_york,
+                "2024-11-03 04:00",
+                "2024-11-03 01:30 UTC-4",
+            ),
+        ];
+
+        for (name, local, time_zone, after, expected) in cases {
+            assert_eq!(
+                next_daily_fire(&time(local), &time_zone, at(after)),
+                at(expected),
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn reminder_takes_a_time_zone_name() {
+        let eight = NaiveTime::from_hms_opt(8, 30, 0).unwrap();
+
+        assert_eq!(parse_reminder("08:30"), Some((eight, Tz::UTC)));
+        assert_eq!(
+            parse_reminder("08:30 Europe/Berlin"),
+            Some((eight, Tz::Europe__Berlin))
+        );
+        assert_eq!(parse_reminder("08:30 Mars/Olympus"), None);
+        assert_eq!(parse_reminder("8.30 UTC"), None);
+        assert_eq!(parse_reminder("08:30 UTC extra"), None);
+    }
+
+    #[tokio::test]
+    async fn due_pushes_go_to_their_chat_or_to_the_subscribers() {
+        let quiz_id = Uuid::new_v4();
+        let to_chat = push(Some(-100), quiz_id, "2024-10-20 09:00");
+        let to_subscribers = push(None, quiz_id, "2024-10-20 10:00");
+        let later = push(Some(-200), quiz_id, "2024-10-21 10:00");
+        let state = FakeState {
+            pushes: vec![to_chat.clone(), to_subscribers.clone(), later],
+            subscribers: HashMap::from([(quiz_id, vec![1, 2])]),
+            ..FakeState::default()
+        };
+        let notifier = FakeNotifier::default();
+        let (scheduler, store) = scheduler("2024-10-20 10:00", state, notifier.clone());
+
+        scheduler.tick().await.unwrap();
+        scheduler.tick().await.unwrap();
+
+        assert_eq!(sent_to(&notifier), [-100, 1, 2]);
+        let sent = notifier.sent.lock().unwrap();
+        assert!(sent
+            .iter()
+            .all(|(_, text)| text.contains("Quiz 'Capitals' is waiting for you!")));
+        // The group is pointed at a group run, subscribers at the menu.
+        assert!(sent[0]
+            .1
+            .ends_with("Use /group Capitals to take it together here."));
+        assert!(sent[1]
+            .1
+            .ends_with("Use /start and choose 'Take a quiz📝' to begin."));
+        assert_eq!(
+            store.0.lock().unwrap().sent_pushes,
+            [*to_chat.uuid(), *to_subscribers.uuid()]
+        );
+    }
+
+    #[tokio::test]
+    async fn unreachable_chat_does_not_hold_back_the_others() {
+        let quiz_id = Uuid::new_v4();
+        let state = FakeState {
+            pushes: vec![push(None, quiz_id, "2024-10-20 09:00")],
+            subscribers: HashMap::from([(quiz_id, vec![1, 2, 3])]),
+            ..FakeState::default()
+        };
+        let notifier = FakeNotifier {
+            unreachable: Some(2),
+            ..FakeNotifier::default()
+        };
+        let (scheduler, store) = scheduler("2024-10-20 10:00", state, notifier.clone());
+
+        scheduler.tick().await.unwrap();
+
+        assert_eq!(sent_to(&notifier), [1, 3]);
+        assert_eq!(store.0.lock().unwrap().sent_pushes.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn expired_attempt_is_closed_against_its_revision() {
+        let expired = Uuid::new_v4();
+        let active = Uuid::new_v4();
+        let answered = vec![QuestionProgress::Answered { points: 1.0 }];
+        let mut state = FakeState {
+            attempts: vec![
+                (
+                    UnfinishedAttempt::retreive(
+                        expired,
+                        1,
+                        "Capitals".to_owned(),
+                        1,
+                        1,
+                        answered.clone(),
+                    ),
+                    at("2024-10-19 09:00"),
+                ),
+                (
+                    UnfinishedAttempt::retreive(active, 2, "Capitals".to_owned(), 2, 1, answered),
+                    at("2024-10-20 09:00"),
+                ),
+            ],
+            ..FakeState::default()
+        };
+        // The quiz got a third question after the expired attempt was started.
+        state.revisions.insert(("Capitals".to_owned(), 1), quiz(2));
+        state.revisions.insert(("Capitals".to_owned(), 2), quiz(3));
+        let notifier = FakeNotifier::default();
+        let (scheduler, store) = scheduler("2024-10-20 10:00", state, notifier.clone());
+
+        scheduler.tick().await.unwrap();
+
+        assert_eq!(store.0.lock().unwrap().finished, [(expired, 1.0, 2.0)]);
+        assert_eq!(
+            *notifier.sent.lock().unwrap(),
+            [(
+                1,
+                "Your unfinished attempt at 'Capitals' expired and was closed.\nYour result is 1/2"
+                    .to_owned()
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn attempt_of_a_missing_quiz_is_left_alone() {
+        let attempt = Uuid::new_v4();
+        let state = FakeState {
+            attempts: vec![(
+                UnfinishedAttempt::retreive(attempt, 1, "Gone".to_owned(), 1, 0, vec![]),
+                at("2024-10-01 09:00"),
+            )],
+            ..FakeState::default()
+        };
+        let notifier = FakeNotifier::default();
+        let (scheduler, store) = scheduler("2024-10-20 10:00", state, notifier.clone());
+
+        scheduler.tick().await.unwrap();
+
+        assert!(sent_to(&notifier).is_empty());
+        let state = store.0.lock().unwrap();
+        assert!(state.finished.is_empty());
+        assert_eq!(state.attempts.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn trash_older_than_the_retention_is_purged() {
+        let (scheduler, store) = scheduler(
+            "2024-10-20 10:00",
+            FakeState::default(),
+            FakeNotifier::default(),
+        );
+
+        scheduler.tick().await.unwrap();
+
+        assert_eq!(
+            store.0.lock().unwrap().purged_before,
+            Some(at("2024-10-20 10:00") - TRASH_RETENTION)
+        );
+    }
+}
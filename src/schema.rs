@@ -8,20 +8,26 @@ use teloxide::{
     dptree::{self, Handler},
     payloads::SendMessageSetters,
     prelude::{DependencyMap, Requester},
-    types::{Message, ReplyMarkup, Update},
+    types::{CallbackQuery, Message, ReplyMarkup, Update},
     Bot,
 };
 use tracing::instrument;
 
 use crate::{
-    commands::{cancel, help, start, Command},
+    commands::{cancel, help, remind, start, Command},
     constructor,
-    database::connection::{Connection, RetreiveQuiz},
-    editor,
-    keyboard::quizes_keyboard,
+    database::connection::{
+        Attempts, Connection, RetreiveQuestion, RetreiveQuiz, Revisions, Trash,
+    },
+    editor, group, inline,
+    keyboard::{GROUP_PREFIX, LIVE_PREFIX},
+    listing,
+    live::{self, JOIN_PAYLOAD},
+    navigation,
+    poll::{self, PollRegistry},
     runner,
-    state::QuizState,
-    HandlerResult, UserDialogue,
+    state::{Listing, QuizState},
+    study, HandlerResult, UserDialogue,
 };
 
 fn schema() -> UpdateHandler<Box<dyn std::error::Error + Send + Sync + 'static>> {
@@ -29,28 +35,79 @@ fn schema() -> UpdateHandler<Box<dyn std::error::Error + Send + Sync + 'static>>
 
     let command_handler = teloxide::filter_command::<Command, _>()
         .branch(case![Command::Help].endpoint(help))
-        .branch(case![Command::Start].endpoint(start))
-        .branch(case![Command::Cancel].endpoint(cancel));
-    // .branch(case![Command::Back].endpoint(back));
+        .branch(
+            case![Command::Start(payload)]
+                .filter(|payload: String| payload.starts_with(JOIN_PAYLOAD))
+                .endpoint(live::join_link),
+        )
+        .branch(
+            case![Command::Start(payload)]
+                .filter(|payload: String| !payload.trim().is_empty())
+                .endpoint(inline::start_from_code::<Connection>),
+        )
+        .branch(case![Command::Start(payload)].endpoint(start::<Connection>))
+        .branch(case![Command::Cancel].endpoint(cancel))
+        .branch(case![Command::Back].endpoint(navigation::back::<Connection>))
+        .branch(case![Command::Undo].endpoint(editor::undo::<Connection>))
+        .branch(case![Command::Study].endpoint(study::study::<Connection>))
+        .branch(case![Command::Subscribe].endpoint(study::subscribe::<Connection>))
+        .branch(case![Command::Remind(args)].endpoint(remind::<Connection>))
+        .branch(case![Command::Group(quiz_name)].endpoint(group::start::<Connection>))
+        .branch(case![Command::Stop].endpoint(group::stop::<Connection>))
+        .branch(case![Command::Host(quiz_name)].endpoint(live::host::<Connection>))
+        .branch(case![Command::Join(code)].endpoint(live::join));
 
     let handler = Update::filter_message()
         .branch(command_handler)
         .branch(case![QuizState::Start].endpoint(choose_what_to_do::<Connection>))
+        .branch(
+            dptree::filter(|state: QuizState| listing::is_listing(&state))
+                .endpoint(listing::search::<Connection>),
+        )
         .branch(constructor_scheme())
         .branch(running_scheme())
         .branch(editor_scheme())
         .endpoint(invalid_state);
 
-    dialogue::enter::<Update, InMemStorage<QuizState>, QuizState, _>()
-        .branch(handler)
-        .branch(callback_query_scheme())
+    // Group and live quiz answers belong to users rather than to the chat dialogue.
+    let group_handler = Update::filter_callback_query()
+        .filter(|q: CallbackQuery| {
+            q.data
+                .as_deref()
+                .is_some_and(|data| data.starts_with(GROUP_PREFIX))
+        })
+        .endpoint(group::take_answer::<Connection>);
+    let live_handler = Update::filter_callback_query()
+        .filter(|q: CallbackQuery| {
+            q.data
+                .as_deref()
+                .is_some_and(|data| data.starts_with(LIVE_PREFIX))
+        })
+        .endpoint(live::take_callback);
+
+    let poll_handler = Update::filter_poll_answer().endpoint(poll::take_poll_answer::<Connection>);
+    let inline_handler = Update::filter_inline_query().endpoint(inline::search::<Connection>);
+
+    dptree::entry()
+        .branch(group_handler)
+        .branch(live_handler)
+        .branch(poll_handler)
+        .branch(inline_handler)
+        .branch(
+            dialogue::enter::<Update, InMemStorage<QuizState>, QuizState, _>()
+                .branch(handler)
+                .branch(callback_query_scheme()),
+        )
 }
 
-async fn choose_what_to_do<QuizRetriever: RetreiveQuiz>(
+async fn choose_what_to_do<
+    Connect: RetreiveQuiz + RetreiveQuestion + Attempts + Revisions + Trash,
+>(
     bot: Bot,
     msg: Message,
     dialogue: UserDialogue,
-    connection: Arc<QuizRetriever>,
+    connection: Arc<Connect>,
+    polls: PollRegistry,
 ) -> HandlerResult {
     match msg.text() {
         Some("Create a new quiz🏗️") => {
@@ -62,29 +119,48 @@ async fn choose_what_to_do<QuizRetriever: RetreiveQuiz>(
             .await?;
             dialogue.update(QuizState::ReceiveQuizName).await?;
         }
+        Some("Import quiz📥") => {
+            bot.send_message(
+                msg.chat.id,
+                "Send a GIFT or Aiken file with the questions. The file name becomes the quiz name.",
+            )
+            .reply_markup(ReplyMarkup::kb_remove())
+            .await?;
+            dialogue.update(QuizState::ImportQuiz).await?;
+        }
         Some("Take a quiz📝") => {
-            let quizes = connection.retreive_all_quiz_names().await?;
-            if quizes.len() < 1 {
-                bot.send_message(msg.chat.id, "No available quizes.")
-                    .await?;
-            } else {
-                bot.send_message(msg.chat.id, "Please, choose available quiz:")
-                    .reply_markup(quizes_keyboard(&quizes))
-                    .await?;
-                dialogue.update(QuizState::Selection).await?;
-            }
+            listing::open_topics(&bot, msg.chat.id, &dialogue, connection.as_ref()).await?;
+        }
+        Some("Trash♻️") => {
+            listing::open(
+                &bot,
+                msg.chat.id,
+                &dialogue,
+                QuizState::TrashSelection {
+                    listing: Listing::default(),
+                },
+                "Choose a quiz to restore.",
+                "The trash is empty.",
+                connection.as_ref(),
+            )
+            .await?;
+        }
+        Some("Continue unfinished quiz⏯️") => {
+            runner::resume(&bot, &msg, &dialogue, connection.as_ref(), &polls).await?;
         }
         Some("Edit an existing quiz✏️️") => {
-            let quizes = connection.retreive_all_quiz_names().await?;
-            if quizes.len() < 1 {
-                bot.send_message(msg.chat.id, "No available quizes.")
-                    .await?;
-            } else {
-                bot.send_message(msg.chat.id, "Select a quiz.")
-                    .reply_markup(quizes_keyboard(&quizes))
-                    .await?;
-                dialogue.update(QuizState::StartSelect).await?;
-            }
+            listing::open(
+                &bot,
+                msg.chat.id,
+                &dialogue,
+                QuizState::StartSelect {
+                    listing: Listing::default(),
+                },
+                "Select a quiz.",
+                "No available quizes.",
+                connection.as_ref(),
+            )
+            .await?;
         }
         other => {
             bot.send_message(msg.chat.id, "Invalid input. Please try again.")
@@ -108,13 +184,18 @@ fn constructor_scheme() -> Handler<
             case![QuizState::ReceiveQuizName]
                 .endpoint(constructor::receive_quiz_description::<Connection>),
         )
+        .branch(case![QuizState::ImportQuiz].endpoint(constructor::receive_import::<Connection>))
         .branch(
             case![QuizState::ReceiveQuizDescription { quiz_name }]
                 .endpoint(constructor::receive_quiz_author),
         )
+        .branch(
+            case![QuizState::ReceiveQuizTags { quiz_info }]
+                .endpoint(constructor::receive_quiz_tags),
+        )
         .branch(
             case![QuizState::ReceiveQuizAuthor { quiz_info }]
-                .endpoint(constructor::receive_new_question::<Connection>),
+                .endpoint(constructor::receive_new_question),
         )
         .branch(
             case![QuizState::ReceiveNewQuestion { quiz_info }]
@@ -147,7 +228,22 @@ fn constructor_scheme() -> Handler<
         )
         .branch(
             case![QuizState::ReceiveAddAnotherQuestion { quiz_info }]
-                .endpoint(constructor::receive_new_question::<Connection>),
+                .endpoint(constructor::receive_new_question),
+        )
+        .branch(
+            case![QuizState::ReviewQuiz { quiz_info }]
+                .endpoint(constructor::review_quiz::<Connection>),
+        )
+        .branch(
+            case![QuizState::ReviewPick { quiz_info, action }].endpoint(constructor::review_pick),
+        )
+        .branch(
+            case![QuizState::ReviewEditText {
+                quiz_info,
+                question_idx,
+                answer_idx
+            }]
+            .endpoint(constructor::review_edit_text),
         )
 }
 
@@ -159,9 +255,10 @@ fn running_scheme() -> teloxide::prelude::Handler<
     DpHandlerDescription,
 > {
     use dptree::case;
-    Update::filter_message()
-        .branch(case![QuizState::Selection].endpoint(runner::selection::<Connection>))
-        .branch(case![QuizState::ReadyToRun { quiz, curr_idx }].endpoint(runner::running_ready))
+    Update::filter_message().branch(
+        case![QuizState::ReadyToRun { quiz, curr_idx }]
+            .endpoint(runner::running_ready::<Connection>),
+    )
 }
 
 #[instrument(level = "debug")]
@@ -173,14 +270,24 @@ fn callback_query_scheme() -> teloxide::prelude::Handler<
 > {
     use dptree::case;
 
-    Update::filter_callback_query().branch(
-        case![QuizState::Running {
-            quiz,
-            curr_idx,
-            score
-        }]
-        .endpoint(runner::take_answer),
-    )
+    Update::filter_callback_query()
+        .branch(
+            dptree::filter(|state: QuizState| listing::is_listing(&state))
+                .endpoint(listing::browse::<Connection>),
+        )
+        .branch(
+            case![QuizState::Running {
+                quiz,
+                curr_idx,
+                progress,
+                attempt
+            }]
+            .endpoint(runner::take_answer::<Connection>),
+        )
+        .branch(
+            case![QuizState::Studying { question, card }]
+                .endpoint(study::take_review::<Connection>),
+        )
 }
 
 #[instrument(level = "debug")]
@@ -192,7 +299,6 @@ fn editor_scheme() -> Handler<
 > {
     use dptree::case;
     Update::filter_message()
-        .branch(case![QuizState::StartSelect].endpoint(editor::select_quiz::<Connection>))
         .branch(
             case![QuizState::HandleQuiz { quiz_name }].endpoint(editor::handle_quiz::<Connection>),
         )
@@ -201,9 +307,20 @@ fn editor_scheme() -> Handler<
             case![QuizState::EditDescription { quiz_name }]
                 .endpoint(editor::edit_description::<Connection>),
         )
+        .branch(case![QuizState::EditTags { quiz_name }].endpoint(editor::edit_tags::<Connection>))
+        .branch(
+            case![QuizState::DeleteQuiz { quiz_name }].endpoint(editor::delete_quiz::<Connection>),
+        )
         .branch(
-            case![QuizState::SelectQuestion { quiz_name }]
-                .endpoint(editor::select_question::<Connection>),
+            case![QuizState::DeleteQuestion {
+                quiz_name,
+                question_name
+            }]
+            .endpoint(editor::delete_question::<Connection>),
+        )
+        .branch(
+            case![QuizState::SelectRevision { quiz_name }]
+                .endpoint(editor::select_revision::<Connection>),
         )
         .branch(
             case![QuizState::HandleQuestion {
@@ -219,6 +336,13 @@ fn editor_scheme() -> Handler<
             }]
             .endpoint(editor::edit_question_text::<Connection>),
         )
+        .branch(
+            case![QuizState::EditQuestionScoring {
+                quiz_name,
+                question_name
+            }]
+            .endpoint(editor::edit_question_scoring::<Connection>),
+        )
         .branch(
             case![QuizState::SelectAnswer {
                 quiz_name,
@@ -265,9 +389,62 @@ fn editor_scheme() -> Handler<
             }]
             .endpoint(editor::editor_add_corectness::<Connection>),
         )
+        .branch(case![QuizState::AddQuestion { quiz_name }].endpoint(editor::editor_add_question))
+        .branch(
+            case![QuizState::AddQuestionReceiveAnswer {
+                quiz_name,
+                text,
+                answers
+            }]
+            .endpoint(editor::editor_add_question_answer),
+        )
+        .branch(
+            case![QuizState::AddQuestionCorectness {
+                quiz_name,
+                text,
+                answers,
+                new_answer
+            }]
+            .endpoint(editor::editor_add_question_corectness),
+        )
+        .branch(
+            case![QuizState::AddQuestionReceiveAnotherAnswer {
+                quiz_name,
+                text,
+                answers
+            }]
+            .endpoint(editor::editor_add_another_answer::<Connection>),
+        )
+        .branch(case![QuizState::EditOpensAt { quiz_name }].endpoint(editor::edit_opens_at))
+        .branch(
+            case![QuizState::EditClosesAt {
+                quiz_name,
+                opens_at
+            }]
+            .endpoint(editor::edit_closes_at),
+        )
+        .branch(
+            case![QuizState::EditMaxAttempts {
+                quiz_name,
+                opens_at,
+                closes_at
+            }]
+            .endpoint(editor::edit_max_attempts::<Connection>),
+        )
+        .branch(case![QuizState::EditPassMark { quiz_name }].endpoint(editor::edit_pass_mark))
+        .branch(
+            case![QuizState::EditGradeBands {
+                quiz_name,
+                pass_mark
+            }]
+            .endpoint(editor::edit_grade_bands::<Connection>),
+        )
+        .branch(
+            case![QuizState::SchedulePushTime { quiz_name }].endpoint(editor::schedule_push_time),
+        )
         .branch(
-            case![QuizState::AddQuestion { quiz_name }]
-                .endpoint(editor::editor_add_question::<Connection>),
+            case![QuizState::SchedulePushTarget { quiz_name, fire_at }]
+                .endpoint(editor::schedule_push_target::<Connection>),
         )
 }
 
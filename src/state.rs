@@ -1,22 +1,56 @@
-use crate::database::quiz::{Answer, Question, Quiz};
+use chrono::{DateTime, Utc};
+use teloxide::types::MessageId;
+use uuid::Uuid;
+
+use crate::database::{
+    attempt::QuestionProgress,
+    grading::PassMark,
+    page::Page,
+    quiz::{Answer, Question, Quiz},
+    study::StudyCard,
+};
 
 #[derive(Debug, Clone)]
 pub struct QuizData {
     pub(crate) quiz_name: String,
     pub(crate) description: String,
     pub(crate) author: String,
+    pub(crate) tags: Vec<String>,
     pub(crate) questions: Vec<Question>,
 }
 
+/// What the author does with the draft item picked in the constructor review.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReviewAction {
+    EditText,
+    ToggleCorrectness,
+    Move,
+    Delete,
+}
+
+/// Page of names offered by an inline keyboard, narrowed down by a search text.
+#[derive(Debug, Clone, Default)]
+pub struct Listing {
+    pub(crate) query: String,
+    pub(crate) page: i64,
+    pub(crate) names: Page,
+    // Message with the keyboard, buttons of older listings are refused.
+    pub(crate) message_id: Option<MessageId>,
+}
+
 #[derive(Debug, Clone, Default)]
 pub enum QuizState {
     #[default]
     Start,
     // PART FOR --- CREATING QUIZ ---
     ReceiveQuizName,
+    ImportQuiz,
     ReceiveQuizDescription {
         quiz_name: String,
     },
+    ReceiveQuizTags {
+        quiz_info: QuizData,
+    },
     ReceiveQuizAuthor {
         quiz_info: QuizData,
     },
@@ -42,9 +76,27 @@ pub enum QuizState {
     ReceiveAddAnotherQuestion {
         quiz_info: QuizData,
     },
+    ReviewQuiz {
+        quiz_info: QuizData,
+    },
+    ReviewPick {
+        quiz_info: QuizData,
+        action: ReviewAction,
+    },
+    ReviewEditText {
+        quiz_info: QuizData,
+        question_idx: usize,
+        answer_idx: Option<usize>,
+    },
 
     // PART FOR --- RUNNING QUIZ ---
-    Selection,
+    TopicSelection {
+        listing: Listing,
+    },
+    Selection {
+        tag: Option<String>,
+        listing: Listing,
+    },
     ReadyToRun {
         quiz: Quiz,
         curr_idx: usize,
@@ -52,14 +104,26 @@ pub enum QuizState {
     Running {
         quiz: Quiz,
         curr_idx: usize,
-        score: u32,
+        progress: Vec<QuestionProgress>,
+        attempt: Uuid,
     },
     Done {
-        score: u32,
+        score: f64,
+    },
+
+    // PART FOR --- STUDYING ---
+    SubscribeSelection {
+        listing: Listing,
+    },
+    Studying {
+        question: Question,
+        card: StudyCard,
     },
 
     // PART FOR --- EDITING ---
-    StartSelect,
+    StartSelect {
+        listing: Listing,
+    },
     HandleQuiz {
         quiz_name: String,
     },
@@ -67,9 +131,16 @@ pub enum QuizState {
         quiz_name: String,
         question_name: String,
     },
-    // DeleteQuiz {
-    //     quiz_name: String,
-    // },
+    DeleteQuiz {
+        quiz_name: String,
+    },
+    TrashSelection {
+        listing: Listing,
+    },
+    QuestionTrashSelection {
+        quiz_name: String,
+        listing: Listing,
+    },
     EditName {
         quiz_name: String,
         // new_name: String,
@@ -78,26 +149,27 @@ pub enum QuizState {
         quiz_name: String,
         // new_description: String,
     },
-    AddQuestion {
+    EditTags {
         quiz_name: String,
     },
-    AddQuestionText {
+    SelectRevision {
+        quiz_name: String,
+    },
+    AddQuestion {
         quiz_name: String,
-        text: String,
-        answers: Vec<Answer>,
     },
-    AddQuestionAnswer {
+    // Question text is in, waiting for an answer to it.
+    AddQuestionReceiveAnswer {
         quiz_name: String,
         text: String,
         answers: Vec<Answer>,
-        new_answer: String,
     },
+    // Waiting for whether `new_answer` is correct.
     AddQuestionCorectness {
         quiz_name: String,
         text: String,
         answers: Vec<Answer>,
         new_answer: String,
-        is_correct: bool,
     },
     AddQuestionReceiveAnotherAnswer {
         quiz_name: String,
@@ -106,7 +178,7 @@ pub enum QuizState {
     },
     SelectQuestion {
         quiz_name: String,
-        // question_name: String,
+        listing: Listing,
     },
     EditQuestionText {
         quiz_name: String,
@@ -117,14 +189,13 @@ pub enum QuizState {
         quiz_name: String,
         question_name: String,
     },
-    AddAnswer {
+    EditQuestionScoring {
         quiz_name: String,
         question_name: String,
     },
-    AddAnswerText {
+    AddAnswer {
         quiz_name: String,
         question_name: String,
-        text: String,
     },
     AddAnswerCorrectness {
         quiz_name: String,
@@ -150,9 +221,30 @@ pub enum QuizState {
         question_name: String,
         answer_name: String,
     },
-    DeleteAnswer {
+    EditOpensAt {
         quiz_name: String,
-        question_name: String,
-        answer_name: String,
+    },
+    EditClosesAt {
+        quiz_name: String,
+        opens_at: Option<DateTime<Utc>>,
+    },
+    EditMaxAttempts {
+        quiz_name: String,
+        opens_at: Option<DateTime<Utc>>,
+        closes_at: Option<DateTime<Utc>>,
+    },
+    EditPassMark {
+        quiz_name: String,
+    },
+    EditGradeBands {
+        quiz_name: String,
+        pass_mark: Option<PassMark>,
+    },
+    SchedulePushTime {
+        quiz_name: String,
+    },
+    SchedulePushTarget {
+        quiz_name: String,
+        fire_at: DateTime<Utc>,
     },
 }
@@ -0,0 +1,201 @@
+use std::sync::Arc;
+
+use chrono::Utc;
+use teloxide::{
+    dispatching::dialogue::GetChatId,
+    payloads::SendMessageSetters,
+    prelude::Requester,
+    types::{CallbackQuery, ChatId, Message, ReplyMarkup},
+    Bot,
+};
+use tracing::instrument;
+
+use crate::{
+    database::{
+        connection::{RetreiveQuestion, RetreiveQuiz, Study, Subscription, Trash},
+        quiz::Question,
+        study::StudyCard,
+    },
+    keyboard::{action_keyboard, answers_keyboard},
+    listing,
+    state::{Listing, QuizState},
+    HandlerResult, UserDialogue,
+};
+
+// SM-2 grades given for a correct and an incorrect answer.
+const CORRECT_QUALITY: u8 = 4;
+const INCORRECT_QUALITY: u8 = 1;
+
+#[instrument(level = "info", skip(connection, bot, dialogue))]
+pub(crate) async fn subscribe<Connect: RetreiveQuiz + RetreiveQuestion + Trash>(
+    bot: Bot,
+    msg: Message,
+    dialogue: UserDialogue,
+    connection: Arc<Connect>,
+) -> HandlerResult {
+    listing::open(
+        &bot,
+        msg.chat.id,
+        &dialogue,
+        QuizState::SubscribeSelection {
+            listing: Listing::default(),
+        },
+        "Choose a quiz to subscribe to. Choosing a subscribed quiz unsubscribes you.",
+        "No available quizes.",
+        connection.as_ref(),
+    )
+    .await
+}
+
+/// Subscribes to the chosen quiz, or unsubscribes if already subscribed.
+pub(crate) async fn toggle<Connect: Subscription>(
+    bot: &Bot,
+    chat_id: ChatId,
+    username: &str,
+    dialogue: &UserDialogue,
+    quiz_name: &str,
+    connection: &Connect,
+) -> HandlerResult {
+    match connection.toggle_subscription(chat_id.0, quiz_name).await? {
+        Some(subscribed) => {
+            let text = if subscribed {
+                format!(
+                    "Subscribed to '{}'. Use /study to review its questions.",
+                    quiz_name
+                )
+            } else {
+                format!("Unsubscribed from '{}'.", quiz_name)
+            };
+            bot.send_message(chat_id, text)
+                .reply_markup(action_keyboard(username))
+                .await?;
+            dialogue.update(QuizState::Start).await?;
+        }
+        None => {
+            bot.send_message(
+                chat_id,
+                format!("Quiz '{}' not found. Try again.", quiz_name),
+            )
+            .await?;
+        }
+    }
+
+    Ok(())
+}
+
+#[instrument(level = "info", skip(connection, bot, dialogue))]
+pub(crate) async fn study<Connect: Study>(
+    bot: Bot,
+    msg: Message,
+    dialogue: UserDialogue,
+    connection: Arc<Connect>,
+) -> HandlerResult {
+    bot.send_message(msg.chat.id, "Let's review!")
+        .reply_markup(ReplyMarkup::kb_remove())
+        .await?;
+    send_due_card(
+        &bot,
+        msg.chat.id,
+        msg.chat.username().unwrap_or_default(),
+        &dialogue,
+        connection.as_ref(),
+    )
+    .await
+}
+
+#[instrument(level = "info", skip(connection, bot, dialogue))]
+pub(crate) async fn take_review<Connect: Study>(
+    bot: Bot,
+    dialogue: UserDialogue,
+    q: CallbackQuery,
+    (question, mut card): (Question, StudyCard),
+    connection: Arc<Connect>,
+) -> HandlerResult {
+    bot.answer_callback_query(&q.id).await?;
+
+    let Some(answer_str) = &q.data else {
+        return Ok(());
+    };
+    let Some(answer) = question
+        .answers()
+        .iter()
+        .find(|answer| answer.text() == answer_str.clone())
+    else {
+        return Ok(());
+    };
+
+    let chat_id = q.chat_id().unwrap();
+
+    let verdict = if answer.is_correct() {
+        card.review(CORRECT_QUALITY, Utc::now());
+        "Answer is correct.✅".to_owned()
+    } else {
+        card.review(INCORRECT_QUALITY, Utc::now());
+        let correct: Vec<String> = question
+            .answers()
+            .iter()
+            .filter(|answer| answer.is_correct())
+            .map(|answer| answer.text())
+            .collect();
+        format!("Answer is incorrect.❌ Correct: {}", correct.join(", "))
+    };
+    connection.save_card(chat_id.0, &card).await?;
+
+    if let Some(message) = &q.message {
+        bot.edit_message_text(
+            chat_id,
+            message.id(),
+            format!(
+                "{}\nGiven answer {}. {}\nNext review in {} day(s).",
+                question.text(),
+                answer_str,
+                verdict,
+                card.interval_days()
+            ),
+        )
+        .await?;
+    }
+
+    send_due_card(
+        &bot,
+        chat_id,
+        q.from.username.as_deref().unwrap_or_default(),
+        &dialogue,
+        connection.as_ref(),
+    )
+    .await
+}
+
+async fn send_due_card<Connect: Study>(
+    bot: &Bot,
+    chat_id: ChatId,
+    username: &str,
+    dialogue: &UserDialogue,
+    connection: &Connect,
+) -> HandlerResult {
+    match connection.retreive_due_card(chat_id.0).await? {
+        Some((question, card)) => {
+            bot.send_message(chat_id, question.text())
+                .reply_markup(answers_keyboard(question.answers()))
+                .await?;
+            dialogue
+                .update(QuizState::Studying { question, card })
+                .await?;
+        }
+        None => {
+            let text = match connection.retreive_next_due_time(chat_id.0).await? {
+                Some(due_at) => format!(
+                    "All caught up! Next review is due at {}.",
+                    due_at.format("%Y-%m-%d %H:%M UTC")
+                ),
+                None => "Nothing to review. Use /subscribe to choose quizes to study.".to_owned(),
+            };
+            bot.send_message(chat_id, text)
+                .reply_markup(action_keyboard(username))
+                .await?;
+            dialogue.update(QuizState::Start).await?;
+        }
+    }
+
+    Ok(())
+}